@@ -4,10 +4,15 @@ pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
     use axum::routing::{get, post};
     use axum::Router;
 
-    let router_plot = Router::new().route(
-        spot::plot::post::PATH,
-        post(spot::plot::post::handler::handler),
-    );
+    let router_plot = Router::new()
+        .route(
+            spot::plot::post::PATH,
+            post(spot::plot::post::handler::handler),
+        )
+        .route(
+            spot::recommend::get::PATH,
+            get(spot::recommend::get::handler::handler),
+        );
 
     let router_track = Router::new().route(
         spot::track::post::PATH,
@@ -19,10 +24,29 @@ pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
         get(spot::price::get::handler::handler),
     );
 
-    let router_normal = Router::new().route(
-        spot::normal::get::PATH,
-        get(spot::normal::get::handler::handler),
-    );
+    let router_market = Router::new()
+        .route(
+            spot::klines::get::PATH,
+            get(spot::klines::get::handler::handler),
+        )
+        .route(
+            spot::ticker::get::PATH,
+            get(spot::ticker::get::handler::handler),
+        )
+        .route(
+            spot::depth::get::PATH,
+            get(spot::depth::get::handler::handler),
+        );
+
+    let router_normal = Router::new()
+        .route(
+            spot::normal::get::PATH,
+            get(spot::normal::get::handler::handler),
+        )
+        .route(
+            spot::symbols::get::PATH,
+            get(spot::symbols::get::handler::handler),
+        );
 
     let router_account = Router::new()
         .route(
@@ -32,6 +56,18 @@ pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
         .route(
             spot::account::commission::post::PATH,
             post(spot::account::commission::post::handler::handler),
+        )
+        .route(
+            spot::account::pnl::post::PATH,
+            post(spot::account::pnl::post::handler::handler),
+        )
+        .route(
+            spot::account::history::status::post::PATH,
+            post(spot::account::history::status::post::handler::handler),
+        )
+        .route(
+            spot::account::history::post::PATH,
+            post(spot::account::history::post::handler::handler),
         );
 
     let router_order = Router::new()
@@ -47,6 +83,10 @@ pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
             spot::order::sell::post::PATH,
             post(spot::order::sell::post::handler::handler),
         )
+        .route(
+            spot::order::execution::post::PATH,
+            post(spot::order::execution::post::handler::handler),
+        )
         .route(
             spot::order::info::post::PATH,
             post(spot::order::info::post::handler::handler),
@@ -56,12 +96,34 @@ pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
             post(spot::order::trades::post::handler::handler),
         );
 
+    let router_risk = Router::new().route(
+        spot::risk::post::PATH,
+        post(spot::risk::post::handler::handler),
+    );
+
+    let router_alert = Router::new()
+        .route(
+            spot::alert::get::PATH,
+            get(spot::alert::get::handler::handler),
+        )
+        .route(
+            spot::alert::post::PATH,
+            post(spot::alert::post::handler::handler),
+        )
+        .route(
+            spot::alert::remove::post::PATH,
+            post(spot::alert::remove::post::handler::handler),
+        );
+
     Router::new()
         .merge(router_account)
+        .merge(router_alert)
         .merge(router_normal)
         .merge(router_track)
         .merge(router_order)
+        .merge(router_risk)
         .merge(router_price)
+        .merge(router_market)
         .merge(router_plot)
         .with_state(state)
 }
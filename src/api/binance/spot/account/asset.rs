@@ -6,6 +6,7 @@ pub mod post {
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
         use crate::services::binance::client_with_sign;
+        use crate::services::binance::limit::{self, weight};
 
         use super::models::{Payload, ResponseBody};
 
@@ -18,7 +19,11 @@ pub mod post {
                 None => None,
             };
 
-            let result = client.user_asset(asset, Some(false), None).await?;
+            let result = limit::request(
+                weight::USER_ASSET,
+                client.user_asset(asset, Some(false), None),
+            )
+            .await?;
 
             Ok(Response::ok(result))
         }
@@ -6,6 +6,7 @@ pub mod post {
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
         use crate::services::binance::client_with_sign;
+        use crate::services::binance::limit::{self, weight};
 
         use super::models::{Payload, ResponseBody};
 
@@ -13,7 +14,8 @@ pub mod post {
         pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
             let client = client_with_sign(p.api_key, p.secret_key)?;
 
-            let result = client.spot_commission(&p.symbol).await?;
+            let result =
+                limit::request(weight::COMMISSION, client.spot_commission(&p.symbol)).await?;
 
             Ok(Response::ok(result))
         }
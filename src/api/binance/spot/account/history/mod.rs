@@ -0,0 +1,81 @@
+pub mod status;
+
+pub mod post {
+    pub const PATH: &str = "/binance/spot/account/history/sync";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::client_with_sign;
+        use crate::services::binance::history::{account_of, discover, spawn};
+        use crate::services::binance::job::JobStatus;
+
+        use super::models::{Payload, ResponseBody};
+
+        /// Starts the sync in the background, its progress is read from the get endpoint
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let account = account_of(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key)?;
+            let store = c.trade_store();
+
+            let running = c
+                .sync_jobs()
+                .jobs()
+                .into_iter()
+                .find(|v| v.status == JobStatus::Running && v.report.account == account);
+            if let Some(job) = running {
+                return Err(Response::bad_request(format!(
+                    "account is syncing in job {}",
+                    job.id
+                )));
+            }
+
+            // Requested symbols, the ones stored for the account and the pairs it holds
+            let stored = store.symbols(&account).await?;
+            if stored.is_empty() && p.symbols.is_empty() {
+                return Err(Response::bad_request(
+                    "symbols required on the first sync, sold out pairs are not discovered".into(),
+                ));
+            }
+
+            let mut symbols = p.symbols;
+            symbols.extend(stored);
+            symbols.extend(discover(&client, c.catalogue()).await?);
+            symbols.sort();
+            symbols.dedup();
+
+            if symbols.is_empty() {
+                return Err(Response::bad_request(
+                    "account has no symbols to sync".into(),
+                ));
+            }
+
+            let job = spawn(c.sync_jobs(), client, store, account, symbols);
+
+            Ok(Response::ok(job))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::history::SyncJob;
+        use crate::services::binance::job::Job;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: String,
+            pub secret_key: String,
+            // Synced besides the stored ones and the pairs of the held assets,
+            // the exchange lists no traded symbols so a sold out pair is only
+            // synced once given here
+            #[serde(default)]
+            pub symbols: Vec<Symbol>,
+        }
+
+        pub type ResponseBody = Job<SyncJob>;
+    }
+}
@@ -1,2 +1,4 @@
 pub mod asset;
 pub mod commission;
+pub mod history;
+pub mod pnl;
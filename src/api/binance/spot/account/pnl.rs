@@ -0,0 +1,126 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/account/pnl";
+
+    pub mod handler {
+        use std::collections::HashMap;
+        use std::str::FromStr;
+
+        use plot::types::Decimal;
+
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::accounting::Ledger;
+        use crate::services::binance::client_with_sign;
+        use crate::services::binance::history::{self, account_of, authenticate};
+        use crate::services::binance::limit::{self, weight};
+
+        use super::models::{Payload, ResponseBody, SymbolReport};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let account = account_of(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key)?;
+            if p.stored {
+                // The store is read without any signed request otherwise
+                authenticate(&client).await?;
+            }
+
+            let method = p.method.unwrap_or_default();
+            let bots = p.bots.unwrap_or_default();
+
+            let mut symbols = HashMap::new();
+            for symbol in p.symbols.iter() {
+                let normal = {
+                    let mut info =
+                        limit::request(weight::EXCHANGE_INFO, client.exchange_info(symbol)).await?;
+                    match info.symbols.pop() {
+                        Some(v) => v,
+                        None => {
+                            return Err(Response::bad_request(format!(
+                                "symbol {} exchange info not found",
+                                symbol
+                            )))
+                        }
+                    }
+                };
+
+                let price = {
+                    let price = limit::request(weight::PRICE, client.price(symbol))
+                        .await?
+                        .price;
+                    Decimal::from_str(&price).ok()
+                };
+
+                // Synced history of the keys when stored, else the whole history
+                let trades = match p.stored {
+                    true => c.trade_store().trades(&account, symbol).await?,
+                    false => history::fetch(&client, symbol).await?,
+                };
+
+                let mut ledger = Ledger::new(
+                    normal.base_asset.clone(),
+                    normal.quote_asset.clone(),
+                    method,
+                );
+                ledger.ingest(trades.iter());
+
+                let mut bot_reports = HashMap::new();
+                for (bot, order_ids) in bots.iter() {
+                    let mut ledger = Ledger::new(
+                        normal.base_asset.clone(),
+                        normal.quote_asset.clone(),
+                        method,
+                    );
+                    ledger.ingest(trades.iter().filter(|v| order_ids.contains(&v.order_id)));
+                    bot_reports.insert(bot.clone(), ledger.report(price));
+                }
+
+                symbols.insert(
+                    symbol.clone(),
+                    SymbolReport {
+                        quote_asset: normal.quote_asset,
+                        total: ledger.report(price),
+                        bots: bot_reports,
+                    },
+                );
+            }
+
+            Ok(Response::ok(ResponseBody { symbols }))
+        }
+    }
+
+    pub mod models {
+        use std::collections::HashMap;
+
+        use binance::types::{Asset, Symbol};
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::accounting::{CostBasis, Report};
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: String,
+            pub secret_key: String,
+            pub symbols: Vec<Symbol>,
+            pub method: Option<CostBasis>,
+            // Read the trades synced for the api key from the local store
+            #[serde(default)]
+            pub stored: bool,
+            // Bot name with the order ids it placed
+            pub bots: Option<HashMap<String, Vec<i64>>>,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub symbols: HashMap<Symbol, SymbolReport>,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct SymbolReport {
+            pub quote_asset: Asset,
+            pub total: Report,
+            pub bots: HashMap<String, Report>,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+pub mod remove;
+
+pub mod get {
+    pub const PATH: &str = "/binance/spot/alert";
+
+    pub mod handler {
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+
+        use super::models::ResponseBody;
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip) -> ResponseResult<ResponseBody> {
+            Ok(Response::ok(c.alerts().alerts()))
+        }
+    }
+
+    pub mod models {
+        use crate::services::binance::alert::Alert;
+
+        pub type ResponseBody = Vec<Alert>;
+    }
+}
+
+pub mod post {
+    pub const PATH: &str = "/binance/spot/alert";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::webhook::check_target;
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c, p))]
+        pub async fn handler(c: Trip, Json(mut p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            p.symbol = p.symbol.to_uppercase();
+
+            // Never a request to the host itself or its private network
+            check_target(&p.webhook.url, c.alerts().private_targets()).await?;
+
+            let symbols = c.catalogue().symbols().await?;
+            if !symbols.iter().any(|v| v.symbol == p.symbol) {
+                return Err(Response::bad_request(format!(
+                    "symbol {} not found",
+                    p.symbol
+                )));
+            }
+
+            let id = c.alerts().add(p).await?;
+
+            Ok(Response::ok(ResponseBody { id }))
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::alert::AlertRule;
+
+        pub type Payload = AlertRule;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub id: u64,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/alert/remove";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            match c.alerts().remove(p.id).await? {
+                Some(v) => Ok(Response::ok(v)),
+                None => Err(Response::bad_request(format!("alert {} not found", p.id))),
+            }
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::alert::Alert;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub id: u64,
+        }
+
+        pub type ResponseBody = Alert;
+    }
+}
@@ -0,0 +1,35 @@
+pub mod get {
+    pub const PATH: &str = "/binance/spot/depth";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::market::Market;
+
+        use super::models::{Params, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let market = Market::new()?;
+            let result = market.depth(&p.symbol, p.limit).await?;
+
+            Ok(Response::ok(result))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::market::Depth;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub symbol: Symbol,
+            pub limit: Option<u16>,
+        }
+
+        pub type ResponseBody = Depth;
+    }
+}
@@ -0,0 +1,41 @@
+pub mod get {
+    pub const PATH: &str = "/binance/spot/klines";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::market::Market;
+
+        use super::models::{Params, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let market = Market::new()?;
+            let result = market
+                .klines_aggregated(&p.symbol, &p.interval, p.start_time, p.end_time, p.limit)
+                .await?;
+
+            Ok(Response::ok(result))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::market::Kline;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub symbol: Symbol,
+            // Native interval such as `1h`, or a custom one such as `90m`
+            pub interval: String,
+            pub start_time: Option<u64>,
+            pub end_time: Option<u64>,
+            pub limit: Option<u16>,
+        }
+
+        pub type ResponseBody = Vec<Kline>;
+    }
+}
@@ -1,6 +1,13 @@
 pub mod account;
+pub mod alert;
+pub mod depth;
+pub mod klines;
 pub mod normal;
 pub mod order;
 pub mod plot;
 pub mod price;
+pub mod recommend;
+pub mod risk;
+pub mod symbols;
+pub mod ticker;
 pub mod track;
@@ -6,27 +6,60 @@ pub mod get {
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
         use crate::services::binance::client;
+        use crate::services::binance::limit::{self, weight};
+        use crate::services::binance::market::{split_symbols, Market};
 
         use super::models::{Params, ResponseBody};
 
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            if let Some(symbols) = p.symbols {
+                let symbols = split_symbols(&symbols);
+                if symbols.is_empty() {
+                    return Err(Response::bad_request("symbols is empty".into()));
+                }
+
+                let info = Market::new()?.exchange_info(&symbols).await?;
+                let result = info
+                    .symbols
+                    .into_iter()
+                    .map(|v| (v.symbol.clone(), v))
+                    .collect();
+
+                return Ok(Response::ok(ResponseBody::Symbols(result)));
+            }
+
+            let symbol = match p.symbol {
+                Some(v) => v,
+                None => return Err(Response::bad_request("symbol or symbols required".into())),
+            };
+
             let client = client()?;
-            let result = client.exchange_info(&p.symbol).await?;
+            let result =
+                limit::request(weight::EXCHANGE_INFO, client.exchange_info(&symbol)).await?;
 
-            Ok(Response::ok(result))
+            Ok(Response::ok(ResponseBody::Info(result)))
         }
     }
 
     pub mod models {
-        use binance::types::{ExchangeInfo, Symbol};
+        use std::collections::BTreeMap;
+
+        use binance::types::{ExchangeInfo, Symbol, SymbolInfo};
         use serde::{Deserialize, Serialize};
 
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Params {
-            pub symbol: Symbol,
+            pub symbol: Option<Symbol>,
+            // Comma separated, answered with one request and keyed by symbol
+            pub symbols: Option<String>,
         }
 
-        pub type ResponseBody = ExchangeInfo;
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[serde(untagged)]
+        pub enum ResponseBody {
+            Info(ExchangeInfo),
+            Symbols(BTreeMap<Symbol, SymbolInfo>),
+        }
     }
 }
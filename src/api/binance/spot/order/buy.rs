@@ -2,45 +2,128 @@ pub mod post {
     pub const PATH: &str = "/binance/spot/order/buy";
 
     pub mod handler {
+        use std::str::FromStr;
+        use std::sync::Arc;
+
         use binance::types::OrderSide;
+        use plot::trade::Trader;
+        use plot::types::Decimal;
 
         use crate::api::http::request::Json;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
-        use crate::services::binance::client_with_sign;
+        use crate::services::binance::execution::{spawn, Execution, Side};
+        use crate::services::binance::history::account_of;
+        use crate::services::binance::limit::{self, weight};
+        use crate::services::binance::notify::notify;
+        use crate::services::binance::validate::OrderTest;
+        use crate::services::binance::{
+            client_with_sign, symbol_info, BinanceSpot, BinanceSpotTest,
+        };
+
+        use super::models::{DryRun, Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let risk = c.risk();
+            let account = p.api_key.clone();
+            let client = client_with_sign(p.api_key, p.secret_key.clone())?;
+
+            let price = limit::request(weight::PRICE, client.price(&p.symbol))
+                .await?
+                .price;
+            let price = Decimal::from_str(&price).unwrap();
+
+            if p.dry_run || p.validate {
+                let mut agent = BinanceSpotTest::with_account(&client, &p.symbol).await?;
+                if p.validate {
+                    let validation = OrderTest::new(account, p.secret_key)?;
+                    agent = agent.with_validation(validation.with_commission(true));
+                }
+                let trades = agent.buy(&price, &p.quote_quantity).await?;
+
+                return Ok(Response::ok(ResponseBody::DryRun(DryRun {
+                    symbol: p.symbol,
+                    price,
+                    commission: agent.commission(),
+                    trades,
+                })));
+            }
+
+            // Child orders pass the risk checks one by one, in the background
+            if let Some(algorithm) = p.algorithm {
+                let normal = symbol_info(&client, &p.symbol).await?;
+                let spot_agent = BinanceSpot::new(normal.clone(), client)
+                    .with_risk(risk, account.clone())
+                    .with_outbox(c.outbox(), account.clone());
+                let execution = Execution::new(spot_agent, normal, algorithm)?;
+                let job = spawn(
+                    c.executions(),
+                    account_of(&account),
+                    execution,
+                    Side::Buy,
+                    price,
+                    p.quote_quantity,
+                );
 
-        use super::models::{Payload, ResponseBody};
+                return Ok(Response::ok(ResponseBody::Execution(job)));
+            }
 
-        #[tracing::instrument(skip(_c))]
-        pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
-            let client = client_with_sign(p.api_key, p.secret_key)?;
+            risk.allow(&account, &p.symbol, &OrderSide::Buy, &p.quote_quantity)?;
 
-            let result = client
-                .spot_market_order_with_quote(
-                    &p.symbol,
-                    OrderSide::Buy,
-                    &p.quote_quantity.to_string(),
-                    None,
-                )
-                .await?;
+            let quantity = p.quote_quantity.to_string();
+            let result =
+                client.spot_market_order_with_quote(&p.symbol, OrderSide::Buy, &quantity, None);
+            let result = limit::order(result).await.map_err(|e| {
+                risk.release(&account, &OrderSide::Buy, &p.quote_quantity);
+                e
+            })?;
+            let client = Arc::new(client);
+            notify(&c.outbox(), &client, &account, &p.symbol, &result, None).await;
 
-            Ok(Response::ok(result))
+            Ok(Response::ok(ResponseBody::Order(result)))
         }
     }
 
     pub mod models {
         use binance::types::{OrderResponseFull, Symbol};
-        use plot::types::Quantity;
+        use plot::trade::Trade;
+        use plot::types::{Decimal, Price, Quantity};
         use serde::{Deserialize, Serialize};
 
+        use crate::services::binance::execution::{Algorithm, ExecutionReport};
+        use crate::services::binance::job::Job;
+
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Payload {
             pub api_key: String,
             pub secret_key: String,
             pub symbol: Symbol,
             pub quote_quantity: Quantity,
+            // Simulate the order at the current price without sending it
+            #[serde(default)]
+            pub dry_run: bool,
+            // Also check the order on the exchange test endpoint, implies dry_run
+            #[serde(default)]
+            pub validate: bool,
+            // Split the order into child orders run as a job, ignored by dry runs
+            pub algorithm: Option<Algorithm>,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[serde(untagged)]
+        pub enum ResponseBody {
+            Order(OrderResponseFull),
+            DryRun(DryRun),
+            Execution(Job<ExecutionReport>),
         }
 
-        pub type ResponseBody = OrderResponseFull;
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct DryRun {
+            pub symbol: Symbol,
+            pub price: Price,
+            pub commission: Decimal,
+            pub trades: Vec<Trade>,
+        }
     }
 }
@@ -0,0 +1,52 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/order/execution";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::client_with_sign;
+        use crate::services::binance::history::{account_of, authenticate};
+
+        use super::models::{Payload, ResponseBody};
+
+        /// Algorithmic orders of the account of the keys
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let account = account_of(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key)?;
+            authenticate(&client).await?;
+
+            let jobs: Vec<_> = c
+                .executions()
+                .jobs()
+                .into_iter()
+                .filter(|v| v.report.account == account)
+                .filter(|v| p.id.is_none_or(|id| v.id == id))
+                .collect();
+
+            if let (Some(id), true) = (p.id, jobs.is_empty()) {
+                return Err(Response::bad_request(format!("execution {} not found", id)));
+            }
+
+            Ok(Response::ok(jobs))
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::execution::ExecutionReport;
+        use crate::services::binance::job::Job;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: String,
+            pub secret_key: String,
+            // Every execution of the account when missing
+            pub id: Option<u64>,
+        }
+
+        pub type ResponseBody = Vec<Job<ExecutionReport>>;
+    }
+}
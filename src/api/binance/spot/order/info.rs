@@ -6,14 +6,17 @@ pub mod post {
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
         use crate::services::binance::client_with_sign;
+        use crate::services::binance::limit::{self, weight};
 
         use super::models::{Payload, ResponseBody};
 
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
             let client = client_with_sign(p.api_key, p.secret_key)?;
-            let order = client.spot_order_info(&p.symbol, p.order_id, None).await?;
-            let trades = client.spot_trade(&p.symbol, p.order_id, None).await?;
+            let order = client.spot_order_info(&p.symbol, p.order_id, None);
+            let order = limit::request(weight::ORDER_INFO, order).await?;
+            let trades = client.spot_trade(&p.symbol, p.order_id, None);
+            let trades = limit::request(weight::MY_TRADES, trades).await?;
 
             Ok(Response::ok(ResponseBody { order, trades }))
         }
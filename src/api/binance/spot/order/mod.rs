@@ -1,4 +1,5 @@
 pub mod buy;
+pub mod execution;
 pub mod info;
 pub mod sell;
 pub mod trades;
@@ -7,53 +8,138 @@ pub mod post {
     pub const PATH: &str = "/binance/spot/order";
 
     pub mod handler {
+        use std::error::Error;
         use std::str::FromStr;
 
-        use plot::trade::Executor;
-        use plot::types::Decimal;
+        use plot::trade::{Executor, Trader};
+        use plot::types::{Decimal, Price};
 
         use crate::api::http::request::Json;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
-        use crate::services::binance::{client_with_sign, BinanceSpot};
+        use crate::services::binance::guard::protect;
+        use crate::services::binance::limit::{self, weight};
+        use crate::services::binance::risk::RiskError;
+        use crate::services::binance::validate::OrderTest;
+        use crate::services::binance::{
+            client_with_sign, symbol_info, BinanceSpot, BinanceSpotTest,
+        };
 
         use super::models::{Order, Payload, ResponseBody};
 
-        #[tracing::instrument(skip(_c))]
-        pub async fn handler(_c: Trip, Json(mut p): Json<Payload>) -> ResponseResult<ResponseBody> {
-            let client = client_with_sign(p.api_key, p.secret_key)?;
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(mut p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let risk = c.risk();
+            let dry_run = p.dry_run || p.validate;
+            if !dry_run && risk.is_killed() {
+                return Err(RiskError::KillSwitch("trading is halted".into()).into());
+            }
+
+            let account = p.api_key.clone();
+            let client = client_with_sign(p.api_key.clone(), p.secret_key.clone())?;
 
             let price = {
-                let price = client.price(&p.symbol).await?.price;
+                let price = limit::request(weight::PRICE, client.price(&p.symbol))
+                    .await?
+                    .price;
                 Decimal::from_str(&price).unwrap()
             };
 
-            let normal = {
-                let mut info = client.exchange_info(&p.symbol).await?;
-                match info.symbols.pop() {
-                    Some(v) => v,
-                    None => return Err(Response::bad_request("exchange info not found".into())),
+            let order = if dry_run {
+                let mut spot_agent = BinanceSpotTest::with_account(&client, &p.symbol).await?;
+                if p.validate {
+                    let validation = OrderTest::new(p.api_key.clone(), p.secret_key.clone())?;
+                    spot_agent = spot_agent.with_validation(validation.with_commission(true));
                 }
+                execute(&spot_agent, &mut p, &price, |_| {}).await?
+            } else {
+                let normal = symbol_info(&client, &p.symbol).await?;
+
+                let spot_agent = BinanceSpot::new(normal, client)
+                    .with_risk(risk, account.clone())
+                    .with_outbox(c.outbox(), account);
+                execute(&spot_agent, &mut p, &price, |v| spot_agent.set_position(v)).await?
             };
 
-            let mut order = Vec::new();
-            let spot_agent = BinanceSpot::new(normal, client);
-            for position in p.positions.iter_mut() {
-                let trades = position.trap(&spot_agent, &price).await.unwrap_or_default();
-                order.push(Order {
-                    order_id: 1,
-                    symbol: p.symbol.clone(),
-                    trades,
-                });
+            // The grid moves once its positions traded at the price
+            if let Some(strategy) = p.strategy.as_mut() {
+                strategy.follow(&price, &mut p.positions).await;
             }
 
             Ok(Response::ok(ResponseBody {
                 positions: p.positions,
+                strategy: p.strategy,
+                guard: p.guard,
                 order,
                 price,
                 symbol: p.symbol,
             }))
         }
+
+        /// Run the guard and the positions of the bot at the price through the trader,
+        /// `at_position` is told the position index before its orders, none for the guard
+        async fn execute<T, F>(
+            spot_agent: &T,
+            p: &mut Payload,
+            price: &Price,
+            at_position: F,
+        ) -> Result<Vec<Order>, Box<dyn Error>>
+        where
+            T: Trader,
+            F: Fn(Option<usize>),
+        {
+            let mut order = Vec::new();
+
+            if let Some(guard) = p.guard.as_mut() {
+                at_position(None);
+                let (positions, strategy) = (&mut p.positions, p.strategy.as_ref());
+
+                // A failed liquidation is retried on the next order, the guard
+                // goes back to the bot with the trigger pending
+                let (trades, error) =
+                    match protect(guard, positions, strategy, spot_agent, price).await {
+                        Ok(v) => (v, None),
+                        Err(e) => {
+                            tracing::warn!("guard liquidation failed: {}", e);
+                            (Vec::new(), Some(e.to_string()))
+                        }
+                    };
+                if !trades.is_empty() || error.is_some() {
+                    order.push(Order {
+                        order_id: 1,
+                        symbol: p.symbol.clone(),
+                        trades,
+                        error,
+                    });
+                }
+            }
+
+            let halted = p.guard.as_ref().is_some_and(|v| v.is_halted());
+            for (i, position) in p.positions.iter_mut().enumerate().filter(|_| !halted) {
+                at_position(Some(i));
+
+                // A refused order leaves the others running, the reason goes with the response
+                let (trades, error) = match position.trap(spot_agent, price).await {
+                    Ok(v) => (v, None),
+                    Err(e) => {
+                        tracing::warn!("position {} not traded: {}", i, e);
+                        (Vec::new(), Some(e.to_string()))
+                    }
+                };
+                if let Some(guard) = p.guard.as_mut() {
+                    guard.record(&trades);
+                }
+
+                order.push(Order {
+                    order_id: 1,
+                    symbol: p.symbol.clone(),
+                    trades,
+                    error,
+                });
+            }
+
+            Ok(order)
+        }
     }
 
     pub mod models {
@@ -61,12 +147,25 @@ pub mod post {
         use plot::{trade::position::Position, trade::Trade, types::Price};
         use serde::{Deserialize, Serialize};
 
+        use crate::services::binance::guard::Guard;
+        use crate::services::binance::strategy::Strategy;
+
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Payload {
             pub api_key: String,
             pub secret_key: String,
             pub symbol: Symbol,
             pub positions: Vec<Position>,
+            // Trailing and infinity strategies move their positions with the price
+            pub strategy: Option<Strategy>,
+            // Stop loss and take profit rules of the bot
+            pub guard: Option<Guard>,
+            // Simulate the orders at the current price without sending them
+            #[serde(default)]
+            pub dry_run: bool,
+            // Also check the orders on the exchange test endpoint, implies dry_run
+            #[serde(default)]
+            pub validate: bool,
         }
 
         #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +173,8 @@ pub mod post {
             pub symbol: Symbol,
             pub price: Price,
             pub positions: Vec<Position>,
+            pub strategy: Option<Strategy>,
+            pub guard: Option<Guard>,
             pub order: Vec<Order>,
         }
 
@@ -82,6 +183,8 @@ pub mod post {
             pub order_id: i64,
             pub symbol: Symbol,
             pub trades: Vec<Trade>,
+            // Why the position did not trade, such as a risk limit or a symbol filter
+            pub error: Option<String>,
         }
     }
 }
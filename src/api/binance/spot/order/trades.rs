@@ -6,23 +6,23 @@ pub mod post {
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
         use crate::services::binance::client_with_sign;
+        use crate::services::binance::limit::{self, weight};
 
         use super::models::{Payload, ResponseBody};
 
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
             let client = client_with_sign(p.api_key, p.secret_key)?;
-            let result = client
-                .spot_trades(
-                    &p.symbol,
-                    p.order_id,
-                    p.start_time,
-                    p.end_time,
-                    p.from_id,
-                    p.limit,
-                    None,
-                )
-                .await?;
+            let result = client.spot_trades(
+                &p.symbol,
+                p.order_id,
+                p.start_time,
+                p.end_time,
+                p.from_id,
+                p.limit,
+                None,
+            );
+            let result = limit::request(weight::MY_TRADES, result).await?;
 
             Ok(Response::ok(result))
         }
@@ -4,7 +4,6 @@ pub mod post {
     pub mod handler {
         use std::str::FromStr;
 
-        use plot::strategy::Strategy;
         use plot::trade::evaluate::Evaluater;
         use plot::types::Decimal;
 
@@ -12,6 +11,7 @@ pub mod post {
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
 
+        use crate::services::binance::limit::{self, weight};
         use crate::services::binance::{client, BinanceSpotTest};
 
         use super::models::{Analyzer, Payload, ResponseBody};
@@ -20,7 +20,8 @@ pub mod post {
         pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
             let client = client()?;
             let normal = {
-                let mut info = client.exchange_info(&p.symbol).await?;
+                let mut info =
+                    limit::request(weight::EXCHANGE_INFO, client.exchange_info(&p.symbol)).await?;
                 match info.symbols.pop() {
                     Some(v) => v,
                     None => return Err(Response::bad_request("exchange info not found".into())),
@@ -29,18 +30,7 @@ pub mod post {
 
             let commission = p.commission.unwrap_or(Decimal::from_str("0.001").unwrap());
 
-            let positions = {
-                let mut positions = Vec::new();
-                if let Some(grid) = p.grid {
-                    positions = grid.assign_position()
-                }
-
-                if let Some(grid) = p.grid_percent {
-                    positions = grid.assign_position()
-                }
-
-                positions
-            };
+            let positions = p.strategy.positions();
 
             let analyzer = {
                 let mut analyzer = Vec::new();
@@ -69,18 +59,18 @@ pub mod post {
     pub mod models {
         use binance::types::Symbol;
         use plot::{
-            strategy::{grid::Grid, grid_percent::GridPercent},
             trade::{evaluate::Evaluate, position::Position, Trade},
             types::Decimal,
         };
         use serde::{Deserialize, Serialize};
 
+        use crate::services::binance::strategy::Strategy;
+
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Payload {
             pub symbol: Symbol,
             pub commission: Option<Decimal>,
-            pub grid: Option<Grid>,
-            pub grid_percent: Option<GridPercent>,
+            pub strategy: Strategy,
         }
 
         #[derive(Debug, Clone, Serialize, Deserialize)]
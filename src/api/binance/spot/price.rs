@@ -2,37 +2,68 @@ pub mod get {
     pub const PATH: &str = "/binance/spot/price";
 
     pub mod handler {
+        use std::collections::BTreeMap;
+
         use crate::api::http::request::Query;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
         use crate::services::binance::client;
+        use crate::services::binance::limit::{self, weight};
+        use crate::services::binance::market::{split_symbols, Market};
 
         use super::models::{Params, ResponseBody};
 
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Query(q): Query<Params>) -> ResponseResult<ResponseBody> {
-            let client = client()?;
+            if let Some(symbols) = q.symbols {
+                let symbols = split_symbols(&symbols);
+                if symbols.is_empty() {
+                    return Err(Response::bad_request("symbols is empty".into()));
+                }
+
+                let prices = Market::new()?.prices(&symbols).await?;
+                let result: BTreeMap<_, _> =
+                    prices.into_iter().map(|v| (v.symbol.clone(), v)).collect();
+
+                if let Some(v) = symbols.iter().find(|v| !result.contains_key(*v)) {
+                    return Err(Response::bad_request(format!("symbol {} not found", v)));
+                }
 
+                return Ok(Response::ok(ResponseBody::Symbols(result)));
+            }
+
+            let client = client()?;
             let result = match q.symbol {
                 Some(v) => {
-                    vec![client.price(&v).await?]
+                    vec![limit::request(weight::PRICE, client.price(&v)).await?]
                 }
-                None => client.prices(None).await?,
+                None => limit::request(weight::PRICES, client.prices(None)).await?,
             };
 
-            Ok(Response::ok(result))
+            Ok(Response::ok(ResponseBody::Prices(result)))
         }
     }
 
     pub mod models {
+        use std::collections::BTreeMap;
+
         use binance::types::{Symbol, SymbolPrice};
         use serde::{Deserialize, Serialize};
 
+        use crate::services::binance::market::TickerPrice;
+
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Params {
             pub symbol: Option<Symbol>,
+            // Comma separated, answered with one request and keyed by symbol
+            pub symbols: Option<String>,
         }
 
-        pub type ResponseBody = Vec<SymbolPrice>;
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[serde(untagged)]
+        pub enum ResponseBody {
+            Prices(Vec<SymbolPrice>),
+            Symbols(BTreeMap<Symbol, TickerPrice>),
+        }
     }
 }
@@ -0,0 +1,83 @@
+pub mod get {
+    pub const PATH: &str = "/binance/spot/plot/recommend";
+
+    pub mod handler {
+        use std::str::FromStr;
+
+        use plot::types::Decimal;
+
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::client;
+        use crate::services::binance::limit::{self, weight};
+        use crate::services::binance::market::{interval_millis, Market};
+        use crate::services::binance::recommend::recommend;
+
+        use super::models::{Params, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let client = client()?;
+            let normal = {
+                let mut info =
+                    limit::request(weight::EXCHANGE_INFO, client.exchange_info(&p.symbol)).await?;
+                match info.symbols.pop() {
+                    Some(v) => v,
+                    None => {
+                        return Err(Response::bad_request(format!(
+                            "symbol {} exchange info not found",
+                            p.symbol
+                        )))
+                    }
+                }
+            };
+
+            let interval = p.interval.unwrap_or("1h".into());
+            let interval_millis = match interval_millis(&interval) {
+                Ok(v) => v,
+                Err(e) => return Err(Response::bad_request(e.to_string())),
+            };
+
+            let klines = Market::new()?
+                .klines_aggregated(&p.symbol, &interval, None, None, p.limit.or(Some(168)))
+                .await?;
+
+            let commission = p.commission.unwrap_or(Decimal::from_str("0.001").unwrap());
+            let min_profit = p.min_profit.unwrap_or(commission);
+
+            let result = recommend(
+                &normal,
+                &klines,
+                interval_millis,
+                &p.investment,
+                &commission,
+                &min_profit,
+            )?;
+
+            Ok(Response::ok(result))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use plot::types::{Decimal, QuoteQuantity};
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::recommend::Recommendation;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub symbol: Symbol,
+            pub investment: QuoteQuantity,
+            pub interval: Option<String>,
+            // Klines analysed, defaults to a week of hourly klines
+            pub limit: Option<u16>,
+            pub commission: Option<Decimal>,
+            // Profit required on each level after commission
+            pub min_profit: Option<Decimal>,
+        }
+
+        pub type ResponseBody = Recommendation;
+    }
+}
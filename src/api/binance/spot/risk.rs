@@ -0,0 +1,59 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/risk/kill";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c, p))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            // Anyone may halt trading, resuming it takes RISK_TOKEN
+            if !p.enabled {
+                let token = match std::env::var("RISK_TOKEN") {
+                    Ok(v) if !v.is_empty() => v,
+                    _ => return Err(Response::forbidden("RISK_TOKEN is not configured".into())),
+                };
+                let given = p.token.as_deref().unwrap_or_default();
+                if !constant_eq(given.as_bytes(), token.as_bytes()) {
+                    return Err(Response::forbidden("invalid risk token".into()));
+                }
+            }
+
+            let risk = c.risk();
+            risk.kill(p.enabled).await?;
+
+            Ok(Response::ok(ResponseBody {
+                enabled: risk.is_killed(),
+            }))
+        }
+
+        /// Compares every byte, the time taken tells nothing about the token
+        fn constant_eq(a: &[u8], b: &[u8]) -> bool {
+            if a.len() != b.len() {
+                return false;
+            }
+
+            a.iter().zip(b.iter()).fold(0, |v, (a, b)| v | (a ^ b)) == 0
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            // Needed to switch off only
+            pub token: Option<String>,
+            // True blocks every order until switched off again
+            pub enabled: bool,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub enabled: bool,
+        }
+    }
+}
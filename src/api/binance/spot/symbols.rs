@@ -0,0 +1,51 @@
+pub mod get {
+    pub const PATH: &str = "/binance/spot/symbols";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::catalogue::CatalogueQuery;
+
+        use super::models::{Params, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Query(q): Query<Params>) -> ResponseResult<ResponseBody> {
+            let query = CatalogueQuery {
+                search: q.search,
+                base_asset: q.base_asset,
+                quote_asset: q.quote_asset,
+                status: q.status,
+                permission: q.permission,
+                order_type: q.order_type,
+            };
+
+            let result = c.catalogue().search(&query).await?;
+
+            Ok(Response::ok(result))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Asset;
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::catalogue::CatalogueSymbol;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            // Part of the symbol name
+            pub search: Option<String>,
+            pub base_asset: Option<Asset>,
+            pub quote_asset: Option<Asset>,
+            // TRADING, BREAK, ...
+            pub status: Option<String>,
+            // SPOT, MARGIN or a permission group
+            pub permission: Option<String>,
+            // LIMIT, MARKET, ...
+            pub order_type: Option<String>,
+        }
+
+        pub type ResponseBody = Vec<CatalogueSymbol>;
+    }
+}
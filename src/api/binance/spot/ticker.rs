@@ -0,0 +1,34 @@
+pub mod get {
+    pub const PATH: &str = "/binance/spot/ticker/24hr";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::market::Market;
+
+        use super::models::{Params, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let market = Market::new()?;
+            let result = market.ticker_24hr(&p.symbol).await?;
+
+            Ok(Response::ok(result))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::market::Ticker24hr;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub symbol: Symbol,
+        }
+
+        pub type ResponseBody = Ticker24hr;
+    }
+}
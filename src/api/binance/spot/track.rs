@@ -12,6 +12,8 @@ pub mod post {
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
 
+        use crate::services::binance::guard::protect;
+        use crate::services::binance::limit::{self, weight};
         use crate::services::binance::{client, BinanceSpotTest};
 
         use super::models::{Payload, ResponseBody};
@@ -20,7 +22,8 @@ pub mod post {
         pub async fn handler(_c: Trip, Json(mut p): Json<Payload>) -> ResponseResult<ResponseBody> {
             let client = client()?;
             let normal = {
-                let mut info = client.exchange_info(&p.symbol).await?;
+                let mut info =
+                    limit::request(weight::EXCHANGE_INFO, client.exchange_info(&p.symbol)).await?;
                 match info.symbols.pop() {
                     Some(v) => v,
                     None => {
@@ -38,13 +41,31 @@ pub mod post {
 
             let mut trades = Vec::new();
             for price in p.prices.iter() {
-                trades.extend(p.positions.trap(&agent, price).await.unwrap_or_default());
+                if let Some(guard) = p.guard.as_mut() {
+                    let (positions, strategy) = (&mut p.positions, p.strategy.as_ref());
+                    trades.extend(protect(guard, positions, strategy, &agent, price).await?);
+                    if guard.is_halted() {
+                        break;
+                    }
+                }
+
+                let executed = p.positions.trap(&agent, price).await.unwrap_or_default();
+                if let Some(guard) = p.guard.as_mut() {
+                    guard.record(&executed);
+                }
+                trades.extend(executed);
+
+                if let Some(strategy) = p.strategy.as_mut() {
+                    strategy.follow(price, &mut p.positions).await;
+                }
             }
 
             Ok(Response::ok(ResponseBody {
                 evaluate: trades.evaluate().await,
                 trades,
                 positions: p.positions,
+                strategy: p.strategy,
+                guard: p.guard,
             }))
         }
     }
@@ -57,12 +78,19 @@ pub mod post {
         };
         use serde::{Deserialize, Serialize};
 
+        use crate::services::binance::guard::Guard;
+        use crate::services::binance::strategy::Strategy;
+
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Payload {
             pub symbol: Symbol,
             pub commission: Option<Decimal>,
             pub positions: Vec<Position>,
             pub prices: Vec<Price>,
+            // Trailing and infinity strategies move their positions with the price
+            pub strategy: Option<Strategy>,
+            // Stop loss and take profit rules of the bot
+            pub guard: Option<Guard>,
         }
 
         #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +98,8 @@ pub mod post {
             pub evaluate: Evaluate,
             pub trades: Vec<Trade>,
             pub positions: Vec<Position>,
+            pub strategy: Option<Strategy>,
+            pub guard: Option<Guard>,
         }
     }
 }
@@ -1,3 +1,5 @@
+mod pair;
+mod rpc;
 mod token;
 mod uniswap;
 
@@ -6,6 +8,34 @@ pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
 
     axum::Router::new()
         .route(uniswap::get::PATH, get(uniswap::get::handler::handler))
+        .route(
+            uniswap::pools::get::PATH,
+            get(uniswap::pools::get::handler::handler),
+        )
+        .route(
+            uniswap::quote::get::PATH,
+            get(uniswap::quote::get::handler::handler),
+        )
+        .route(
+            uniswap::ticks::get::PATH,
+            get(uniswap::ticks::get::handler::handler),
+        )
+        .route(
+            uniswap::twap::get::PATH,
+            get(uniswap::twap::get::handler::handler),
+        )
+        .route(
+            pair::price::get::PATH,
+            get(pair::price::get::handler::handler),
+        )
+        .route(
+            pair::quote::get::PATH,
+            get(pair::quote::get::handler::handler),
+        )
+        .route(
+            rpc::health::get::PATH,
+            get(rpc::health::get::handler::handler),
+        )
         .route(
             token::erc20::get::PATH,
             get(token::erc20::get::handler::handler),
@@ -0,0 +1,275 @@
+pub mod price {
+    pub mod get {
+        pub const PATH: &str = "/crypto/pair/price";
+
+        pub mod handler {
+            use crate::api::http::request::Query;
+            use crate::api::http::response::{Response, ResponseResult};
+            use crate::api::http::trip::Trip;
+            use crate::services::crypto::block::resolve_block;
+
+            use super::models::{Params, ResponseBody};
+
+            #[tracing::instrument(skip(_c))]
+            pub async fn handler(
+                _c: Trip,
+                Query(p): Query<Params>,
+            ) -> ResponseResult<ResponseBody> {
+                let block = resolve_block(&p.chain, p.block.as_deref(), p.timestamp).await?;
+
+                let factory = p.chain.uniswap_v2_factory_address(p.exchange)?;
+                let factory = p.chain.contract_uniswap_v2_factory(factory)?.at(block);
+
+                let base = p.chain.lookup_or_validate_address(p.base)?;
+                let quote = p.chain.lookup_or_validate_address(p.quote)?;
+
+                let pair = factory.get_pair(&base, &quote).await?;
+                let pair = p.chain.contract_uniswap_v2_pair(pair)?.at(block);
+
+                let price = pair
+                    .price(
+                        &p.chain.contract_erc_20(base)?,
+                        &p.chain.contract_erc_20(quote)?,
+                    )
+                    .await?;
+
+                let result = Response::ok(ResponseBody {
+                    pair: pair.address().clone(),
+                    fee: p.exchange.fee(),
+                    reserve_base: price.reserve_base,
+                    reserve_quote: price.reserve_quote,
+                    block_timestamp_last: price.block_timestamp_last,
+                    base_decimals: price.base_decimals,
+                    quote_decimals: price.quote_decimals,
+                    quote_per_base: price.quote_per_base,
+                    base_per_quote: price.base_per_quote,
+                    block: price.block,
+                });
+
+                Ok(result)
+            }
+        }
+
+        pub mod models {
+            use serde::{Deserialize, Serialize};
+
+            use crate::services::crypto::chain::{BlockChain, Exchange};
+            use crate::services::crypto::contract::types::*;
+
+            #[rustfmt::skip]
+            #[derive(Debug, Clone, Serialize, Deserialize)]
+            pub struct Params {
+                pub chain:     BlockChain,
+                pub exchange:  Exchange,
+                pub base:      String, // Address or Symbol
+                pub quote:     String,
+                // Number, hash or tag, the latest block when missing
+                pub block:     Option<String>,
+                // Unix seconds, read at the last block before it
+                pub timestamp: Option<u64>,
+            }
+
+            #[rustfmt::skip]
+            #[derive(Debug, Clone, Serialize, Deserialize)]
+            pub struct ResponseBody {
+                pub pair:                 Address,
+                // Swap fee in basis points
+                pub fee:                  u32,
+                // Raw units
+                pub reserve_base:         Uint128,
+                pub reserve_quote:        Uint128,
+                pub block_timestamp_last: u32,
+                pub base_decimals:        Uint8,
+                pub quote_decimals:       Uint8,
+                // Whole tokens
+                pub quote_per_base:       String,
+                pub base_per_quote:       String,
+                // Block the values were read at
+                pub block:                u64,
+            }
+        }
+    }
+}
+
+pub mod quote {
+    pub mod get {
+        pub const PATH: &str = "/crypto/pair/quote";
+
+        pub mod handler {
+            use ethers::types::U256;
+
+            use crate::api::http::request::Query;
+            use crate::api::http::response::{Response, ResponseResult};
+            use crate::api::http::trip::Trip;
+            use crate::services::crypto::block::pin_block;
+            use crate::services::crypto::contract::{amounts_in, amounts_out};
+            use crate::services::crypto::contract::{
+                to_raw_amount, to_token_amount, ContractError,
+            };
+            use crate::services::crypto::quote::{parse_path, quote_prices};
+
+            use super::models::{Exact, Hop, Params, ResponseBody};
+
+            #[tracing::instrument(skip(_c))]
+            pub async fn handler(
+                _c: Trip,
+                Query(p): Query<Params>,
+            ) -> ResponseResult<ResponseBody> {
+                let tokens = parse_path(&p.chain, &p.path)?;
+
+                // Every read of the quote sees the same block
+                let block = pin_block(&p.chain, p.block.as_deref(), p.timestamp).await?;
+
+                let factory = p.chain.uniswap_v2_factory_address(p.exchange)?;
+                let factory = p
+                    .chain
+                    .contract_uniswap_v2_factory(factory)?
+                    .at(Some(block));
+
+                let mut hops = Vec::with_capacity(tokens.len() - 1);
+                let mut prices = Vec::with_capacity(tokens.len() - 1);
+                for pair in tokens.windows(2) {
+                    let (token_in, token_out) = (&pair[0], &pair[1]);
+                    let pair = factory.get_pair(token_in, token_out).await?;
+
+                    let price = p
+                        .chain
+                        .contract_uniswap_v2_pair(pair.clone())?
+                        .at(Some(block))
+                        .price(
+                            &p.chain.contract_erc_20(token_in.clone())?,
+                            &p.chain.contract_erc_20(token_out.clone())?,
+                        )
+                        .await?;
+
+                    hops.push(Hop {
+                        pair,
+                        token_in: token_in.clone(),
+                        token_out: token_out.clone(),
+                        reserve_in: price.reserve_base,
+                        reserve_out: price.reserve_quote,
+                        spot_price: price.quote_per_base.clone(),
+                        amount_in: String::new(),
+                        amount_out: String::new(),
+                    });
+                    prices.push(price);
+                }
+
+                let decimals_in = prices[0].base_decimals;
+                let decimals_out = prices[prices.len() - 1].quote_decimals;
+                let reserves: Vec<(U256, U256)> = hops
+                    .iter()
+                    .map(|v| (U256::from(v.reserve_in), U256::from(v.reserve_out)))
+                    .collect();
+
+                let to_u256 = |v: String| {
+                    U256::from_dec_str(&v).map_err(|e| ContractError::Calculation(e.to_string()))
+                };
+                let fee = p.exchange.fee();
+                let amounts = match p.exact {
+                    Exact::Input => {
+                        let amount = to_u256(to_raw_amount(&p.amount, decimals_in)?)?;
+                        amounts_out(amount, &reserves, fee)?
+                    }
+                    Exact::Output => {
+                        let amount = to_u256(to_raw_amount(&p.amount, decimals_out)?)?;
+                        amounts_in(amount, &reserves, fee)?
+                    }
+                };
+
+                for (hop, amount) in hops.iter_mut().zip(amounts.windows(2)) {
+                    hop.amount_in = amount[0].to_string();
+                    hop.amount_out = amount[1].to_string();
+                }
+
+                let amount_in_raw = amounts[0].to_string();
+                let amount_out_raw = amounts[amounts.len() - 1].to_string();
+                let amount_in = to_token_amount(&amount_in_raw, decimals_in)?;
+                let amount_out = to_token_amount(&amount_out_raw, decimals_out)?;
+
+                // Output tokens for one input token, at the reserves and as swapped
+                let spot_prices: Vec<&str> =
+                    prices.iter().map(|v| v.quote_per_base.as_str()).collect();
+                let quoted = quote_prices(&spot_prices, &amount_in, &amount_out)?;
+
+                let result = Response::ok(ResponseBody {
+                    exact: p.exact,
+                    fee,
+                    amount_in,
+                    amount_out,
+                    amount_in_raw,
+                    amount_out_raw,
+                    price: quoted.price,
+                    spot_price: quoted.spot_price,
+                    price_impact: quoted.price_impact,
+                    hops,
+                    block: prices[0].block,
+                });
+
+                Ok(result)
+            }
+        }
+
+        pub mod models {
+            use serde::{Deserialize, Serialize};
+
+            use crate::services::crypto::chain::{BlockChain, Exchange};
+            use crate::services::crypto::contract::types::*;
+
+            pub use crate::api::crypto::uniswap::quote::get::models::Exact;
+
+            #[rustfmt::skip]
+            #[derive(Debug, Clone, Serialize, Deserialize)]
+            pub struct Params {
+                pub chain:     BlockChain,
+                pub exchange:  Exchange,
+                // Comma separated addresses or symbols from the input token
+                pub path:      String,
+                // Whole tokens of the input or the output
+                pub amount:    String,
+                #[serde(default)]
+                pub exact:     Exact,
+                // Number, hash or tag, the latest block when missing
+                pub block:     Option<String>,
+                // Unix seconds, read at the last block before it
+                pub timestamp: Option<u64>,
+            }
+
+            #[rustfmt::skip]
+            #[derive(Debug, Clone, Serialize, Deserialize)]
+            pub struct Hop {
+                pub pair:        Address,
+                pub token_in:    Address,
+                pub token_out:   Address,
+                // Raw units
+                pub reserve_in:  Uint128,
+                pub reserve_out: Uint128,
+                pub amount_in:   Uint256,
+                pub amount_out:  Uint256,
+                // Whole output tokens for one input token at the reserves
+                pub spot_price:  String,
+            }
+
+            #[rustfmt::skip]
+            #[derive(Debug, Clone, Serialize, Deserialize)]
+            pub struct ResponseBody {
+                pub exact:          Exact,
+                // Swap fee of every pair in basis points
+                pub fee:            u32,
+                // Whole tokens
+                pub amount_in:      String,
+                pub amount_out:     String,
+                pub amount_in_raw:  Uint256,
+                pub amount_out_raw: Uint256,
+                // Whole output tokens for one input token, as swapped and at the reserves
+                pub price:          String,
+                pub spot_price:     String,
+                // Fraction of the spot price lost to the reserves and the fees, 0.01 is 1%
+                pub price_impact:   String,
+                pub hops:           Vec<Hop>,
+                // Block the values were read at
+                pub block:          u64,
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+pub mod health {
+    pub mod get {
+        pub const PATH: &str = "/crypto/rpc/health";
+
+        pub mod handler {
+            use std::collections::BTreeMap;
+
+            use crate::api::http::request::Query;
+            use crate::api::http::response::{Response, ResponseResult};
+            use crate::api::http::trip::Trip;
+            use crate::services::crypto::chain::BlockChain;
+
+            use super::models::{Params, ResponseBody};
+
+            #[tracing::instrument(skip(_c))]
+            pub async fn handler(
+                _c: Trip,
+                Query(p): Query<Params>,
+            ) -> ResponseResult<ResponseBody> {
+                let chains = match p.chain {
+                    Some(v) => vec![v],
+                    None => BlockChain::ALL.to_vec(),
+                };
+
+                let mut result = BTreeMap::new();
+                for chain in chains {
+                    let pool = chain.rpc_pool();
+                    let health = match p.probe {
+                        true => pool.probe().await,
+                        false => pool.health(),
+                    };
+
+                    result.insert(format!("{:?}", chain), health);
+                }
+
+                Ok(Response::ok(result))
+            }
+        }
+
+        pub mod models {
+            use std::collections::BTreeMap;
+
+            use serde::{Deserialize, Serialize};
+
+            use crate::services::crypto::chain::BlockChain;
+            use crate::services::crypto::rpc::EndpointHealth;
+
+            #[derive(Debug, Clone, Serialize, Deserialize)]
+            pub struct Params {
+                pub chain: Option<BlockChain>,
+                // Ask every endpoint for the latest block before answering
+                #[serde(default)]
+                pub probe: bool,
+            }
+
+            pub type ResponseBody = BTreeMap<String, Vec<EndpointHealth>>;
+        }
+    }
+}
@@ -5,6 +5,7 @@ pub mod get {
         use crate::api::http::request::Query;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
+        use crate::services::crypto::block::resolve_block;
 
         use super::models::{Params, ResponseBody};
 
@@ -12,14 +13,18 @@ pub mod get {
         pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
             let address = p.chain.lookup_or_validate_address(p.token)?;
 
-            let token = p.chain.contract_erc_20(address.clone())?;
+            let block = resolve_block(&p.chain, p.block.as_deref(), p.timestamp).await?;
+            let token = p.chain.contract_erc_20(address.clone())?.at(block);
+
+            let metadata = token.metadata().await?;
 
             let result = ResponseBody {
                 address,
-                name: token.name().await?,
-                symbol: token.symbol().await?,
-                decimals: token.decimals().await?,
-                total_supply: token.total_supply().await?,
+                name: metadata.name,
+                symbol: metadata.symbol,
+                decimals: metadata.decimals,
+                total_supply: metadata.total_supply,
+                block: metadata.block,
             };
 
             Ok(Response::ok(result))
@@ -35,8 +40,12 @@ pub mod get {
         #[rustfmt::skip]
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Params {
-            pub chain:   BlockChain,
-            pub token:   String,
+            pub chain:     BlockChain,
+            pub token:     String,
+            // Number, hash or tag, the latest block when missing
+            pub block:     Option<String>,
+            // Unix seconds, read at the last block before it
+            pub timestamp: Option<u64>,
         }
 
         #[rustfmt::skip]
@@ -47,6 +56,8 @@ pub mod get {
             pub address:      Address,
             pub decimals:     Uint8,
             pub total_supply: Uint256,
+            // Block the values were read at
+            pub block:        u64,
         }
     }
 }
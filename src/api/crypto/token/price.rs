@@ -5,29 +5,42 @@ pub mod get {
         use crate::api::http::request::Query;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
+        use crate::services::crypto::block::resolve_block;
 
         use super::models::{Params, ResponseBody};
 
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let block = resolve_block(&p.chain, p.block.as_deref(), p.timestamp).await?;
+
             // Factory Contract
             let factory = p.chain.uniswap_v3_factory_address();
-            let factory = p.chain.contract_uniswap_v3_factory(factory)?;
+            let factory = p.chain.contract_uniswap_v3_factory(factory)?.at(block);
 
             let base = p.chain.lookup_or_validate_address(p.base)?;
             let quote = p.chain.lookup_or_validate_address(p.quote)?;
 
             // Pool Contract
-            let pool = factory.get_pool(&base, &quote, p.fee).await?;
-            let pool = p.chain.contract_uniswap_v3_pool(pool)?;
+            let pool = factory.pool(&base, &quote, p.fee).await?;
+            let pool = p.chain.contract_uniswap_v3_pool(pool)?.at(block);
+
+            let price = pool
+                .price(
+                    &p.chain.contract_erc_20(base)?,
+                    &p.chain.contract_erc_20(quote)?,
+                )
+                .await?;
 
             let result = ResponseBody {
-                price: pool
-                    .price(
-                        &p.chain.contract_erc_20(base)?,
-                        &p.chain.contract_erc_20(quote)?,
-                    )
-                    .await?,
+                pool: pool.address().clone(),
+                price: price.quote_per_base.clone(),
+                quote_per_base: price.quote_per_base,
+                base_per_quote: price.base_per_quote,
+                base_decimals: price.base_decimals,
+                quote_decimals: price.quote_decimals,
+                tick: price.tick,
+                sqrt_price_x96: price.sqrt_price_x96,
+                block: price.block,
             };
 
             Ok(Response::ok(result))
@@ -38,21 +51,36 @@ pub mod get {
         use serde::{Deserialize, Serialize};
 
         use crate::services::crypto::chain::BlockChain;
-        use crate::services::crypto::contract::types::{Uint24, Uint256};
+        use crate::services::crypto::contract::types::{Address, Int24, Uint160, Uint24, Uint8};
 
         #[rustfmt::skip]
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Params {
-            pub chain: BlockChain,
-            pub base:  String, // Address or Symbol
-            pub quote: String,
-            pub fee:   Uint24,
+            pub chain:     BlockChain,
+            pub base:      String, // Address or Symbol
+            pub quote:     String,
+            // The most liquid tier when missing
+            pub fee:       Option<Uint24>,
+            // Number, hash or tag, the latest block when missing
+            pub block:     Option<String>,
+            // Unix seconds, read at the last block before it
+            pub timestamp: Option<u64>,
         }
 
         #[rustfmt::skip]
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct ResponseBody {
-            pub price: Uint256,
+            pub pool:           Address,
+            // Quote per base, kept for the older clients
+            pub price:          String,
+            // Whole tokens, normalised for the decimals of both
+            pub quote_per_base: String,
+            pub base_per_quote: String,
+            pub base_decimals:  Uint8,
+            pub quote_decimals: Uint8,
+            pub tick:           Int24,
+            pub sqrt_price_x96: Uint160,
+            pub block:          u64,
         }
     }
 }
@@ -1,3 +1,8 @@
+pub mod pools;
+pub mod quote;
+pub mod ticks;
+pub mod twap;
+
 pub mod get {
     pub const PATH: &str = "/crypto/uniswap";
 
@@ -5,49 +10,54 @@ pub mod get {
         use crate::api::http::request::Query;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
+        use crate::services::crypto::block::resolve_block;
 
         use super::models::{Params, ResponseBody, Slot0};
 
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let block = resolve_block(&p.chain, p.block.as_deref(), p.timestamp).await?;
+
             let factory_contract_address = p.chain.uniswap_v3_factory_address();
             let factory_contract = p
                 .chain
-                .contract_uniswap_v3_factory(factory_contract_address)?;
+                .contract_uniswap_v3_factory(factory_contract_address)?
+                .at(block);
 
             let token_0 = p.chain.lookup_or_validate_address(p.token_0)?;
             let token_1 = p.chain.lookup_or_validate_address(p.token_1)?;
 
             // get pool address by factory contract
-            let pool_contract_address =
-                factory_contract.get_pool(&token_0, &token_1, p.fee).await?;
+            let pool_contract_address = factory_contract.pool(&token_0, &token_1, p.fee).await?;
 
             let pool_contract = p
                 .chain
-                .contract_uniswap_v3_pool(pool_contract_address.clone())?;
+                .contract_uniswap_v3_pool(pool_contract_address.clone())?
+                .at(block);
 
-            let slot_0 = pool_contract.slot_0().await?;
+            let state = pool_contract.state().await?;
 
             let slot_0 = Slot0 {
-                sqrt_price_x96: slot_0.sqrt_price_x96,
-                tick: slot_0.tick,
-                observation_cardinality: slot_0.observation_cardinality,
-                observation_cardinality_next: slot_0.observation_cardinality_next,
-                observation_index: slot_0.observation_index,
-                fee_protocol: slot_0.fee_protocol,
-                unlocked: slot_0.unlocked,
+                sqrt_price_x96: state.slot_0.sqrt_price_x96,
+                tick: state.slot_0.tick,
+                observation_cardinality: state.slot_0.observation_cardinality,
+                observation_cardinality_next: state.slot_0.observation_cardinality_next,
+                observation_index: state.slot_0.observation_index,
+                fee_protocol: state.slot_0.fee_protocol,
+                unlocked: state.slot_0.unlocked,
             };
 
             let result = Response::ok(ResponseBody {
                 address: pool_contract_address,
                 slot_0,
-                factory: pool_contract.factory().await?,
-                fee: pool_contract.fee().await?,
-                fee_growth_global_0x128: pool_contract.fee_growth_global_0x128().await?,
-                fee_growth_global_1x128: pool_contract.fee_growth_global_1x128().await?,
-                liquidity: pool_contract.liquidity().await?,
-                token_0: pool_contract.token_0().await?,
-                token_1: pool_contract.token_1().await?,
+                factory: state.factory,
+                fee: state.fee,
+                fee_growth_global_0x128: state.fee_growth_global_0x128,
+                fee_growth_global_1x128: state.fee_growth_global_1x128,
+                liquidity: state.liquidity,
+                token_0: state.token_0,
+                token_1: state.token_1,
+                block: state.block,
             });
 
             Ok(result)
@@ -63,10 +73,15 @@ pub mod get {
         #[rustfmt::skip]
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Params {
-            pub chain:   BlockChain,
-            pub fee:     Uint24,
-            pub token_0: String,  // Address or Symbol
-            pub token_1: String,  // Address or Symbol
+            pub chain:     BlockChain,
+            // The most liquid tier when missing
+            pub fee:       Option<Uint24>,
+            pub token_0:   String,  // Address or Symbol
+            pub token_1:   String,  // Address or Symbol
+            // Number, hash or tag, the latest block when missing
+            pub block:     Option<String>,
+            // Unix seconds, read at the last block before it
+            pub timestamp: Option<u64>,
         }
 
         #[rustfmt::skip]
@@ -94,6 +109,8 @@ pub mod get {
             pub token_0:                  Address,
             pub token_1:                  Address,
             pub slot_0:                   Slot0,
+            // Block the values were read at
+            pub block:                    u64,
         }
     }
 }
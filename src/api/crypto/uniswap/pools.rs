@@ -0,0 +1,77 @@
+pub mod get {
+    pub const PATH: &str = "/crypto/uniswap/pools";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::crypto::block::resolve_block;
+
+        use super::models::{Params, Pool, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let block = resolve_block(&p.chain, p.block.as_deref(), p.timestamp).await?;
+
+            let factory = p.chain.uniswap_v3_factory_address();
+            let factory = p.chain.contract_uniswap_v3_factory(factory)?.at(block);
+
+            let token_0 = p.chain.lookup_or_validate_address(p.token_0)?;
+            let token_1 = p.chain.lookup_or_validate_address(p.token_1)?;
+
+            let ranked = factory.pools(&token_0, &token_1).await?;
+
+            let result = Response::ok(ResponseBody {
+                pools: ranked
+                    .pools
+                    .into_iter()
+                    .map(|v| Pool {
+                        address: v.address,
+                        fee: v.fee,
+                        liquidity: v.liquidity,
+                    })
+                    .collect(),
+                block: ranked.block,
+            });
+
+            Ok(result)
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::crypto::chain::BlockChain;
+        use crate::services::crypto::contract::types::*;
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub chain:     BlockChain,
+            pub token_0:   String,  // Address or Symbol
+            pub token_1:   String,  // Address or Symbol
+            // Number, hash or tag, the latest block when missing
+            pub block:     Option<String>,
+            // Unix seconds, read at the last block before it
+            pub timestamp: Option<u64>,
+        }
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Pool {
+            pub address:   Address,
+            pub fee:       Uint24,
+            // In range liquidity
+            pub liquidity: Uint128,
+        }
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            // Existing pools of every fee tier, the most liquid first
+            pub pools: Vec<Pool>,
+            // Block the values were read at
+            pub block: u64,
+        }
+    }
+}
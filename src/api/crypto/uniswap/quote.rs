@@ -0,0 +1,198 @@
+pub mod get {
+    pub const PATH: &str = "/crypto/uniswap/quote";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::crypto::block::pin_block;
+        use crate::services::crypto::contract::types::Uint24;
+        use crate::services::crypto::contract::{to_raw_amount, to_token_amount};
+        use crate::services::crypto::quote::{parse_path, quote_prices};
+
+        use super::models::{Exact, Hop, Params, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let tokens = parse_path(&p.chain, &p.path)?;
+
+            let fees: Vec<Option<Uint24>> = match &p.fees {
+                Some(v) => v
+                    .split(',')
+                    .map(|v| v.trim().parse().map(Some))
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| Response::bad_request(format!("invalid fees: {}", e)))?,
+                None => vec![None; tokens.len() - 1],
+            };
+            if fees.len() != tokens.len() - 1 {
+                return Err(Response::bad_request(format!(
+                    "path of {} tokens needs {} fees",
+                    tokens.len(),
+                    tokens.len() - 1
+                )));
+            }
+
+            // Every read of the quote sees the same block
+            let block = pin_block(&p.chain, p.block.as_deref(), p.timestamp).await?;
+
+            let factory = p.chain.uniswap_v3_factory_address();
+            let factory = p
+                .chain
+                .contract_uniswap_v3_factory(factory)?
+                .at(Some(block));
+
+            let mut hops = Vec::with_capacity(fees.len());
+            let mut prices = Vec::with_capacity(fees.len());
+            for (pair, fee) in tokens.windows(2).zip(fees.iter()) {
+                let (token_in, token_out) = (&pair[0], &pair[1]);
+                let (pool, fee) = match fee {
+                    Some(v) => (factory.get_pool(token_in, token_out, *v).await?, *v),
+                    None => {
+                        let pool = factory.best_pool(token_in, token_out).await?;
+                        (pool.address, pool.fee)
+                    }
+                };
+
+                let price = p
+                    .chain
+                    .contract_uniswap_v3_pool(pool.clone())?
+                    .at(Some(block))
+                    .price(
+                        &p.chain.contract_erc_20(token_in.clone())?,
+                        &p.chain.contract_erc_20(token_out.clone())?,
+                    )
+                    .await?;
+
+                hops.push(Hop {
+                    pool,
+                    fee,
+                    token_in: token_in.clone(),
+                    token_out: token_out.clone(),
+                    spot_price: price.quote_per_base.clone(),
+                    sqrt_price_x96_after: String::new(),
+                    ticks_crossed: 0,
+                });
+                prices.push(price);
+            }
+
+            let decimals_in = prices[0].base_decimals;
+            let decimals_out = prices[prices.len() - 1].quote_decimals;
+            let fees: Vec<Uint24> = hops.iter().map(|v| v.fee).collect();
+
+            let quoter = p.chain.uniswap_v3_quoter_address();
+            let quoter = p.chain.contract_uniswap_v3_quoter(quoter)?.at(Some(block));
+            let quote = match p.exact {
+                Exact::Input => {
+                    let amount = to_raw_amount(&p.amount, decimals_in)?;
+                    quoter.quote_exact_input(&tokens, &fees, &amount).await?
+                }
+                Exact::Output => {
+                    let amount = to_raw_amount(&p.amount, decimals_out)?;
+                    quoter.quote_exact_output(&tokens, &fees, &amount).await?
+                }
+            };
+
+            let hops_after = quote
+                .sqrt_price_x96_after
+                .iter()
+                .zip(quote.ticks_crossed.iter());
+            for (hop, (sqrt_price_x96, ticks_crossed)) in hops.iter_mut().zip(hops_after) {
+                hop.sqrt_price_x96_after = sqrt_price_x96.clone();
+                hop.ticks_crossed = *ticks_crossed;
+            }
+
+            let amount_in = to_token_amount(&quote.amount_in, decimals_in)?;
+            let amount_out = to_token_amount(&quote.amount_out, decimals_out)?;
+
+            // Output tokens for one input token, at the pool prices and as swapped
+            let spot_prices: Vec<&str> = prices.iter().map(|v| v.quote_per_base.as_str()).collect();
+            let quoted = quote_prices(&spot_prices, &amount_in, &amount_out)?;
+
+            let result = Response::ok(ResponseBody {
+                exact: p.exact,
+                amount_in,
+                amount_out,
+                amount_in_raw: quote.amount_in,
+                amount_out_raw: quote.amount_out,
+                price: quoted.price,
+                spot_price: quoted.spot_price,
+                price_impact: quoted.price_impact,
+                ticks_crossed: quote.ticks_crossed.iter().sum(),
+                gas_estimate: quote.gas_estimate,
+                hops,
+                block: prices[0].block,
+            });
+
+            Ok(result)
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::crypto::chain::BlockChain;
+        use crate::services::crypto::contract::types::*;
+
+        #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        pub enum Exact {
+            // The amount is spent
+            #[default]
+            Input,
+            // The amount is received
+            Output,
+        }
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub chain:     BlockChain,
+            // Comma separated addresses or symbols from the input token
+            pub path:      String,
+            // Comma separated fee of every hop, the most liquid tier when missing
+            pub fees:      Option<String>,
+            // Whole tokens of the input or the output
+            pub amount:    String,
+            #[serde(default)]
+            pub exact:     Exact,
+            // Number, hash or tag, the latest block when missing
+            pub block:     Option<String>,
+            // Unix seconds, read at the last block before it
+            pub timestamp: Option<u64>,
+        }
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Hop {
+            pub pool:                 Address,
+            pub fee:                  Uint24,
+            pub token_in:             Address,
+            pub token_out:            Address,
+            // Whole output tokens for one input token at slot0
+            pub spot_price:           String,
+            pub sqrt_price_x96_after: Uint160,
+            pub ticks_crossed:        u32,
+        }
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub exact:          Exact,
+            // Whole tokens
+            pub amount_in:      String,
+            pub amount_out:     String,
+            pub amount_in_raw:  Uint256,
+            pub amount_out_raw: Uint256,
+            // Whole output tokens for one input token, as swapped and at slot0
+            pub price:          String,
+            pub spot_price:     String,
+            // Fraction of the spot price lost to the depth and the fees, 0.01 is 1%
+            pub price_impact:   String,
+            pub ticks_crossed:  u32,
+            pub gas_estimate:   Uint256,
+            pub hops:           Vec<Hop>,
+            // Block the values were read at
+            pub block:          u64,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+pub mod get {
+    pub const PATH: &str = "/crypto/uniswap/ticks";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::crypto::block::resolve_block;
+        use crate::services::crypto::contract;
+
+        use super::models::{Depth, LiquidityRange, Params, ResponseBody, Tick};
+
+        /// Percent of the price covered by the ranges when not given
+        const RANGE: f64 = 10.0;
+        /// Percent of the price move of the depth when not given
+        const DEPTH: f64 = 2.0;
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let block = resolve_block(&p.chain, p.block.as_deref(), p.timestamp).await?;
+
+            let factory = p.chain.uniswap_v3_factory_address();
+            let factory = p.chain.contract_uniswap_v3_factory(factory)?.at(block);
+
+            let token_0 = p.chain.lookup_or_validate_address(p.token_0)?;
+            let token_1 = p.chain.lookup_or_validate_address(p.token_1)?;
+
+            let pool = factory.pool(&token_0, &token_1, p.fee).await?;
+            let pool = p.chain.contract_uniswap_v3_pool(pool)?.at(block);
+
+            let distribution = pool
+                .liquidity_distribution(p.range.unwrap_or(RANGE), p.depth.unwrap_or(DEPTH))
+                .await?;
+
+            let depth = |v: &contract::Depth| Depth {
+                percent: v.percent,
+                price: v.price,
+                amount_0: v.amount_0,
+                amount_1: v.amount_1,
+            };
+
+            let result = Response::ok(ResponseBody {
+                address: pool.address().clone(),
+                sqrt_price_x96: distribution.sqrt_price_x96,
+                tick: distribution.tick,
+                tick_spacing: distribution.tick_spacing,
+                liquidity: distribution.liquidity,
+                decimals_0: distribution.decimals_0,
+                decimals_1: distribution.decimals_1,
+                price: distribution.price,
+                ticks: distribution
+                    .ticks
+                    .into_iter()
+                    .map(|v| Tick {
+                        tick: v.tick,
+                        liquidity_gross: v.liquidity_gross,
+                        liquidity_net: v.liquidity_net,
+                    })
+                    .collect(),
+                ranges: distribution
+                    .ranges
+                    .into_iter()
+                    .map(|v| LiquidityRange {
+                        tick_lower: v.tick_lower,
+                        tick_upper: v.tick_upper,
+                        price_lower: v.price_lower,
+                        price_upper: v.price_upper,
+                        liquidity: v.liquidity,
+                        amount_0: v.amount_0,
+                        amount_1: v.amount_1,
+                    })
+                    .collect(),
+                depth_up: depth(&distribution.depth_up),
+                depth_down: depth(&distribution.depth_down),
+                block: distribution.block,
+            });
+
+            Ok(result)
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::crypto::chain::BlockChain;
+        use crate::services::crypto::contract::types::*;
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub chain:     BlockChain,
+            // The most liquid tier when missing
+            pub fee:       Option<Uint24>,
+            pub token_0:   String,  // Address or Symbol
+            pub token_1:   String,  // Address or Symbol
+            // Percent of the price covered by the ranges, 10 when missing
+            pub range:     Option<f64>,
+            // Percent of the price move of the depth, 2 when missing
+            pub depth:     Option<f64>,
+            // Number, hash or tag, the latest block when missing
+            pub block:     Option<String>,
+            // Unix seconds, read at the last block before it
+            pub timestamp: Option<u64>,
+        }
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Tick {
+            pub tick:            Int24,
+            pub liquidity_gross: Uint128,
+            // Liquidity added when the price crosses the tick upwards
+            pub liquidity_net:   i128,
+        }
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct LiquidityRange {
+            pub tick_lower:  Int24,
+            pub tick_upper:  Int24,
+            // Whole token 1 for one whole token 0
+            pub price_lower: f64,
+            pub price_upper: f64,
+            pub liquidity:   Uint128,
+            // Whole tokens held by the range at the current price
+            pub amount_0:    f64,
+            pub amount_1:    f64,
+        }
+
+        // Whole tokens swapped until the price moves by the percent
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Depth {
+            pub percent:  f64,
+            pub price:    f64,
+            pub amount_0: f64,
+            pub amount_1: f64,
+        }
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            // Uniswap Pool Address
+            pub address:        Address,
+            pub sqrt_price_x96: Uint160,
+            pub tick:           Int24,
+            pub tick_spacing:   Int24,
+            // Active liquidity
+            pub liquidity:      Uint128,
+            pub decimals_0:     Uint8,
+            pub decimals_1:     Uint8,
+            // Whole token 1 for one whole token 0
+            pub price:          f64,
+            // Initialized ticks within the range
+            pub ticks:          Vec<Tick>,
+            pub ranges:         Vec<LiquidityRange>,
+            // Token 0 leaves the pool as the price rises, token 1 as it falls
+            pub depth_up:       Depth,
+            pub depth_down:     Depth,
+            // Block the values were read at
+            pub block:          u64,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+pub mod get {
+    pub const PATH: &str = "/crypto/uniswap/twap";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::crypto::block::resolve_block;
+
+        use super::models::{Params, ResponseBody};
+
+        /// Seconds of the window when not given
+        const WINDOW: u32 = 1800;
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let block = resolve_block(&p.chain, p.block.as_deref(), p.timestamp).await?;
+
+            let factory = p.chain.uniswap_v3_factory_address();
+            let factory = p.chain.contract_uniswap_v3_factory(factory)?.at(block);
+
+            let base = p.chain.lookup_or_validate_address(p.base)?;
+            let quote = p.chain.lookup_or_validate_address(p.quote)?;
+
+            let pool = factory.pool(&base, &quote, p.fee).await?;
+            let pool = p.chain.contract_uniswap_v3_pool(pool)?.at(block);
+
+            let twap = pool
+                .twap(
+                    &p.chain.contract_erc_20(base)?,
+                    &p.chain.contract_erc_20(quote)?,
+                    p.window.unwrap_or(WINDOW),
+                )
+                .await?;
+
+            let result = Response::ok(ResponseBody {
+                pool: pool.address().clone(),
+                window: twap.window,
+                tick: twap.tick,
+                spot_tick: twap.spot_tick,
+                sqrt_price_x96: twap.sqrt_price_x96,
+                harmonic_mean_liquidity: twap.harmonic_mean_liquidity,
+                base_decimals: twap.base_decimals,
+                quote_decimals: twap.quote_decimals,
+                quote_per_base: twap.quote_per_base,
+                base_per_quote: twap.base_per_quote,
+                block: twap.block,
+            });
+
+            Ok(result)
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::crypto::chain::BlockChain;
+        use crate::services::crypto::contract::types::*;
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub chain:     BlockChain,
+            pub base:      String, // Address or Symbol
+            pub quote:     String,
+            // The most liquid tier when missing
+            pub fee:       Option<Uint24>,
+            // Seconds before the block, 1800 when missing
+            pub window:    Option<u32>,
+            // Number, hash or tag, the latest block when missing
+            pub block:     Option<String>,
+            // Unix seconds, read at the last block before it
+            pub timestamp: Option<u64>,
+        }
+
+        #[rustfmt::skip]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub pool:                    Address,
+            pub window:                  u32,
+            // Arithmetic mean tick of the window and the tick at the block
+            pub tick:                    Int24,
+            pub spot_tick:               Int24,
+            // At the mean tick
+            pub sqrt_price_x96:          Uint160,
+            pub harmonic_mean_liquidity: Uint128,
+            pub base_decimals:           Uint8,
+            pub quote_decimals:          Uint8,
+            // Whole tokens at the mean tick
+            pub quote_per_base:          String,
+            pub base_per_quote:          String,
+            // Block the window ends at
+            pub block:                   u64,
+        }
+    }
+}
@@ -11,6 +11,8 @@ pub mod get {
         pub async fn handler(c: Trip) -> Response<ResponseBody> {
             Response::ok(ResponseBody {
                 timestamp: c.timestamp_millis(),
+                #[cfg(feature = "service-binance")]
+                binance: crate::services::binance::limit::usage(),
             })
         }
     }
@@ -21,6 +23,8 @@ pub mod get {
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct ResponseBody {
             pub timestamp: u128,
+            #[cfg(feature = "service-binance")]
+            pub binance: crate::services::binance::limit::RateLimitUsage,
         }
     }
 }
@@ -1,20 +1,169 @@
 pub mod trip {
     use std::sync::Arc;
 
+    #[cfg(feature = "service-binance")]
+    use crate::services::binance::alert::{self, AlertEngine};
+    #[cfg(feature = "service-binance")]
+    use crate::services::binance::catalogue::Catalogue;
+    #[cfg(feature = "service-binance")]
+    use crate::services::binance::execution::ExecutionReport;
+    #[cfg(feature = "service-binance")]
+    use crate::services::binance::history::{SyncJob, TradeStore};
+    #[cfg(feature = "service-binance")]
+    use crate::services::binance::job::Jobs;
+    #[cfg(feature = "service-binance")]
+    use crate::services::binance::risk::{RiskConfig, RiskEngine};
+    #[cfg(feature = "service-binance")]
+    use crate::services::webhook::{self, Outbox};
     use crate::time::timestamp;
 
     pub(crate) type Trip = axum::extract::State<Arc<State>>;
 
-    pub struct State {}
+    pub struct State {
+        #[cfg(feature = "service-binance")]
+        trade_store: Arc<TradeStore>,
+        #[cfg(feature = "service-binance")]
+        sync_jobs: Arc<Jobs<SyncJob>>,
+        #[cfg(feature = "service-binance")]
+        executions: Arc<Jobs<ExecutionReport>>,
+        #[cfg(feature = "service-binance")]
+        risk: Arc<RiskEngine>,
+        #[cfg(feature = "service-binance")]
+        catalogue: Catalogue,
+        #[cfg(feature = "service-binance")]
+        alerts: Arc<AlertEngine>,
+        #[cfg(feature = "service-binance")]
+        outbox: Arc<Outbox>,
+    }
 
     impl State {
         pub async fn new() -> Self {
-            Self {}
+            #[allow(unused_variables)]
+            let data_path = std::env::var("DATA_PATH").unwrap_or("data".into());
+
+            #[cfg(feature = "service-binance")]
+            let alerts = {
+                let interval = std::env::var("ALERT_INTERVAL")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(5);
+
+                // Alert webhooks may target the host and its private networks
+                let private_targets = std::env::var("WEBHOOK_ALLOW_PRIVATE")
+                    .is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+
+                // A bad file is left as it is, the alerts are kept in memory only
+                let alerts = match AlertEngine::load(&data_path) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        tracing::error!("alerts not loaded, new ones are not saved: {}", e);
+                        AlertEngine::new()
+                    }
+                };
+                let alerts = alerts.with_private_targets(private_targets);
+                let alerts = Arc::new(alerts);
+                alert::spawn(alerts.clone(), std::time::Duration::from_secs(interval));
+
+                alerts
+            };
+
+            // An unreadable configuration halts trading instead of dropping the limits,
+            // without one the kill switch is kept in DATA_PATH
+            #[cfg(feature = "service-binance")]
+            let risk = {
+                let risk = match std::env::var("RISK_CONFIG") {
+                    Ok(v) => RiskEngine::from_file(v),
+                    Err(_) => RiskEngine::new(RiskConfig::default())
+                        .with_state(std::path::Path::new(&data_path).join("risk.state.json")),
+                };
+
+                Arc::new(match risk {
+                    Ok(v) => v,
+                    Err(e) => {
+                        tracing::error!("risk engine not loaded, trading is halted: {}", e);
+                        let risk = RiskEngine::new(RiskConfig::default());
+                        risk.kill(true).await.ok();
+                        risk
+                    }
+                })
+            };
+
+            // Trade events of the executed orders, delivered from DATA_PATH/outbox
+            #[cfg(feature = "service-binance")]
+            let outbox = {
+                let outbox = Arc::new(match std::env::var("TRADE_WEBHOOKS") {
+                    Ok(v) => match Outbox::from_file(&data_path, &v) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            tracing::error!("trade webhooks {} not loaded: {}", v, e);
+                            Outbox::new(&data_path, Vec::new())
+                        }
+                    },
+                    Err(_) => Outbox::new(&data_path, Vec::new()),
+                });
+                if outbox.is_enabled() {
+                    webhook::spawn(outbox.clone(), std::time::Duration::from_secs(2));
+                }
+
+                outbox
+            };
+
+            Self {
+                #[cfg(feature = "service-binance")]
+                trade_store: Arc::new(TradeStore::new(&data_path)),
+                #[cfg(feature = "service-binance")]
+                sync_jobs: Arc::new(Jobs::new()),
+                #[cfg(feature = "service-binance")]
+                executions: Arc::new(Jobs::new()),
+                #[cfg(feature = "service-binance")]
+                risk,
+                #[cfg(feature = "service-binance")]
+                catalogue: Catalogue::new(std::time::Duration::from_secs(300)),
+                #[cfg(feature = "service-binance")]
+                alerts,
+                #[cfg(feature = "service-binance")]
+                outbox,
+            }
         }
 
         pub fn timestamp_millis(&self) -> u128 {
             timestamp().as_millis()
         }
+
+        #[cfg(feature = "service-binance")]
+        pub fn trade_store(&self) -> Arc<TradeStore> {
+            self.trade_store.clone()
+        }
+
+        #[cfg(feature = "service-binance")]
+        pub fn sync_jobs(&self) -> &Arc<Jobs<SyncJob>> {
+            &self.sync_jobs
+        }
+
+        #[cfg(feature = "service-binance")]
+        pub fn executions(&self) -> &Arc<Jobs<ExecutionReport>> {
+            &self.executions
+        }
+
+        #[cfg(feature = "service-binance")]
+        pub fn risk(&self) -> Arc<RiskEngine> {
+            self.risk.clone()
+        }
+
+        #[cfg(feature = "service-binance")]
+        pub fn catalogue(&self) -> &Catalogue {
+            &self.catalogue
+        }
+
+        #[cfg(feature = "service-binance")]
+        pub fn alerts(&self) -> &AlertEngine {
+            &self.alerts
+        }
+
+        #[cfg(feature = "service-binance")]
+        pub fn outbox(&self) -> Arc<Outbox> {
+            self.outbox.clone()
+        }
     }
 }
 
@@ -56,13 +205,14 @@ pub mod response {
             response
         }
 
-        // pub fn forbidden(message: String) -> Self {
-        //     let mut response = Self::new();
-        //     response.code = 403;
-        //     response.message = Some(message);
+        pub fn forbidden(message: String) -> Self {
+            let mut response = Self::new();
+            response.ok = false;
+            response.code = 403;
+            response.message = Some(message);
 
-        //     response
-        // }
+            response
+        }
 
         pub fn bad_request(message: String) -> Self {
             let mut response = Self::new();
@@ -73,14 +223,23 @@ pub mod response {
             response
         }
 
-        // pub fn internal_error(message: String) -> Self {
-        //     let mut response = Self::new();
-        //     response.ok = false;
-        //     response.code = 500;
-        //     response.message = Some(message);
+        pub fn too_many_requests(message: String) -> Self {
+            let mut response = Self::new();
+            response.ok = false;
+            response.code = 429;
+            response.message = Some(message);
 
-        //     response
-        // }
+            response
+        }
+
+        pub fn internal_error(message: String) -> Self {
+            let mut response = Self::new();
+            response.ok = false;
+            response.code = 500;
+            response.message = Some(message);
+
+            response
+        }
     }
 
     impl<T> IntoResponse for Response<T>
@@ -105,7 +264,37 @@ pub mod response {
             T: Serialize,
         {
             fn from(value: Box<dyn Error>) -> Self {
-                Self::bad_request(value.to_string())
+                #[cfg(feature = "service-binance")]
+                if value.is::<crate::services::binance::limit::RateLimitError>() {
+                    return Self::too_many_requests(value.to_string());
+                }
+
+                #[cfg(feature = "service-binance")]
+                if value.is::<crate::services::binance::risk::RiskError>() {
+                    return Self::forbidden(value.to_string());
+                }
+
+                match value.downcast::<std::io::Error>() {
+                    Ok(v) => Self::from(*v),
+                    Err(v) => Self::bad_request(v.to_string()),
+                }
+            }
+        }
+    }
+
+    mod from_io_error {
+        use super::{Response, Serialize};
+
+        impl<T> From<std::io::Error> for Response<T>
+        where
+            T: Serialize,
+        {
+            /// Storage failures are internal, only a rejected input is the request's fault
+            fn from(value: std::io::Error) -> Self {
+                match value.kind() {
+                    std::io::ErrorKind::InvalidInput => Self::bad_request(value.to_string()),
+                    _ => Self::internal_error(value.to_string()),
+                }
             }
         }
     }
@@ -157,6 +346,22 @@ pub mod response {
             }
         }
     }
+
+    #[cfg(feature = "service-binance")]
+    mod from_binance_risk_error {
+        use crate::services::binance::risk::RiskError;
+
+        use super::{Response, Serialize};
+
+        impl<T> From<RiskError> for Response<T>
+        where
+            T: Serialize,
+        {
+            fn from(value: RiskError) -> Self {
+                Self::forbidden(value.to_string())
+            }
+        }
+    }
 }
 
 pub mod request {
@@ -0,0 +1,324 @@
+use std::collections::{HashMap, VecDeque};
+
+use binance::types::{Asset, Trade};
+use plot::types::{BaseQuantity, Decimal, Price, QuoteQuantity};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum CostBasis {
+    #[default]
+    Fifo,
+    Lifo,
+    Average,
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub method:             CostBasis,
+    pub trades:             usize,
+    pub position:           BaseQuantity,
+    pub cost:               QuoteQuantity,
+    pub average:            Option<Price>,
+    pub price:              Option<Price>,
+    pub realised:           QuoteQuantity,
+    pub unrealised:         Option<QuoteQuantity>,
+    pub fees:               QuoteQuantity,
+    pub fees_other:         HashMap<Asset, Decimal>,
+    pub net:                QuoteQuantity,
+    // Sold quantity that had no matching buy in the history, its proceeds
+    // have no known cost and stay out of the realised
+    pub unmatched:          BaseQuantity,
+    pub unmatched_proceeds: QuoteQuantity,
+}
+
+#[derive(Debug, Clone)]
+struct Lot {
+    quantity: BaseQuantity,
+    cost: QuoteQuantity,
+}
+
+/// Cost basis ledger of one symbol, fed with the account trade history
+pub struct Ledger {
+    base_asset: Asset,
+    quote_asset: Asset,
+    method: CostBasis,
+    lots: VecDeque<Lot>,
+    trades: usize,
+    realised: QuoteQuantity,
+    fees: QuoteQuantity,
+    fees_other: HashMap<Asset, Decimal>,
+    unmatched: BaseQuantity,
+    unmatched_proceeds: QuoteQuantity,
+}
+
+impl Ledger {
+    pub fn new(base_asset: Asset, quote_asset: Asset, method: CostBasis) -> Self {
+        Self {
+            base_asset,
+            quote_asset,
+            method,
+            lots: VecDeque::new(),
+            trades: 0,
+            realised: Decimal::ZERO,
+            fees: Decimal::ZERO,
+            fees_other: HashMap::new(),
+            unmatched: Decimal::ZERO,
+            unmatched_proceeds: Decimal::ZERO,
+        }
+    }
+
+    /// Ingest trades, they are applied in trade id order
+    pub fn ingest<'a, I>(&mut self, trades: I)
+    where
+        I: IntoIterator<Item = &'a Trade>,
+    {
+        let mut trades: Vec<&Trade> = trades.into_iter().collect();
+        trades.sort_by_key(|v| v.id);
+
+        for trade in trades {
+            self.apply(trade);
+        }
+    }
+
+    fn apply(&mut self, trade: &Trade) {
+        let price = dec(&trade.price);
+        let quantity = dec(&trade.qty);
+        let commission = dec(&trade.commission);
+
+        // Commission paid in the base asset reduces the received quantity,
+        // the slice of cost it carried is booked as a fee
+        let (quantity_commission, fee) = if trade.commission_asset == self.base_asset {
+            (commission, commission * price)
+        } else if trade.commission_asset == self.quote_asset {
+            (Decimal::ZERO, commission)
+        } else {
+            *self
+                .fees_other
+                .entry(trade.commission_asset.clone())
+                .or_default() += commission;
+
+            (Decimal::ZERO, Decimal::ZERO)
+        };
+
+        self.trades += 1;
+        self.fees += fee;
+
+        if trade.is_buyer {
+            let quantity = quantity - quantity_commission;
+            self.open(Lot {
+                quantity,
+                cost: quantity * price,
+            });
+        } else {
+            let proceeds = price * quantity;
+            let sold = quantity + quantity_commission;
+            let (cost, unmatched) = self.close(sold);
+
+            let unmatched_proceeds = match sold.is_zero() {
+                true => Decimal::ZERO,
+                false => proceeds * unmatched / sold,
+            };
+            self.unmatched += unmatched;
+            self.unmatched_proceeds += unmatched_proceeds;
+            self.realised += proceeds - unmatched_proceeds - cost;
+        }
+    }
+
+    fn open(&mut self, lot: Lot) {
+        match self.method {
+            CostBasis::Fifo | CostBasis::Lifo => self.lots.push_back(lot),
+            CostBasis::Average => match self.lots.front_mut() {
+                Some(v) => {
+                    v.quantity += lot.quantity;
+                    v.cost += lot.cost;
+                }
+                None => self.lots.push_back(lot),
+            },
+        }
+    }
+
+    /// Remove the quantity from the open lots, returns the cost it carried
+    /// and the quantity no lot was left for
+    fn close(&mut self, mut quantity: BaseQuantity) -> (QuoteQuantity, BaseQuantity) {
+        let mut cost = Decimal::ZERO;
+
+        while quantity > Decimal::ZERO {
+            let lot = match self.method {
+                CostBasis::Lifo => self.lots.back_mut(),
+                CostBasis::Fifo | CostBasis::Average => self.lots.front_mut(),
+            };
+
+            let lot = match lot {
+                Some(v) => v,
+                None => return (cost, quantity),
+            };
+
+            if lot.quantity > quantity {
+                let part = lot.cost * quantity / lot.quantity;
+                lot.quantity -= quantity;
+                lot.cost -= part;
+                cost += part;
+
+                return (cost, Decimal::ZERO);
+            }
+
+            quantity -= lot.quantity;
+            cost += lot.cost;
+
+            match self.method {
+                CostBasis::Lifo => self.lots.pop_back(),
+                CostBasis::Fifo | CostBasis::Average => self.lots.pop_front(),
+            };
+        }
+
+        (cost, Decimal::ZERO)
+    }
+
+    pub fn report(&self, price: Option<Price>) -> Report {
+        let position: BaseQuantity = self.lots.iter().map(|v| v.quantity).sum();
+        let cost: QuoteQuantity = self.lots.iter().map(|v| v.cost).sum();
+
+        let average = match position.is_zero() {
+            true => None,
+            false => Some(cost / position),
+        };
+
+        let unrealised = price.map(|v| v * position - cost);
+
+        Report {
+            method: self.method,
+            trades: self.trades,
+            position,
+            cost,
+            average,
+            price,
+            realised: self.realised,
+            unrealised,
+            fees: self.fees,
+            fees_other: self.fees_other.clone(),
+            net: self.realised + unrealised.unwrap_or_default() - self.fees,
+            unmatched: self.unmatched,
+            unmatched_proceeds: self.unmatched_proceeds,
+        }
+    }
+}
+
+fn dec(value: &String) -> Decimal {
+    use std::str::FromStr;
+
+    Decimal::from_str(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use binance::types::Trade;
+
+    use super::super::fixture::dec;
+    use super::{CostBasis, Ledger};
+
+    fn trade(id: i64, is_buyer: bool, price: &str, qty: &str, fee: &str, asset: &str) -> Trade {
+        let value = serde_json::json!({
+            "symbol": "ETHUSDT",
+            "id": id,
+            "orderId": id,
+            "orderListId": -1,
+            "price": price,
+            "qty": qty,
+            "quoteQty": (dec(price) * dec(qty)).to_string(),
+            "commission": fee,
+            "commissionAsset": asset,
+            "time": 1700000000000u64 + id as u64,
+            "isBuyer": is_buyer,
+            "isMaker": false,
+            "isBestMatch": true
+        });
+
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn history() -> Vec<Trade> {
+        vec![
+            trade(1, true, "100", "1", "0", "USDT"),
+            trade(2, true, "200", "1", "0", "USDT"),
+            trade(3, false, "300", "1", "0.3", "USDT"),
+        ]
+    }
+
+    fn ledger(method: CostBasis) -> Ledger {
+        let mut ledger = Ledger::new("ETH".into(), "USDT".into(), method);
+        ledger.ingest(history().iter());
+
+        ledger
+    }
+
+    #[test]
+    fn test_fifo() {
+        let report = ledger(CostBasis::Fifo).report(Some(dec("250")));
+        assert_eq!(report.realised, dec("200"));
+        assert_eq!(report.position, dec("1"));
+        assert_eq!(report.unrealised, Some(dec("50")));
+        assert_eq!(report.fees, dec("0.3"));
+    }
+
+    #[test]
+    fn test_lifo() {
+        let report = ledger(CostBasis::Lifo).report(Some(dec("250")));
+        assert_eq!(report.realised, dec("100"));
+        assert_eq!(report.unrealised, Some(dec("150")));
+    }
+
+    #[test]
+    fn test_average() {
+        let report = ledger(CostBasis::Average).report(Some(dec("250")));
+        assert_eq!(report.realised, dec("150"));
+        assert_eq!(report.average, Some(dec("150")));
+        assert_eq!(report.unrealised, Some(dec("100")));
+    }
+
+    #[test]
+    fn test_commission_assets() {
+        let mut ledger = Ledger::new("ETH".into(), "USDT".into(), CostBasis::Fifo);
+        ledger.ingest(
+            vec![
+                trade(1, true, "100", "1", "0.01", "ETH"),
+                trade(2, false, "200", "0.5", "0.001", "BNB"),
+            ]
+            .iter(),
+        );
+
+        let report = ledger.report(None);
+        assert_eq!(report.position, dec("0.49"));
+        assert_eq!(report.fees, dec("1"));
+        assert_eq!(report.realised, dec("50"));
+        assert_eq!(report.fees_other.get("BNB"), Some(&dec("0.001")));
+        assert_eq!(report.unrealised, None);
+    }
+
+    #[test]
+    fn test_unmatched_sell() {
+        let mut ledger = Ledger::new("ETH".into(), "USDT".into(), CostBasis::Fifo);
+        ledger.ingest(vec![trade(1, false, "100", "2", "0", "USDT")].iter());
+
+        let report = ledger.report(None);
+        assert_eq!(report.unmatched, dec("2"));
+        assert_eq!(report.unmatched_proceeds, dec("200"));
+        assert_eq!(report.realised, dec("0"));
+
+        // Only the matched part of a sell is realised
+        let mut ledger = Ledger::new("ETH".into(), "USDT".into(), CostBasis::Fifo);
+        ledger.ingest(
+            vec![
+                trade(1, true, "100", "1", "0", "USDT"),
+                trade(2, false, "150", "2", "0", "USDT"),
+            ]
+            .iter(),
+        );
+
+        let report = ledger.report(None);
+        assert_eq!(report.realised, dec("50"));
+        assert_eq!(report.unmatched, dec("1"));
+        assert_eq!(report.unmatched_proceeds, dec("150"));
+        assert_eq!(report.position, dec("0"));
+    }
+}
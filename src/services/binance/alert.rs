@@ -0,0 +1,562 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use binance::types::Symbol;
+use plot::types::{Decimal, Price};
+use serde::{Deserialize, Serialize};
+
+use super::market::Market;
+use crate::services::webhook::{Webhook, WebhookClient};
+use crate::time::timestamp;
+
+#[cfg(feature = "service-crypto")]
+use crate::services::crypto::{chain::BlockChain, contract::types::Uint24};
+
+/// Seconds a rule stays quiet after it triggered when not given
+const COOLDOWN: u64 = 60;
+
+pub const EVENT: &str = "alert";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Condition {
+    // Price moves through the level in either direction
+    Cross {
+        level: Price,
+    },
+    // Price moves by this fraction within the window, 0.05 is 5%
+    Move {
+        change: Decimal,
+        window: u64,
+    },
+    // Binance and the Uniswap V3 pool prices differ by this fraction of the Binance price
+    #[cfg(feature = "service-crypto")]
+    Spread {
+        chain: BlockChain,
+        base: String,
+        quote: String,
+        // The most liquid tier when missing
+        fee: Option<Uint24>,
+        threshold: Decimal,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub symbol: Symbol,
+    pub condition: Condition,
+    pub webhook: Webhook,
+    // Seconds between two notifications of the rule
+    pub cooldown: Option<u64>,
+    // Remove the rule after its first notification
+    #[serde(default)]
+    pub once: bool,
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertEvent {
+    pub id:        u64,
+    pub symbol:    Symbol,
+    pub condition: Condition,
+    pub price:     Price,
+    // Uniswap price of a spread rule
+    pub reference: Option<Decimal>,
+    pub timestamp: u128,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: u64,
+    pub rule: AlertRule,
+    pub triggered: u64,
+    pub last_triggered: Option<u128>,
+    #[serde(skip)]
+    prices: VecDeque<(u128, Price)>,
+}
+
+impl Alert {
+    pub fn new(id: u64, rule: AlertRule) -> Self {
+        Self {
+            id,
+            rule,
+            triggered: 0,
+            last_triggered: None,
+            prices: VecDeque::new(),
+        }
+    }
+
+    /// Feed a price observed at `now` in milliseconds, returns the event when the rule fires
+    pub fn check(
+        &mut self,
+        now: u128,
+        price: &Price,
+        reference: Option<Decimal>,
+    ) -> Option<AlertEvent> {
+        let previous = self.prices.back().map(|v| v.1);
+        self.prices.push_back((now, *price));
+
+        let fired = match &self.rule.condition {
+            Condition::Cross { level } => {
+                self.prices.drain(..self.prices.len() - 1);
+                previous.is_some_and(|v| {
+                    (v < *level && price >= level) || (v > *level && price <= level)
+                })
+            }
+            Condition::Move { change, window } => {
+                let since = now.saturating_sub(*window as u128 * 1000);
+                while self.prices.front().is_some_and(|v| v.0 < since) {
+                    self.prices.pop_front();
+                }
+
+                let moved = self
+                    .prices
+                    .iter()
+                    .any(|(_, v)| !v.is_zero() && ((price - v) / v).abs() >= *change);
+                if moved {
+                    // Measure the next move from here
+                    self.prices.drain(..self.prices.len() - 1);
+                }
+
+                moved
+            }
+            #[cfg(feature = "service-crypto")]
+            Condition::Spread { threshold, .. } => {
+                self.prices.drain(..self.prices.len() - 1);
+                match reference {
+                    Some(v) if !price.is_zero() => ((price - v) / price).abs() >= *threshold,
+                    _ => false,
+                }
+            }
+        };
+
+        let cooldown = self.rule.cooldown.unwrap_or(COOLDOWN) as u128 * 1000;
+        let cooling = self.last_triggered.is_some_and(|v| now < v + cooldown);
+        if !fired || cooling {
+            return None;
+        }
+
+        self.triggered += 1;
+        self.last_triggered = Some(now);
+
+        Some(AlertEvent {
+            id: self.id,
+            symbol: self.rule.symbol.clone(),
+            condition: self.rule.condition.clone(),
+            price: *price,
+            reference,
+            timestamp: now,
+        })
+    }
+}
+
+/// Saved form of an alert, unlike the responses it keeps the webhook secret
+#[derive(Serialize, Deserialize)]
+struct SavedAlert {
+    #[serde(flatten)]
+    alert: Alert,
+    secret: Option<String>,
+}
+
+pub struct AlertEngine {
+    next_id: AtomicU64,
+    alerts: Mutex<BTreeMap<u64, Alert>>,
+    // The rules are saved to this file on every change when set
+    path: Option<PathBuf>,
+    // One save at a time, the last one writes the latest rules
+    saving: tokio::sync::Mutex<()>,
+    // Webhooks may target the host and its private networks
+    private_targets: bool,
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            alerts: Mutex::new(BTreeMap::new()),
+            path: None,
+            saving: tokio::sync::Mutex::new(()),
+            private_targets: false,
+        }
+    }
+
+    /// Rules saved in `alerts.json` of the directory, kept across restarts
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, Box<dyn Error>> {
+        let path = path.into().join("alerts.json");
+
+        let saved: Vec<SavedAlert> = match fs::read(&path) {
+            Ok(v) => serde_json::from_slice(&v)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut alerts = BTreeMap::new();
+        for SavedAlert { mut alert, secret } in saved {
+            alert.rule.webhook.secret = secret;
+            alerts.insert(alert.id, alert);
+        }
+        let next_id = alerts.keys().max().map_or(1, |v| v + 1);
+
+        Ok(Self {
+            next_id: AtomicU64::new(next_id),
+            alerts: Mutex::new(alerts),
+            path: Some(path),
+            saving: tokio::sync::Mutex::new(()),
+            private_targets: false,
+        })
+    }
+
+    /// Let the rules notify a local or private network receiver
+    pub fn with_private_targets(mut self, allow: bool) -> Self {
+        self.private_targets = allow;
+
+        self
+    }
+
+    pub fn private_targets(&self) -> bool {
+        self.private_targets
+    }
+
+    /// Write the current rules, through a temporary file so a crash never
+    /// leaves half of them
+    async fn save(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(v) => v.clone(),
+            None => return Ok(()),
+        };
+
+        let _guard = self.saving.lock().await;
+        let saved: Vec<SavedAlert> = self
+            .alerts()
+            .into_iter()
+            .map(|v| SavedAlert {
+                secret: v.rule.webhook.secret.clone(),
+                alert: v,
+            })
+            .collect();
+        let body = serde_json::to_vec(&saved)?;
+
+        tokio::task::spawn_blocking(move || {
+            if let Some(v) = path.parent() {
+                fs::create_dir_all(v)?;
+            }
+
+            let temp = path.with_extension("tmp");
+            let mut file = File::create(&temp)?;
+            file.write_all(&body)?;
+            file.sync_all()?;
+
+            fs::rename(temp, path)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+
+    pub async fn add(&self, rule: AlertRule) -> io::Result<u64> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        {
+            let mut alerts = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+            alerts.insert(id, Alert::new(id, rule));
+        }
+
+        if let Err(e) = self.save().await {
+            let mut alerts = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+            alerts.remove(&id);
+
+            return Err(e);
+        }
+
+        Ok(id)
+    }
+
+    pub async fn remove(&self, id: u64) -> io::Result<Option<Alert>> {
+        let alert = {
+            let mut alerts = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+            alerts.remove(&id)
+        };
+
+        if alert.is_some() {
+            self.save().await?;
+        }
+
+        Ok(alert)
+    }
+
+    pub fn alerts(&self) -> Vec<Alert> {
+        let alerts = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+        alerts.values().cloned().collect()
+    }
+
+    fn rules(&self) -> Vec<(u64, AlertRule)> {
+        let alerts = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+        alerts.iter().map(|(k, v)| (*k, v.rule.clone())).collect()
+    }
+
+    /// Fetch the prices of every rule and check them once
+    pub async fn evaluate(
+        &self,
+        market: &Market,
+    ) -> Result<Vec<(AlertEvent, Webhook)>, Box<dyn Error>> {
+        let rules = self.rules();
+        if rules.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let symbols: BTreeSet<Symbol> = rules.iter().map(|(_, v)| v.symbol.clone()).collect();
+        let symbols: Vec<Symbol> = symbols.into_iter().collect();
+        let prices: BTreeMap<Symbol, Price> = match market.prices(&symbols).await {
+            Ok(v) => v.into_iter().map(|v| (v.symbol, v.price)).collect(),
+            // One delisted or invalid symbol fails the batch, the others are read one by one
+            Err(e) => {
+                tracing::warn!("alert prices: {}", e);
+
+                let mut prices = BTreeMap::new();
+                for symbol in symbols.iter() {
+                    match market.price(symbol).await {
+                        Ok(v) => {
+                            prices.insert(symbol.clone(), v);
+                        }
+                        Err(e) => tracing::warn!("alert price of {}: {}", symbol, e),
+                    }
+                }
+
+                prices
+            }
+        };
+
+        let mut references = BTreeMap::new();
+        for (id, rule) in rules.iter() {
+            match reference(&rule.condition).await {
+                Ok(Some(v)) => {
+                    references.insert(*id, v);
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("alert {} reference price: {}", id, e),
+            }
+        }
+
+        let now = timestamp().as_millis();
+        let mut events = Vec::new();
+        {
+            let mut alerts = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+            for (id, _) in rules.iter() {
+                let alert = match alerts.get_mut(id) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let price = match prices.get(&alert.rule.symbol) {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                if let Some(event) = alert.check(now, price, references.get(id).cloned()) {
+                    events.push((event, alert.rule.webhook.clone()));
+                    if alert.rule.once {
+                        alerts.remove(id);
+                    }
+                }
+            }
+        }
+
+        // Keeps the trigger counts and drops the fired once rules
+        if !events.is_empty() {
+            if let Err(e) = self.save().await {
+                tracing::error!("alerts not saved: {}", e);
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(feature = "service-crypto")]
+async fn reference(condition: &Condition) -> Result<Option<Decimal>, Box<dyn Error>> {
+    use std::str::FromStr;
+
+    let (chain, base, quote, fee) = match condition {
+        Condition::Spread {
+            chain,
+            base,
+            quote,
+            fee,
+            ..
+        } => (chain, base, quote, *fee),
+        _ => return Ok(None),
+    };
+
+    let factory = chain.contract_uniswap_v3_factory(chain.uniswap_v3_factory_address())?;
+    let base = chain.lookup_or_validate_address(base.clone())?;
+    let quote = chain.lookup_or_validate_address(quote.clone())?;
+
+    let pool = factory.pool(&base, &quote, fee).await?;
+    let pool = chain.contract_uniswap_v3_pool(pool)?;
+    let price = pool
+        .price(
+            &chain.contract_erc_20(base)?,
+            &chain.contract_erc_20(quote)?,
+        )
+        .await?;
+
+    // Keep what fits the decimal precision
+    let price = price.quote_per_base;
+    let price = match price.split_once('.') {
+        Some((int, frac)) => format!("{}.{}", int, &frac[..frac.len().min(18)]),
+        None => price,
+    };
+
+    Ok(Some(Decimal::from_str(&price)?))
+}
+
+#[cfg(not(feature = "service-crypto"))]
+async fn reference(_condition: &Condition) -> Result<Option<Decimal>, Box<dyn Error>> {
+    Ok(None)
+}
+
+/// Evaluate the rules on every interval and deliver the events in the background
+pub fn spawn(engine: Arc<AlertEngine>, interval: Duration) {
+    tokio::spawn(async move {
+        let webhook = WebhookClient::public(engine.private_targets());
+        let (market, webhook) = match (Market::new(), webhook) {
+            (Ok(market), Ok(webhook)) => (market, Arc::new(webhook)),
+            _ => {
+                tracing::error!("alert engine failed to start");
+                return;
+            }
+        };
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let events = match engine.evaluate(&market).await {
+                Ok(v) => v,
+                Err(e) => {
+                    tracing::warn!("alert evaluation failed: {}", e);
+                    continue;
+                }
+            };
+
+            for (event, target) in events {
+                let webhook = webhook.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = webhook.deliver(&target, EVENT, &event).await {
+                        tracing::error!("alert {} not delivered: {}", event.id, e);
+                    }
+                });
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use plot::types::Decimal;
+
+    use super::{Alert, AlertEngine, AlertRule, Condition};
+    use crate::services::webhook::Webhook;
+
+    fn rule(condition: Condition) -> AlertRule {
+        AlertRule {
+            symbol: "ETHUSDT".into(),
+            condition,
+            webhook: Webhook {
+                url: "http://127.0.0.1/hook".into(),
+                secret: None,
+            },
+            cooldown: Some(0),
+            once: false,
+        }
+    }
+
+    fn alert(condition: Condition) -> Alert {
+        Alert::new(1, rule(condition))
+    }
+
+    #[test]
+    fn test_cross() {
+        let mut alert = alert(Condition::Cross {
+            level: Decimal::from(100),
+        });
+
+        assert!(alert.check(0, &Decimal::from(99), None).is_none());
+        assert!(alert.check(1, &Decimal::from(100), None).is_some());
+        assert!(alert.check(2, &Decimal::from(101), None).is_none());
+        assert!(alert.check(3, &Decimal::from(98), None).is_some());
+    }
+
+    #[test]
+    fn test_move() {
+        let mut alert = alert(Condition::Move {
+            change: Decimal::new(5, 2),
+            window: 60,
+        });
+
+        assert!(alert.check(0, &Decimal::from(100), None).is_none());
+        assert!(alert.check(30_000, &Decimal::from(104), None).is_none());
+        assert!(alert.check(40_000, &Decimal::from(105), None).is_some());
+
+        // Older prices leave the window
+        assert!(alert.check(200_000, &Decimal::from(110), None).is_none());
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let mut alert = alert(Condition::Cross {
+            level: Decimal::from(100),
+        });
+        alert.rule.cooldown = Some(10);
+
+        alert.check(0, &Decimal::from(99), None);
+        assert!(alert.check(1_000, &Decimal::from(101), None).is_some());
+        assert!(alert.check(2_000, &Decimal::from(99), None).is_none());
+        assert!(alert.check(12_000, &Decimal::from(101), None).is_some());
+        assert_eq!(alert.triggered, 2);
+    }
+
+    #[test]
+    fn test_load() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let path = std::env::temp_dir().join(format!("harmony-alerts-{}", std::process::id()));
+
+            let engine = AlertEngine::load(&path).unwrap();
+            let first = engine
+                .add(rule(Condition::Cross {
+                    level: Decimal::from(100),
+                }))
+                .await
+                .unwrap();
+
+            let mut secret = rule(Condition::Move {
+                change: Decimal::new(5, 2),
+                window: 60,
+            });
+            secret.webhook.secret = Some("secret".into());
+            let second = engine.add(secret).await.unwrap();
+            engine.remove(first).await.unwrap();
+
+            // A restart keeps the rules, their secrets and the ids
+            let engine = AlertEngine::load(&path).unwrap();
+            let alerts = engine.alerts();
+            assert_eq!(alerts.len(), 1);
+            assert_eq!(alerts[0].id, second);
+            assert_eq!(alerts[0].rule.webhook.secret.as_deref(), Some("secret"));
+
+            let third = engine
+                .add(rule(Condition::Cross {
+                    level: Decimal::from(100),
+                }))
+                .await
+                .unwrap();
+            assert_eq!(third, second + 1);
+
+            std::fs::remove_dir_all(path).unwrap();
+        });
+    }
+}
@@ -0,0 +1,277 @@
+use std::error::Error;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use binance::types::{Asset, Symbol};
+use plot::types::{Decimal, Price};
+use serde::{Deserialize, Serialize};
+
+use super::market::Market;
+
+/// Weight of the exchange info of every symbol
+const EXCHANGE_INFO_WEIGHT: u32 = 20;
+
+/// Symbol filters with their values parsed, unknown filter types become `Other`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    tag = "filterType",
+    rename_all = "SCREAMING_SNAKE_CASE",
+    rename_all_fields = "camelCase"
+)]
+pub enum Filter {
+    PriceFilter {
+        min_price: Price,
+        max_price: Price,
+        tick_size: Price,
+    },
+    PercentPrice {
+        multiplier_up: Decimal,
+        multiplier_down: Decimal,
+        avg_price_mins: u32,
+    },
+    PercentPriceBySide {
+        bid_multiplier_up: Decimal,
+        bid_multiplier_down: Decimal,
+        ask_multiplier_up: Decimal,
+        ask_multiplier_down: Decimal,
+        avg_price_mins: u32,
+    },
+    LotSize {
+        min_qty: Decimal,
+        max_qty: Decimal,
+        step_size: Decimal,
+    },
+    MarketLotSize {
+        min_qty: Decimal,
+        max_qty: Decimal,
+        step_size: Decimal,
+    },
+    MinNotional {
+        min_notional: Decimal,
+        apply_to_market: bool,
+        avg_price_mins: u32,
+    },
+    Notional {
+        min_notional: Decimal,
+        apply_min_to_market: bool,
+        max_notional: Decimal,
+        apply_max_to_market: bool,
+        avg_price_mins: u32,
+    },
+    IcebergParts {
+        limit: u32,
+    },
+    MaxNumOrders {
+        max_num_orders: u32,
+    },
+    MaxNumAlgoOrders {
+        max_num_algo_orders: u32,
+    },
+    MaxNumIcebergOrders {
+        max_num_iceberg_orders: u32,
+    },
+    MaxPosition {
+        max_position: Decimal,
+    },
+    TrailingDelta {
+        min_trailing_above_delta: u32,
+        max_trailing_above_delta: u32,
+        min_trailing_below_delta: u32,
+        max_trailing_below_delta: u32,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct CatalogueSymbol {
+    pub symbol:                     Symbol,
+    pub status:                     String,
+    pub base_asset:                 Asset,
+    pub base_asset_precision:       u32,
+    pub quote_asset:                Asset,
+    pub quote_asset_precision:      u32,
+    #[serde(default)]
+    pub order_types:                Vec<String>,
+    #[serde(default)]
+    pub is_spot_trading_allowed:    bool,
+    #[serde(default)]
+    pub is_margin_trading_allowed:  bool,
+    #[serde(default)]
+    pub permissions:                Vec<String>,
+    #[serde(default)]
+    pub permission_sets:            Vec<Vec<String>>,
+    #[serde(default)]
+    pub filters:                    Vec<Filter>,
+}
+
+impl CatalogueSymbol {
+    pub fn has_permission(&self, permission: &str) -> bool {
+        let allowed = match permission {
+            "SPOT" => self.is_spot_trading_allowed,
+            "MARGIN" => self.is_margin_trading_allowed,
+            _ => false,
+        };
+
+        allowed
+            || self.permissions.iter().any(|v| v == permission)
+            || self
+                .permission_sets
+                .iter()
+                .flatten()
+                .any(|v| v == permission)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CatalogueQuery {
+    pub search: Option<String>,
+    pub base_asset: Option<Asset>,
+    pub quote_asset: Option<Asset>,
+    pub status: Option<String>,
+    pub permission: Option<String>,
+    pub order_type: Option<String>,
+}
+
+impl CatalogueQuery {
+    /// Every given criterion must match, case insensitive
+    pub fn matches(&self, symbol: &CatalogueSymbol) -> bool {
+        let eq = |filter: &Option<String>, value: &str| {
+            filter
+                .as_ref()
+                .is_none_or(|v| v.eq_ignore_ascii_case(value))
+        };
+
+        let search = self
+            .search
+            .as_ref()
+            .is_none_or(|v| symbol.symbol.contains(&v.to_uppercase()));
+        let permission = self
+            .permission
+            .as_ref()
+            .is_none_or(|v| symbol.has_permission(&v.to_uppercase()));
+        let order_type = self
+            .order_type
+            .as_ref()
+            .is_none_or(|v| symbol.order_types.iter().any(|t| t.eq_ignore_ascii_case(v)));
+
+        search
+            && permission
+            && order_type
+            && eq(&self.base_asset, &symbol.base_asset)
+            && eq(&self.quote_asset, &symbol.quote_asset)
+            && eq(&self.status, &symbol.status)
+    }
+}
+
+#[derive(Deserialize)]
+struct CatalogueInfo {
+    symbols: Vec<CatalogueSymbol>,
+}
+
+/// Every symbol of the exchange, refreshed from the exchange info after the ttl
+pub struct Catalogue {
+    ttl: Duration,
+    cache: RwLock<Option<(Instant, Arc<Vec<CatalogueSymbol>>)>>,
+}
+
+impl Catalogue {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cache: RwLock::new(None),
+        }
+    }
+
+    pub async fn symbols(&self) -> Result<Arc<Vec<CatalogueSymbol>>, Box<dyn Error>> {
+        let cached = self.cache.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some((updated, symbols)) = cached {
+            if updated.elapsed() < self.ttl {
+                return Ok(symbols);
+            }
+        }
+
+        let info: CatalogueInfo = Market::new()?
+            .get("/api/v3/exchangeInfo", &[], EXCHANGE_INFO_WEIGHT)
+            .await?;
+        let mut symbols = info.symbols;
+        symbols.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        let symbols = Arc::new(symbols);
+
+        let mut cache = self.cache.write().unwrap_or_else(|e| e.into_inner());
+        *cache = Some((Instant::now(), symbols.clone()));
+
+        Ok(symbols)
+    }
+
+    pub async fn search(
+        &self,
+        query: &CatalogueQuery,
+    ) -> Result<Vec<CatalogueSymbol>, Box<dyn Error>> {
+        let symbols = self.symbols().await?;
+
+        Ok(symbols
+            .iter()
+            .filter(|v| query.matches(v))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plot::types::Decimal;
+
+    use super::{CatalogueQuery, CatalogueSymbol, Filter};
+
+    const SYMBOL: &str = r#"{
+        "symbol": "ETHUSDT",
+        "status": "TRADING",
+        "baseAsset": "ETH",
+        "baseAssetPrecision": 8,
+        "quoteAsset": "USDT",
+        "quotePrecision": 8,
+        "quoteAssetPrecision": 8,
+        "orderTypes": ["LIMIT", "LIMIT_MAKER", "MARKET", "STOP_LOSS_LIMIT", "TAKE_PROFIT_LIMIT"],
+        "icebergAllowed": true,
+        "isSpotTradingAllowed": true,
+        "isMarginTradingAllowed": true,
+        "filters": [
+            {"filterType": "PRICE_FILTER", "minPrice": "0.01000000", "maxPrice": "1000000.00000000", "tickSize": "0.01000000"},
+            {"filterType": "LOT_SIZE", "minQty": "0.00010000", "maxQty": "9000.00000000", "stepSize": "0.00010000"},
+            {"filterType": "NOTIONAL", "minNotional": "5.00000000", "applyMinToMarket": true, "maxNotional": "9000000.00000000", "applyMaxToMarket": false, "avgPriceMins": 5},
+            {"filterType": "SOMETHING_NEW", "value": "1"}
+        ],
+        "permissions": [],
+        "permissionSets": [["SPOT", "MARGIN", "TRD_GRP_004"]]
+    }"#;
+
+    #[test]
+    fn test_filters() {
+        let symbol: CatalogueSymbol = serde_json::from_str(SYMBOL).unwrap();
+
+        match &symbol.filters[1] {
+            Filter::LotSize { step_size, .. } => {
+                assert_eq!(*step_size, Decimal::new(1, 4));
+            }
+            v => panic!("unexpected filter {:?}", v),
+        }
+        assert!(matches!(symbol.filters[3], Filter::Other));
+    }
+
+    #[test]
+    fn test_query() {
+        let symbol: CatalogueSymbol = serde_json::from_str(SYMBOL).unwrap();
+
+        let mut query = CatalogueQuery::default();
+        query.quote_asset = Some("usdt".into());
+        query.permission = Some("margin".into());
+        query.order_type = Some("market".into());
+        assert!(query.matches(&symbol));
+
+        query.permission = Some("TRD_GRP_005".into());
+        assert!(!query.matches(&symbol));
+    }
+}
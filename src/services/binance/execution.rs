@@ -0,0 +1,665 @@
+use std::error::Error;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use binance::types::{Symbol, SymbolInfo};
+use plot::trade::{Trade, Trader};
+use plot::types::{BaseQuantity, Decimal, Price, QuoteQuantity};
+use serde::{Deserialize, Serialize};
+
+use super::job::{Job, JobHandle, Jobs};
+use super::market::{Market, MarketTrade};
+use super::{BinanceSpot, ConvertFilter};
+
+/// Upper bound of the child orders of one parent, slices grow beyond it
+const MAX_CHILDREN: u32 = 1000;
+
+/// Seconds between two iceberg slices when not given
+const ICEBERG_INTERVAL: u64 = 1;
+
+/// Public trades read on each participation tick
+const POV_TRADES_LIMIT: u16 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Algorithm {
+    // Equal child orders spread evenly over the duration
+    Twap {
+        minutes: u64,
+        slices: u32,
+    },
+    // Child orders of a fixed quantity, quote for buys and base for sells
+    Iceberg {
+        slice: Decimal,
+        interval: Option<u64>,
+    },
+    // Child orders of a fraction of the market volume traded since the last tick,
+    // whatever is left after the duration is not executed
+    Pov {
+        participation: Decimal,
+        interval: u64,
+        minutes: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    // Store name of the account that placed the order, see history::account_of
+    pub account:        String,
+    pub symbol:         Symbol,
+    pub side:           Side,
+    // Parent quantity, quote for buys and base for sells
+    pub quantity:       Decimal,
+    // Child orders placed, each may fill in several trades
+    pub children:       usize,
+    // Base bought and quote spent, or base sold and quote received, after commission
+    pub base_quantity:  BaseQuantity,
+    pub quote_quantity: QuoteQuantity,
+    // Fill price weighted by the filled base, before commission
+    pub average_price:  Option<Price>,
+    // Paid in base for buys and in quote for sells
+    pub commission:     Decimal,
+    // Parent quantity not executed
+    pub remaining:      Decimal,
+    pub trades:         Vec<Trade>,
+}
+
+impl ExecutionReport {
+    pub fn new(symbol: Symbol, side: Side, quantity: Decimal) -> Self {
+        Self {
+            account: String::new(),
+            symbol,
+            side,
+            quantity,
+            children: 0,
+            base_quantity: Decimal::ZERO,
+            quote_quantity: Decimal::ZERO,
+            average_price: None,
+            commission: Decimal::ZERO,
+            remaining: quantity,
+            trades: Vec::new(),
+        }
+    }
+
+    /// Book the trades of one child order
+    pub fn add(&mut self, trades: Vec<Trade>) {
+        self.children += 1;
+        self.trades.extend(trades);
+
+        // Buys pay the commission in base and sells in quote, the fills before it
+        // are the spent quote at the price and the sold base
+        let mut filled = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+        for trade in self.trades.iter() {
+            let base = match self.side {
+                Side::Buy if !trade.price.is_zero() => trade.quote_quantity / trade.price,
+                _ => trade.base_quantity,
+            };
+
+            filled += base;
+            notional += base * trade.price;
+        }
+
+        self.base_quantity = self.trades.iter().map(|v| v.base_quantity).sum();
+        self.quote_quantity = self.trades.iter().map(|v| v.quote_quantity).sum();
+        self.average_price = match filled.is_zero() {
+            true => None,
+            false => Some(notional / filled),
+        };
+
+        let (commission, executed) = match self.side {
+            Side::Buy => (filled - self.base_quantity, self.quote_quantity),
+            Side::Sell => (notional - self.quote_quantity, self.base_quantity),
+        };
+        self.commission = commission.max(Decimal::ZERO);
+        self.remaining = (self.quantity - executed).max(Decimal::ZERO);
+    }
+}
+
+/// Market data the algorithms price and pace their child orders with
+pub trait MarketData {
+    fn price(&self, symbol: &Symbol) -> impl Future<Output = Result<Price, Box<dyn Error>>> + Send;
+
+    fn trades(
+        &self,
+        symbol: &Symbol,
+        limit: Option<u16>,
+    ) -> impl Future<Output = Result<Vec<MarketTrade>, Box<dyn Error>>> + Send;
+}
+
+impl MarketData for Market {
+    async fn price(&self, symbol: &Symbol) -> Result<Price, Box<dyn Error>> {
+        Market::price(self, symbol).await
+    }
+
+    async fn trades(
+        &self,
+        symbol: &Symbol,
+        limit: Option<u16>,
+    ) -> Result<Vec<MarketTrade>, Box<dyn Error>> {
+        Market::trades(self, symbol, limit).await
+    }
+}
+
+/// Splits the parent quantity into child orders sent through the inner trader
+pub struct Execution<T, M = Market> {
+    trader: T,
+    normal: SymbolInfo,
+    algorithm: Algorithm,
+    market: M,
+    report: Mutex<ExecutionReport>,
+    handle: Option<JobHandle<ExecutionReport>>,
+}
+
+impl<T> Execution<T>
+where
+    T: Trader,
+{
+    pub fn new(
+        trader: T,
+        normal: SymbolInfo,
+        algorithm: Algorithm,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::with_market(trader, normal, algorithm, Market::new()?))
+    }
+}
+
+impl<T, M> Execution<T, M>
+where
+    T: Trader,
+    M: MarketData,
+{
+    pub fn with_market(trader: T, normal: SymbolInfo, algorithm: Algorithm, market: M) -> Self {
+        let report = ExecutionReport::new(normal.symbol.clone(), Side::Buy, Decimal::ZERO);
+
+        Self {
+            trader,
+            normal,
+            algorithm,
+            market,
+            report: Mutex::new(report),
+            handle: None,
+        }
+    }
+
+    /// Publish the report to the job after every child order
+    pub fn with_handle(mut self, handle: JobHandle<ExecutionReport>) -> Self {
+        self.handle = Some(handle);
+
+        self
+    }
+
+    /// Report of the latest parent order
+    pub fn report(&self) -> ExecutionReport {
+        self.report
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn begin(&self, side: Side, quantity: &Decimal) {
+        let report = ExecutionReport::new(self.normal.symbol.clone(), side, *quantity);
+        *self.report.lock().unwrap_or_else(|e| e.into_inner()) = report;
+    }
+
+    fn is_valid(&self, side: Side, price: &Price, quantity: &Decimal) -> bool {
+        match side {
+            Side::Buy => match self.normal.correct_quote_quantity(price, quantity) {
+                Ok(v) => self.normal.filter_quote_quantity(price, &v).is_ok(),
+                Err(_) => false,
+            },
+            Side::Sell => match self.normal.correct_base_quantity(price, quantity) {
+                Ok(v) => self.normal.filter_base_quantity(price, &v).is_ok(),
+                Err(_) => false,
+            },
+        }
+    }
+
+    /// Split the quantity into at most `count` equal children that pass the
+    /// symbol filters, the last child takes the rounding remainder
+    fn split(&self, side: Side, price: &Price, quantity: &Decimal, count: u32) -> Vec<Decimal> {
+        let mut count = count.clamp(1, MAX_CHILDREN);
+        while count > 1 {
+            let slice = quantity / Decimal::from(count);
+            if self.is_valid(side, price, &slice) {
+                break;
+            }
+
+            count -= 1;
+        }
+
+        let slice = quantity / Decimal::from(count);
+        let slice = match side {
+            Side::Buy => self.normal.correct_quote_quantity(price, &slice),
+            Side::Sell => self.normal.correct_base_quantity(price, &slice),
+        }
+        .unwrap_or(slice);
+
+        let mut slices = vec![slice; count as usize - 1];
+        slices.push(quantity - slice * Decimal::from(count - 1));
+
+        slices
+    }
+
+    async fn child(
+        &self,
+        side: Side,
+        price: &Price,
+        quantity: &Decimal,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        match side {
+            Side::Buy => self.trader.buy(price, quantity).await,
+            Side::Sell => self.trader.sell(price, quantity).await,
+        }
+    }
+
+    /// Current price for the child order, the parent price when unavailable
+    async fn price(&self, fallback: &Price) -> Price {
+        self.market
+            .price(&self.normal.symbol)
+            .await
+            .unwrap_or(*fallback)
+    }
+
+    async fn execute(
+        &self,
+        side: Side,
+        price: &Price,
+        quantity: &Decimal,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        self.begin(side, quantity);
+
+        let (slices, interval) = match &self.algorithm {
+            Algorithm::Twap { minutes, slices } => {
+                let slices = self.split(side, price, quantity, *slices);
+                let interval = minutes * 60 / slices.len() as u64;
+
+                (slices, interval)
+            }
+            Algorithm::Iceberg { slice, interval } => {
+                let count = match slice <= &Decimal::ZERO {
+                    true => 1,
+                    false => (quantity / slice).ceil().try_into().unwrap_or(u32::MAX),
+                };
+                let slices = self.split(side, price, quantity, count);
+
+                (slices, interval.unwrap_or(ICEBERG_INTERVAL))
+            }
+            Algorithm::Pov {
+                participation,
+                interval,
+                minutes,
+            } => {
+                return self
+                    .participate(side, price, quantity, participation, *interval, *minutes)
+                    .await
+            }
+        };
+
+        let mut trades = Vec::new();
+        for (i, slice) in slices.iter().enumerate() {
+            if i > 0 {
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+            }
+
+            let price = self.price(price).await;
+            if !self.settle(&mut trades, self.child(side, &price, slice).await)? {
+                break;
+            }
+        }
+
+        Ok(trades)
+    }
+
+    async fn participate(
+        &self,
+        side: Side,
+        price: &Price,
+        quantity: &Decimal,
+        participation: &Decimal,
+        interval: u64,
+        minutes: u64,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        let symbol = &self.normal.symbol;
+        let interval = interval.max(1);
+        let ticks = (minutes * 60 / interval).max(1);
+
+        let mut last_id = self
+            .market
+            .trades(symbol, Some(1))
+            .await?
+            .last()
+            .map_or(0, |v| v.id);
+
+        let mut trades = Vec::new();
+        let mut remaining = *quantity;
+        // Volume share not sent yet, small shares add up until they pass the filters
+        let mut pending = Decimal::ZERO;
+
+        for _ in 0..ticks {
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+
+            let market_trades = self.market.trades(symbol, Some(POV_TRADES_LIMIT)).await?;
+            let volume: Decimal = market_trades
+                .iter()
+                .filter(|v| v.id > last_id)
+                .map(|v| match side {
+                    Side::Buy => v.quote_qty,
+                    Side::Sell => v.qty,
+                })
+                .sum();
+            last_id = market_trades.last().map_or(last_id, |v| v.id.max(last_id));
+
+            pending = (pending + volume * participation).min(remaining);
+            let price = market_trades.last().map_or(*price, |v| v.price);
+            if !self.is_valid(side, &price, &pending) {
+                continue;
+            }
+
+            if !self.settle(&mut trades, self.child(side, &price, &pending).await)? {
+                break;
+            }
+
+            remaining = *quantity
+                - trades
+                    .iter()
+                    .map(|v| match side {
+                        Side::Buy => v.quote_quantity,
+                        Side::Sell => v.base_quantity,
+                    })
+                    .sum::<Decimal>();
+            pending = Decimal::ZERO;
+        }
+
+        Ok(trades)
+    }
+
+    /// Collect the trades of a child order. A failed child stops the execution,
+    /// the error is returned only when nothing was executed before it
+    fn settle(
+        &self,
+        trades: &mut Vec<Trade>,
+        executed: Result<Vec<Trade>, Box<dyn Error>>,
+    ) -> Result<bool, Box<dyn Error>> {
+        match executed {
+            Ok(v) => {
+                let report = {
+                    let mut report = self.report.lock().unwrap_or_else(|e| e.into_inner());
+                    report.add(v.clone());
+                    report.clone()
+                };
+                if let Some(handle) = &self.handle {
+                    handle.update(|v| {
+                        *v = ExecutionReport {
+                            account: v.account.clone(),
+                            ..report
+                        }
+                    });
+                }
+
+                trades.extend(v);
+                Ok(true)
+            }
+            Err(e) if trades.is_empty() => Err(e),
+            Err(e) => {
+                tracing::warn!("execution stopped after {} trades: {}", trades.len(), e);
+                Ok(false)
+            }
+        }
+    }
+}
+
+impl<T, M> Trader for Execution<T, M>
+where
+    T: Trader,
+    M: MarketData,
+{
+    async fn buy(
+        &self,
+        price: &Price,
+        quantity: &QuoteQuantity,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        self.execute(Side::Buy, price, quantity).await
+    }
+
+    async fn sell(
+        &self,
+        price: &Price,
+        quantity: &BaseQuantity,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        self.execute(Side::Sell, price, quantity).await
+    }
+}
+
+/// Run the parent order in the background, the job report follows its child orders
+pub fn spawn(
+    jobs: &Arc<Jobs<ExecutionReport>>,
+    account: String,
+    execution: Execution<BinanceSpot>,
+    side: Side,
+    price: Price,
+    quantity: Decimal,
+) -> Job<ExecutionReport> {
+    let mut report = ExecutionReport::new(execution.normal.symbol.clone(), side, quantity);
+    report.account = account;
+
+    jobs.spawn(report, move |handle| async move {
+        let execution = execution.with_handle(handle);
+        let executed = match side {
+            Side::Buy => execution.buy(&price, &quantity).await,
+            Side::Sell => execution.sell(&price, &quantity).await,
+        };
+
+        executed.map(|_| ()).map_err(|e| e.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::error::Error;
+    use std::sync::Mutex;
+
+    use binance::types::Symbol;
+    use plot::trade::{Trade, Trader};
+    use plot::types::{BaseQuantity, Decimal, Price, QuoteQuantity};
+
+    use super::super::fixture::{self, dec};
+    use super::super::market::MarketTrade;
+    use super::{Algorithm, Execution, ExecutionReport, MarketData, Side};
+
+    /// Fills each child order in two trades at the price, a commission of 0.1%
+    /// is taken from the bought base or the received quote
+    #[derive(Default)]
+    struct Fill {
+        orders: Mutex<Vec<Decimal>>,
+    }
+
+    impl Trader for Fill {
+        async fn buy(
+            &self,
+            price: &Price,
+            quantity: &QuoteQuantity,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            self.orders.lock().unwrap().push(*quantity);
+
+            let half = quantity / Decimal::TWO;
+            let base = half / price * dec("0.999");
+            Ok(vec![Trade::with_buy(*price, base, half); 2])
+        }
+
+        async fn sell(
+            &self,
+            price: &Price,
+            quantity: &BaseQuantity,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            self.orders.lock().unwrap().push(*quantity);
+
+            let half = quantity / Decimal::TWO;
+            let quote = half * price * dec("0.999");
+            Ok(vec![Trade::with_sell(*price, half, quote); 2])
+        }
+    }
+
+    /// Prices at 100 and replays the public trades of each tick
+    #[derive(Default)]
+    struct Tape {
+        ticks: Mutex<VecDeque<Vec<MarketTrade>>>,
+    }
+
+    impl MarketData for Tape {
+        async fn price(&self, _symbol: &Symbol) -> Result<Price, Box<dyn Error>> {
+            Ok(Decimal::ONE_HUNDRED)
+        }
+
+        async fn trades(
+            &self,
+            _symbol: &Symbol,
+            _limit: Option<u16>,
+        ) -> Result<Vec<MarketTrade>, Box<dyn Error>> {
+            Ok(self.ticks.lock().unwrap().pop_front().unwrap_or_default())
+        }
+    }
+
+    fn market_trade(id: i64, quote_qty: &str) -> MarketTrade {
+        MarketTrade {
+            id,
+            price: Decimal::ONE_HUNDRED,
+            qty: dec(quote_qty) / Decimal::ONE_HUNDRED,
+            quote_qty: dec(quote_qty),
+            time: 0,
+            is_buyer_maker: false,
+        }
+    }
+
+    fn execution(algorithm: Algorithm, tape: Tape) -> Execution<Fill, Tape> {
+        let normal = fixture::symbol_info(&[fixture::LOT_SIZE, fixture::NOTIONAL]);
+
+        Execution::with_market(Fill::default(), normal, algorithm, tape)
+    }
+
+    #[test]
+    fn test_report() {
+        let mut report = ExecutionReport::new("ETHUSDT".into(), Side::Buy, dec("250"));
+        report.add(vec![Trade::with_buy(dec("100"), dec("0.999"), dec("100"))]);
+        report.add(vec![Trade::with_buy(dec("110"), dec("0.999"), dec("110"))]);
+
+        assert_eq!(report.children, 2);
+        assert_eq!(report.average_price, Some(dec("105")));
+        assert_eq!(report.commission, dec("0.002"));
+        assert_eq!(report.remaining, dec("40"));
+
+        let report = ExecutionReport::new("ETHUSDT".into(), Side::Sell, Decimal::ONE);
+        assert_eq!(report.average_price, None);
+        assert_eq!(report.remaining, Decimal::ONE);
+    }
+
+    #[test]
+    fn test_split() {
+        let execution = execution(
+            Algorithm::Iceberg {
+                slice: Decimal::ONE,
+                interval: None,
+            },
+            Tape::default(),
+        );
+        let price = Decimal::ONE_HUNDRED;
+
+        // Slices below the minimum notional of 5 merge into fewer children
+        let slices = execution.split(Side::Buy, &price, &dec("100"), 50);
+        assert_eq!(slices.len(), 20);
+        assert!(slices.iter().all(|v| v == &dec("5")));
+
+        // The last child takes what the lot size cut off
+        let slices = execution.split(Side::Sell, &price, &Decimal::ONE, 3);
+        assert_eq!(slices, vec![dec("0.3333"), dec("0.3333"), dec("0.3334")]);
+    }
+
+    #[test]
+    fn test_twap() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let algorithm = Algorithm::Twap {
+                minutes: 0,
+                slices: 4,
+            };
+            let execution = execution(algorithm, Tape::default());
+
+            let trades = execution.buy(&dec("90"), &dec("100")).await.unwrap();
+            assert_eq!(trades.len(), 8);
+            assert_eq!(*execution.trader.orders.lock().unwrap(), vec![dec("25"); 4]);
+
+            // Children are orders, not fills, and the price leaves the commission out
+            let report = execution.report();
+            assert_eq!(report.children, 4);
+            assert_eq!(report.average_price, Some(Decimal::ONE_HUNDRED));
+            assert_eq!(report.commission, dec("0.001"));
+            assert_eq!(report.remaining, Decimal::ZERO);
+        });
+    }
+
+    #[test]
+    fn test_iceberg() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let algorithm = Algorithm::Iceberg {
+                slice: dec("0.3"),
+                interval: Some(0),
+            };
+            let execution = execution(algorithm, Tape::default());
+
+            execution.sell(&dec("90"), &Decimal::ONE).await.unwrap();
+            assert_eq!(execution.trader.orders.lock().unwrap().len(), 4);
+
+            let report = execution.report();
+            assert_eq!(report.side, Side::Sell);
+            assert_eq!(report.children, 4);
+            assert_eq!(report.base_quantity, Decimal::ONE);
+            assert_eq!(report.average_price, Some(Decimal::ONE_HUNDRED));
+            assert_eq!(report.commission, dec("0.1"));
+        });
+    }
+
+    #[test]
+    fn test_pov() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let tape = Tape::default();
+            tape.ticks.lock().unwrap().extend([
+                vec![market_trade(10, "1")],
+                vec![
+                    market_trade(10, "1"),
+                    market_trade(11, "400"),
+                    market_trade(12, "600"),
+                ],
+            ]);
+
+            let algorithm = Algorithm::Pov {
+                participation: dec("0.1"),
+                interval: 1,
+                minutes: 0,
+            };
+            let execution = execution(algorithm, tape);
+
+            // A tenth of the 1000 traded after the first tick, capped by the parent
+            execution.buy(&dec("90"), &dec("50")).await.unwrap();
+            assert_eq!(*execution.trader.orders.lock().unwrap(), vec![dec("50")]);
+            assert_eq!(execution.report().children, 1);
+        });
+    }
+}
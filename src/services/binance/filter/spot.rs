@@ -304,21 +304,43 @@ pub mod quote_quantity {
     }
 }
 
+pub mod price {
+    use binance::types::{SymbolFilter, SymbolInfo};
+    use plot::types::Price;
+
+    use crate::services::binance::filter::{self, SymbolFilterResult};
+
+    /// Round the price down to the tick size, or to the quote precision without a price filter
+    pub fn correct(norms: &SymbolInfo, price: &Price) -> SymbolFilterResult<Price> {
+        for filter in norms.filters.iter() {
+            if let SymbolFilter::PriceFilter(v) = filter {
+                let tick_size = filter::dec(&v.tick_size)?;
+                if !tick_size.is_zero() {
+                    return Ok((price - (price % tick_size)).normalize());
+                }
+            }
+        }
+
+        Ok(price.trunc_with_scale(norms.quote_asset_precision.into()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use binance::types::SymbolInfo;
     use plot::types::Decimal;
 
-    const SYMBOL_PRICE: &str = "3685.96000000";
-    const SYMBOL_NORMS: &str = r#"{"allowTrailingStop":true,"allowedSelfTradePreventionModes":["EXPIRE_TAKER","EXPIRE_MAKER","EXPIRE_BOTH"],"baseAsset":"ETH","baseAssetPrecision":8,"baseCommissionPrecision":8,"cancelReplaceAllowed":true,"defaultSelfTradePreventionMode":"EXPIRE_MAKER","filters":[{"filterType":"PRICE_FILTER","maxPrice":"1000000.00000000","minPrice":"0.01000000","tickSize":"0.01000000"},{"filterType":"LOT_SIZE","maxQty":"9000.00000000","minQty":"0.00010000","stepSize":"0.00010000"},{"filterType":"ICEBERG_PARTS","limit":10},{"filterType":"MARKET_LOT_SIZE","maxQty":"1701.08445000","minQty":"0.00000000","stepSize":"0.00000000"},{"filterType":"TRAILING_DELTA","maxTrailingAboveDelta":2000,"maxTrailingBelowDelta":2000,"minTrailingAboveDelta":10,"minTrailingBelowDelta":10},{"askMultiplierDown":"0.2","askMultiplierUp":"5","avgPriceMins":5,"bidMultiplierDown":"0.2","bidMultiplierUp":"5","filterType":"PERCENT_PRICE_BY_SIDE"},{"applyMaxToMarket":false,"applyMinToMarket":true,"avgPriceMins":5,"filterType":"NOTIONAL","maxNotional":"9000000.00000000","minNotional":"5.00000000"},{"filterType":"MAX_NUM_ORDERS","maxNumOrders":200},{"filterType":"MAX_NUM_ALGO_ORDERS","maxNumAlgoOrders":5}],"icebergAllowed":true,"isMarginTradingAllowed":true,"isSpotTradingAllowed":true,"ocoAllowed":true,"orderTypes":["LIMIT","LIMIT_MAKER","MARKET","STOP_LOSS_LIMIT","TAKE_PROFIT_LIMIT"],"otoAllowed":false,"permissionSets":[["SPOT","MARGIN","TRD_GRP_004","TRD_GRP_005","TRD_GRP_006","TRD_GRP_009","TRD_GRP_010","TRD_GRP_011","TRD_GRP_012","TRD_GRP_013","TRD_GRP_014","TRD_GRP_015","TRD_GRP_016","TRD_GRP_017","TRD_GRP_018","TRD_GRP_019","TRD_GRP_020","TRD_GRP_021","TRD_GRP_022","TRD_GRP_023","TRD_GRP_024","TRD_GRP_025"]],"permissions":[],"quoteAsset":"USDT","quoteAssetPrecision":8,"quoteCommissionPrecision":8,"quoteOrderQtyMarketAllowed":true,"quotePrecision":8,"status":"TRADING","symbol":"ETHUSDT"}"#;
+    use crate::services::binance::fixture::{self, dec};
 
-    fn dec(value: &str) -> Decimal {
-        use std::str::FromStr;
-        Decimal::from_str(value).unwrap()
-    }
+    const SYMBOL_PRICE: &str = "3685.96000000";
 
     fn symbol_norms() -> SymbolInfo {
-        serde_json::from_str(SYMBOL_NORMS).unwrap()
+        fixture::symbol_info(&[
+            fixture::PRICE_FILTER,
+            fixture::LOT_SIZE,
+            fixture::MARKET_LOT_SIZE,
+            fixture::NOTIONAL,
+        ])
     }
 
     fn symbol_price() -> Decimal {
@@ -443,4 +465,15 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_correct_price() {
+        use crate::services::binance::filter::spot::price::correct;
+
+        assert_eq!(
+            correct(&symbol_norms(), &dec("3685.9678")).unwrap(),
+            dec("3685.96")
+        );
+        assert_eq!(correct(&symbol_norms(), &dec("3685")).unwrap(), dec("3685"));
+    }
 }
@@ -0,0 +1,25 @@
+//! Exchange info of ETHUSDT shared by the tests, a test builds the symbol from
+//! the filters it needs
+
+use binance::types::SymbolInfo;
+use plot::types::Decimal;
+
+pub const PRICE_FILTER: &str = r#"{"filterType":"PRICE_FILTER","maxPrice":"1000000.00000000","minPrice":"0.01000000","tickSize":"0.01000000"}"#;
+pub const LOT_SIZE: &str = r#"{"filterType":"LOT_SIZE","maxQty":"9000.00000000","minQty":"0.00010000","stepSize":"0.00010000"}"#;
+pub const MARKET_LOT_SIZE: &str = r#"{"filterType":"MARKET_LOT_SIZE","maxQty":"1701.08445000","minQty":"0.00000000","stepSize":"0.00000000"}"#;
+pub const NOTIONAL: &str = r#"{"applyMaxToMarket":false,"applyMinToMarket":true,"avgPriceMins":5,"filterType":"NOTIONAL","maxNotional":"9000000.00000000","minNotional":"5.00000000"}"#;
+
+/// ETHUSDT with only the filters
+pub fn symbol_info(filters: &[&str]) -> SymbolInfo {
+    let info = format!(
+        r#"{{"allowTrailingStop":true,"allowedSelfTradePreventionModes":["EXPIRE_TAKER","EXPIRE_MAKER","EXPIRE_BOTH"],"baseAsset":"ETH","baseAssetPrecision":8,"baseCommissionPrecision":8,"cancelReplaceAllowed":true,"defaultSelfTradePreventionMode":"EXPIRE_MAKER","filters":[{}],"icebergAllowed":true,"isMarginTradingAllowed":true,"isSpotTradingAllowed":true,"ocoAllowed":true,"orderTypes":["LIMIT","LIMIT_MAKER","MARKET","STOP_LOSS_LIMIT","TAKE_PROFIT_LIMIT"],"otoAllowed":false,"permissionSets":[["SPOT","MARGIN"]],"permissions":[],"quoteAsset":"USDT","quoteAssetPrecision":8,"quoteCommissionPrecision":8,"quoteOrderQtyMarketAllowed":true,"quotePrecision":8,"status":"TRADING","symbol":"ETHUSDT"}}"#,
+        filters.join(",")
+    );
+
+    serde_json::from_str(&info).unwrap()
+}
+
+pub fn dec(value: &str) -> Decimal {
+    use std::str::FromStr;
+    Decimal::from_str(value).unwrap()
+}
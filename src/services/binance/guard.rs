@@ -0,0 +1,353 @@
+use std::error::Error;
+
+use plot::trade::position::Position;
+use plot::trade::{Trade, TradeSide, Trader};
+use plot::types::{BaseQuantity, Decimal, Price, QuoteQuantity};
+use serde::{Deserialize, Serialize};
+
+use super::strategy::{held_base, Strategy};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Trigger {
+    StopPrice,
+    TrailingStop,
+    MaxDrawdown,
+    TakeProfit,
+}
+
+/// Bot level risk rules, liquidates the held base quantity and halts the bot
+/// when one of them triggers. The state fields round trip with the bot.
+#[rustfmt::skip]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Guard {
+    // Sell everything at or below this price
+    pub stop_price:    Option<Price>,
+    // Sell everything when price falls this fraction below its peak, 0.1 is 10%
+    pub trailing_stop: Option<Decimal>,
+    // Sell everything when the profit falls this much quote below its peak
+    pub max_drawdown:  Option<QuoteQuantity>,
+    // Sell everything once the profit reaches this much quote
+    pub take_profit:   Option<QuoteQuantity>,
+
+    #[serde(default)]
+    pub base:          BaseQuantity,
+    // Quote received minus quote spent
+    #[serde(default)]
+    pub quote:         QuoteQuantity,
+    #[serde(default)]
+    pub peak_price:    Option<Price>,
+    #[serde(default)]
+    pub peak_profit:   Option<QuoteQuantity>,
+    #[serde(default)]
+    pub halted:        Option<Trigger>,
+    // Triggered and not sold yet, the next check sells again
+    #[serde(default)]
+    pub liquidating:   Option<Trigger>,
+}
+
+impl Guard {
+    /// No more orders of the positions, also while the liquidation is retried
+    pub fn is_halted(&self) -> bool {
+        self.halted.is_some() || self.liquidating.is_some()
+    }
+
+    /// Book the executed trades of the bot
+    pub fn record(&mut self, trades: &[Trade]) {
+        for trade in trades.iter() {
+            match trade.side {
+                TradeSide::Buy => {
+                    self.base += trade.base_quantity;
+                    self.quote -= trade.quote_quantity;
+                }
+                TradeSide::Sell => {
+                    self.base -= trade.base_quantity;
+                    self.quote += trade.quote_quantity;
+                }
+            }
+        }
+    }
+
+    /// Profit of the bot with the held base valued at the price
+    pub fn profit(&self, price: &Price) -> QuoteQuantity {
+        self.quote + self.base * price
+    }
+
+    /// Update the peaks with the price and return the first rule it breaks
+    pub fn check(&mut self, price: &Price) -> Option<Trigger> {
+        if self.is_halted() {
+            return None;
+        }
+
+        let profit = self.profit(price);
+        let peak_price = self.peak_price.map_or(*price, |v| v.max(*price));
+        let peak_profit = self.peak_profit.map_or(profit, |v| v.max(profit));
+        self.peak_price = Some(peak_price);
+        self.peak_profit = Some(peak_profit);
+
+        if let Some(v) = self.stop_price {
+            if price <= &v {
+                return Some(Trigger::StopPrice);
+            }
+        }
+
+        if let Some(v) = self.trailing_stop {
+            if price <= &(peak_price * (Decimal::ONE - v)) {
+                return Some(Trigger::TrailingStop);
+            }
+        }
+
+        if let Some(v) = self.max_drawdown {
+            if peak_profit - profit >= v {
+                return Some(Trigger::MaxDrawdown);
+            }
+        }
+
+        if let Some(v) = self.take_profit {
+            if profit >= v {
+                return Some(Trigger::TakeProfit);
+            }
+        }
+
+        None
+    }
+}
+
+/// Seed a guard new to the bot with the base its positions hold, valued at the
+/// price so the profit counts from here
+pub async fn attach(guard: &mut Guard, positions: &[Position], price: &Price) {
+    if guard.peak_price.is_some() || !guard.base.is_zero() || !guard.quote.is_zero() {
+        return;
+    }
+
+    for position in positions.iter() {
+        guard.base += held_base(position).await;
+    }
+    guard.quote = -(guard.base * price);
+}
+
+/// Check the guard at the price, on a trigger sell the held base through the
+/// trader and halt. Returns the liquidation trades.
+///
+/// The positions lose their base with the liquidation, they start over idle from
+/// the strategy or without the holding ones.
+pub async fn protect<T>(
+    guard: &mut Guard,
+    positions: &mut Vec<Position>,
+    strategy: Option<&Strategy>,
+    trader: &T,
+    price: &Price,
+) -> Result<Vec<Trade>, Box<dyn Error>>
+where
+    T: Trader,
+{
+    attach(guard, positions, price).await;
+
+    let trigger = match guard.liquidating.or_else(|| guard.check(price)) {
+        Some(v) => v,
+        None => return Ok(Vec::new()),
+    };
+
+    tracing::warn!("guard {:?} triggered at price {}", trigger, price);
+
+    // A failed sell leaves the trigger pending for the next check
+    guard.liquidating = Some(trigger);
+    let mut trades = Vec::new();
+    if guard.base > Decimal::ZERO {
+        trades = trader.sell(price, &guard.base).await?;
+        guard.record(&trades);
+    }
+    guard.liquidating = None;
+    guard.halted = Some(trigger);
+
+    match strategy {
+        Some(v) => *positions = v.positions(),
+        None => {
+            let mut idle = Vec::with_capacity(positions.len());
+            for position in positions.drain(..) {
+                if held_base(&position).await.is_zero() {
+                    idle.push(position);
+                }
+            }
+            *positions = idle;
+        }
+    }
+
+    Ok(trades)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use plot::trade::{Executor, Trade, Trader};
+    use plot::types::{BaseQuantity, Decimal, Price, QuoteQuantity};
+
+    use super::super::fixture::dec;
+    use super::super::strategy::{held_base, Geometric, Strategy};
+    use super::{protect, Guard, Trigger};
+
+    fn holding() -> Guard {
+        let mut guard = Guard::default();
+        guard.record(&[Trade::with_buy(dec("100"), dec("1"), dec("100"))]);
+
+        guard
+    }
+
+    #[test]
+    fn test_stop_price() {
+        let mut guard = holding();
+        guard.stop_price = Some(dec("90"));
+
+        assert_eq!(guard.check(&dec("95")), None);
+        assert_eq!(guard.check(&dec("90")), Some(Trigger::StopPrice));
+    }
+
+    #[test]
+    fn test_trailing_stop() {
+        let mut guard = holding();
+        guard.trailing_stop = Some(dec("0.1"));
+
+        assert_eq!(guard.check(&dec("150")), None);
+        assert_eq!(guard.check(&dec("136")), None);
+        assert_eq!(guard.check(&dec("135")), Some(Trigger::TrailingStop));
+    }
+
+    #[test]
+    fn test_drawdown_and_take_profit() {
+        let mut guard = holding();
+        guard.max_drawdown = Some(dec("20"));
+        guard.take_profit = Some(dec("60"));
+
+        assert_eq!(guard.check(&dec("130")), None);
+        assert_eq!(guard.check(&dec("110")), Some(Trigger::MaxDrawdown));
+
+        let mut guard = holding();
+        guard.take_profit = Some(dec("60"));
+        assert_eq!(guard.check(&dec("160")), Some(Trigger::TakeProfit));
+    }
+
+    /// Fills every order at the price
+    struct Fill;
+
+    impl Trader for Fill {
+        async fn buy(
+            &self,
+            price: &Price,
+            quantity: &QuoteQuantity,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            Ok(vec![Trade::with_buy(*price, quantity / price, *quantity)])
+        }
+
+        async fn sell(
+            &self,
+            price: &Price,
+            quantity: &BaseQuantity,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            Ok(vec![Trade::with_sell(*price, *quantity, quantity * price)])
+        }
+    }
+
+    /// Refuses every order
+    struct Refuse;
+
+    impl Trader for Refuse {
+        async fn buy(
+            &self,
+            _price: &Price,
+            _quantity: &QuoteQuantity,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            Err("refused".into())
+        }
+
+        async fn sell(
+            &self,
+            _price: &Price,
+            _quantity: &BaseQuantity,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            Err("refused".into())
+        }
+    }
+
+    #[test]
+    fn test_liquidation_retry() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let mut guard = holding();
+            guard.stop_price = Some(dec("90"));
+            let mut positions = Vec::new();
+
+            let refused = protect(&mut guard, &mut positions, None, &Refuse, &dec("90")).await;
+            assert!(refused.is_err());
+            assert_eq!(guard.liquidating, Some(Trigger::StopPrice));
+            assert_eq!(guard.halted, None);
+            assert!(guard.is_halted());
+
+            // Sold on the next check, also once the price is back above the stop
+            let trades = protect(&mut guard, &mut positions, None, &Fill, &dec("95"))
+                .await
+                .unwrap();
+            assert_eq!(trades.len(), 1);
+            assert_eq!(guard.base, Decimal::ZERO);
+            assert_eq!(guard.liquidating, None);
+            assert_eq!(guard.halted, Some(Trigger::StopPrice));
+        });
+    }
+
+    #[test]
+    fn test_attach_and_liquidate() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let strategy = Strategy::Geometric(Geometric {
+                lower: dec("100"),
+                upper: dec("400"),
+                levels: 2,
+                investment: dec("200"),
+            });
+            let mut positions = strategy.positions();
+
+            // The bot bought before the guard came in
+            let mut held = Decimal::ZERO;
+            for position in positions.iter_mut() {
+                position.trap(&Fill, &dec("100")).await.unwrap();
+                held += held_base(position).await;
+            }
+            assert!(held > Decimal::ZERO);
+
+            let mut guard = Guard::default();
+            guard.stop_price = Some(dec("80"));
+
+            let trades = protect(
+                &mut guard,
+                &mut positions,
+                Some(&strategy),
+                &Fill,
+                &dec("90"),
+            )
+            .await
+            .unwrap();
+            assert!(trades.is_empty());
+            assert_eq!(guard.base, held);
+            assert_eq!(guard.profit(&dec("90")), Decimal::ZERO);
+
+            let trades = protect(
+                &mut guard,
+                &mut positions,
+                Some(&strategy),
+                &Fill,
+                &dec("80"),
+            )
+            .await
+            .unwrap();
+            assert_eq!(trades.len(), 1);
+            assert_eq!(guard.base, Decimal::ZERO);
+            assert_eq!(guard.halted, Some(Trigger::StopPrice));
+
+            // Nothing is left for the positions to sell
+            for position in positions.iter() {
+                assert!(held_base(position).await.is_zero());
+            }
+        });
+    }
+}
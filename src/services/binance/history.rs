@@ -0,0 +1,451 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use binance::prelude::Client;
+use binance::types::{Asset, Symbol, Trade};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use super::catalogue::Catalogue;
+use super::job::{Job, Jobs};
+use super::limit::{self, weight};
+
+/// Binance `myTrades` page size limit
+const PAGE_LIMIT: u16 = 1000;
+
+/// Pause between pages, leaves request weight to the interactive requests
+const PAGE_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Local trade history, one JSON lines file per account and symbol
+pub struct TradeStore {
+    path: PathBuf,
+    // Last stored trade id of every file seen so far, a file is read once for it
+    last_ids: Mutex<HashMap<PathBuf, Option<i64>>>,
+}
+
+impl TradeStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            last_ids: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn account_path(&self, account: &str) -> io::Result<PathBuf> {
+        let valid = !account.is_empty()
+            && account
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid trade store account name {}", account),
+            ));
+        }
+
+        Ok(self.path.join("trades").join(account))
+    }
+
+    fn symbol_path(&self, account: &str, symbol: &Symbol) -> io::Result<PathBuf> {
+        let path = self.account_path(account)?;
+
+        if !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid symbol {}", symbol),
+            ));
+        }
+
+        Ok(path.join(format!("{}.jsonl", symbol)))
+    }
+
+    /// Stored trades of the symbol, ordered by trade id
+    pub async fn trades(&self, account: &str, symbol: &Symbol) -> io::Result<Vec<Trade>> {
+        let path = self.symbol_path(account, symbol)?;
+
+        // Never reads a file while an append writes it
+        let _guard = self.last_ids.lock().await;
+        blocking(move || read(&path)).await
+    }
+
+    /// Symbols that have a stored history for the account
+    pub async fn symbols(&self, account: &str) -> io::Result<Vec<Symbol>> {
+        let path = self.account_path(account)?;
+
+        blocking(move || list(&path)).await
+    }
+
+    pub async fn last_id(&self, account: &str, symbol: &Symbol) -> io::Result<Option<i64>> {
+        let path = self.symbol_path(account, symbol)?;
+        let mut last_ids = self.last_ids.lock().await;
+
+        cached_last_id(&mut last_ids, &path).await
+    }
+
+    /// Append the trades newer than the stored ones, returns the appended count
+    pub async fn append(
+        &self,
+        account: &str,
+        symbol: &Symbol,
+        trades: &[Trade],
+    ) -> io::Result<usize> {
+        let directory = self.account_path(account)?;
+        let path = self.symbol_path(account, symbol)?;
+        let mut last_ids = self.last_ids.lock().await;
+
+        let last_id = cached_last_id(&mut last_ids, &path).await?;
+        let mut trades: Vec<&Trade> = trades
+            .iter()
+            .filter(|v| last_id.is_none_or(|id| v.id > id))
+            .collect();
+        trades.sort_by_key(|v| v.id);
+        trades.dedup_by_key(|v| v.id);
+
+        let last_id = match trades.last() {
+            Some(v) => Some(v.id),
+            None => return Ok(0),
+        };
+        let lines = trades
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let file = path.clone();
+        let written = blocking(move || {
+            fs::create_dir_all(directory)?;
+            let mut file = OpenOptions::new().create(true).append(true).open(file)?;
+            for line in lines.iter() {
+                writeln!(file, "{}", line)?;
+            }
+
+            Ok(())
+        })
+        .await;
+
+        match written {
+            Ok(()) => last_ids.insert(path, last_id),
+            // Part of the lines may be written, read the file again next time
+            Err(e) => {
+                last_ids.remove(&path);
+                return Err(e);
+            }
+        };
+
+        Ok(trades.len())
+    }
+}
+
+async fn cached_last_id(
+    last_ids: &mut HashMap<PathBuf, Option<i64>>,
+    path: &Path,
+) -> io::Result<Option<i64>> {
+    if let Some(v) = last_ids.get(path) {
+        return Ok(*v);
+    }
+
+    let file = path.to_path_buf();
+    let last_id = blocking(move || Ok(read(&file)?.iter().map(|v| v.id).max())).await?;
+    last_ids.insert(path.to_path_buf(), last_id);
+
+    Ok(last_id)
+}
+
+/// File system work off the async runtime
+async fn blocking<T, F>(work: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(io::Error::other)?
+}
+
+fn read(path: &Path) -> io::Result<Vec<Trade>> {
+    let file = match File::open(path) {
+        Ok(v) => v,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut trades = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        trades.push(serde_json::from_str(&line)?);
+    }
+
+    Ok(trades)
+}
+
+fn list(path: &Path) -> io::Result<Vec<Symbol>> {
+    let entries = match fs::read_dir(path) {
+        Ok(v) => v,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut symbols = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|v| v == "jsonl") {
+            if let Some(v) = path.file_stem().and_then(|v| v.to_str()) {
+                symbols.push(v.to_string());
+            }
+        }
+    }
+    symbols.sort();
+
+    Ok(symbols)
+}
+
+/// Store name of the account of the api key, an account is only read and
+/// synced with its own keys
+pub fn account_of(api_key: &str) -> String {
+    let digest = Sha256::digest(api_key.as_bytes());
+
+    hex::encode(&digest[..16])
+}
+
+/// Fails unless the exchange accepts the keys of the client
+pub async fn authenticate(client: &Client) -> Result<(), Box<dyn Error>> {
+    let assets = client.user_asset(None, Some(false), None);
+    limit::request(weight::USER_ASSET, assets).await?;
+
+    Ok(())
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub fetched: usize,
+    pub stored:  usize,
+    pub pages:   usize,
+    pub last_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncJob {
+    pub account: String,
+    pub symbols: Vec<Symbol>,
+    // Reports of the symbols synced so far
+    pub synced: BTreeMap<Symbol, SyncReport>,
+}
+
+/// Page through the symbol trade history from the last stored trade to now
+pub async fn sync(
+    client: &Client,
+    store: &TradeStore,
+    account: &str,
+    symbol: &Symbol,
+) -> Result<SyncReport, Box<dyn Error>> {
+    let mut report = SyncReport {
+        fetched: 0,
+        stored: 0,
+        pages: 0,
+        last_id: store.last_id(account, symbol).await?,
+    };
+
+    loop {
+        if report.pages > 0 {
+            tokio::time::sleep(PAGE_INTERVAL).await;
+        }
+
+        let page = fetch_page(client, symbol, report.last_id.map_or(0, |v| v + 1)).await?;
+
+        report.pages += 1;
+        report.fetched += page.len();
+        report.stored += store.append(account, symbol, &page).await?;
+
+        if let Some(v) = page.iter().map(|v| v.id).max() {
+            report.last_id = Some(v);
+        }
+
+        if page.len() < PAGE_LIMIT as usize {
+            break;
+        }
+    }
+
+    Ok(report)
+}
+
+/// One page of the symbol trades from the trade id on
+async fn fetch_page(
+    client: &Client,
+    symbol: &Symbol,
+    from_id: i64,
+) -> Result<Vec<Trade>, Box<dyn Error>> {
+    let page = client.spot_trades(
+        symbol,
+        None,
+        None,
+        None,
+        Some(from_id),
+        Some(PAGE_LIMIT),
+        None,
+    );
+
+    limit::request(weight::MY_TRADES, page).await
+}
+
+/// Every trade of the symbol from the exchange, page by page without storing them
+pub async fn fetch(client: &Client, symbol: &Symbol) -> Result<Vec<Trade>, Box<dyn Error>> {
+    let mut trades: Vec<Trade> = Vec::new();
+
+    loop {
+        let from_id = trades.last().map_or(0, |v| v.id + 1);
+        let mut page = fetch_page(client, symbol, from_id).await?;
+        page.sort_by_key(|v| v.id);
+
+        let full = page.len() >= PAGE_LIMIT as usize;
+        trades.extend(page);
+
+        if !full {
+            break;
+        }
+    }
+
+    Ok(trades)
+}
+
+/// Sync the symbols one after another in the background
+pub fn spawn(
+    jobs: &Arc<Jobs<SyncJob>>,
+    client: Client,
+    store: Arc<TradeStore>,
+    account: String,
+    symbols: Vec<Symbol>,
+) -> Job<SyncJob> {
+    let job = SyncJob {
+        account: account.clone(),
+        symbols: symbols.clone(),
+        synced: BTreeMap::new(),
+    };
+
+    jobs.spawn(job, move |handle| async move {
+        for symbol in symbols.iter() {
+            let report = sync(&client, &store, &account, symbol)
+                .await
+                .map_err(|e| format!("{} {}", symbol, e))?;
+
+            handle.update(|v| {
+                v.synced.insert(symbol.clone(), report);
+            });
+        }
+
+        Ok(())
+    })
+}
+
+/// Symbols the account may have traded, every pair of two assets it holds.
+/// The exchange lists no traded symbols, a pair whose assets are both sold
+/// out is only synced when given or already stored
+pub async fn discover(
+    client: &Client,
+    catalogue: &Catalogue,
+) -> Result<Vec<Symbol>, Box<dyn Error>> {
+    let assets = limit::request(
+        weight::USER_ASSET,
+        client.user_asset(None, Some(false), None),
+    )
+    .await?;
+    let assets: BTreeSet<Asset> = assets.into_iter().map(|v| v.asset).collect();
+
+    let symbols = catalogue.symbols().await?;
+
+    Ok(symbols
+        .iter()
+        .filter(|v| assets.contains(&v.base_asset) && assets.contains(&v.quote_asset))
+        .map(|v| v.symbol.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use binance::types::Trade;
+
+    use super::{account_of, TradeStore};
+
+    fn trade(id: i64) -> Trade {
+        let value = serde_json::json!({
+            "symbol": "ETHUSDT",
+            "id": id,
+            "orderId": id,
+            "orderListId": -1,
+            "price": "100",
+            "qty": "1",
+            "quoteQty": "100",
+            "commission": "0",
+            "commissionAsset": "USDT",
+            "time": 1700000000000u64 + id as u64,
+            "isBuyer": true,
+            "isMaker": false,
+            "isBestMatch": true
+        });
+
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_store() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let path = std::env::temp_dir().join(format!("harmony-trades-{}", std::process::id()));
+            let symbol = "ETHUSDT".to_string();
+
+            let store = TradeStore::new(&path);
+            assert_eq!(store.last_id("key", &symbol).await.unwrap(), None);
+            assert_eq!(
+                store
+                    .append("key", &symbol, &[trade(2), trade(1), trade(2)])
+                    .await
+                    .unwrap(),
+                2
+            );
+
+            // Only the newer trades of an overlapping page are appended
+            assert_eq!(
+                store
+                    .append("key", &symbol, &[trade(2), trade(3)])
+                    .await
+                    .unwrap(),
+                1
+            );
+            assert_eq!(store.last_id("key", &symbol).await.unwrap(), Some(3));
+
+            // A new store reads the last id from the file
+            let store = TradeStore::new(&path);
+            assert_eq!(store.last_id("key", &symbol).await.unwrap(), Some(3));
+            assert_eq!(store.trades("key", &symbol).await.unwrap().len(), 3);
+            assert_eq!(store.symbols("key").await.unwrap(), vec![symbol]);
+
+            assert!(store
+                .append("../key", &"ETHUSDT".into(), &[])
+                .await
+                .is_err());
+
+            std::fs::remove_dir_all(path).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_account_of() {
+        let account = account_of("api-key");
+
+        assert_eq!(account, account_of("api-key"));
+        assert_ne!(account, account_of("other-key"));
+        assert_eq!(account.len(), 32);
+        assert!(account.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::time::timestamp;
+
+/// Finished jobs kept for their reports, the oldest are dropped first
+const RETAINED: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Done,
+    Failed,
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job<T> {
+    pub id:       u64,
+    pub status:   JobStatus,
+    pub started:  u128,
+    pub finished: Option<u128>,
+    // Latest report, a failed job keeps what it did before the error
+    pub report:   T,
+    pub error:    Option<String>,
+}
+
+/// Work that outlives the request starting it, kept in memory with its report
+pub struct Jobs<T> {
+    next_id: AtomicU64,
+    jobs: Mutex<BTreeMap<u64, Job<T>>>,
+}
+
+impl<T> Jobs<T>
+where
+    T: Clone + Send + 'static,
+{
+    pub fn new() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            jobs: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Run the task in the background, it updates the report through its handle
+    pub fn spawn<F, Fut>(self: &Arc<Self>, report: T, task: F) -> Job<T>
+    where
+        F: FnOnce(JobHandle<T>) -> Fut,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let job = Job {
+            id,
+            status: JobStatus::Running,
+            started: timestamp().as_millis(),
+            finished: None,
+            report,
+            error: None,
+        };
+
+        {
+            let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+            jobs.insert(id, job.clone());
+
+            let finished: Vec<u64> = jobs
+                .values()
+                .filter(|v| v.status != JobStatus::Running)
+                .map(|v| v.id)
+                .collect();
+            for id in finished
+                .iter()
+                .take(finished.len().saturating_sub(RETAINED))
+            {
+                jobs.remove(id);
+            }
+        }
+
+        let task = task(JobHandle {
+            id,
+            jobs: self.clone(),
+        });
+        let jobs = self.clone();
+        tokio::spawn(async move {
+            // A panic of the task fails the job instead of leaving it running
+            let result = match tokio::spawn(task).await {
+                Ok(v) => v,
+                Err(e) => Err(e.to_string()),
+            };
+
+            jobs.finish(id, result);
+        });
+
+        job
+    }
+
+    pub fn get(&self, id: u64) -> Option<Job<T>> {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.get(&id).cloned()
+    }
+
+    pub fn jobs(&self) -> Vec<Job<T>> {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.values().cloned().collect()
+    }
+
+    fn update(&self, id: u64, update: impl FnOnce(&mut T)) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(job) = jobs.get_mut(&id) {
+            update(&mut job.report);
+        }
+    }
+
+    fn finish(&self, id: u64, result: Result<(), String>) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(job) = jobs.get_mut(&id) {
+            job.finished = Some(timestamp().as_millis());
+            match result {
+                Ok(()) => job.status = JobStatus::Done,
+                Err(e) => {
+                    tracing::warn!("job {} failed: {}", id, e);
+                    job.status = JobStatus::Failed;
+                    job.error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+impl<T> Default for Jobs<T>
+where
+    T: Clone + Send + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Given to the task of a job to publish its progress
+pub struct JobHandle<T> {
+    id: u64,
+    jobs: Arc<Jobs<T>>,
+}
+
+impl<T> JobHandle<T>
+where
+    T: Clone + Send + 'static,
+{
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn update(&self, update: impl FnOnce(&mut T)) {
+        self.jobs.update(self.id, update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{JobStatus, Jobs};
+
+    #[test]
+    fn test_jobs() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let jobs = Arc::new(Jobs::new());
+
+            let done = jobs.spawn(0u32, |handle| async move {
+                handle.update(|v| *v += 2);
+                Ok(())
+            });
+            let failed = jobs.spawn(0u32, |handle| async move {
+                handle.update(|v| *v += 1);
+                Err("stopped".into())
+            });
+            assert_eq!(done.status, JobStatus::Running);
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            let done = jobs.get(done.id).unwrap();
+            assert_eq!(done.status, JobStatus::Done);
+            assert_eq!(done.report, 2);
+
+            // The progress before the error is kept
+            let failed = jobs.get(failed.id).unwrap();
+            assert_eq!(failed.status, JobStatus::Failed);
+            assert_eq!(failed.report, 1);
+            assert_eq!(failed.error.as_deref(), Some("stopped"));
+        });
+    }
+}
@@ -0,0 +1,434 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use binance::error::ClientError;
+use serde::{Deserialize, Serialize};
+
+use super::market::ErrorBody;
+use crate::time::timestamp;
+
+/// Request weight of the REST endpoints, as documented by Binance
+pub mod weight {
+    pub const PRICE: u32 = 2;
+    pub const PRICES: u32 = 4;
+    pub const EXCHANGE_INFO: u32 = 20;
+    pub const ORDER: u32 = 1;
+    pub const ORDER_INFO: u32 = 4;
+    pub const MY_TRADES: u32 = 20;
+    pub const COMMISSION: u32 = 20;
+    pub const USER_ASSET: u32 = 5;
+}
+
+/// Request weight allowed per minute
+pub const WEIGHT_LIMIT: u32 = 1200;
+/// Orders allowed per 10 seconds, counted locally only
+pub const ORDER_LIMIT: u32 = 50;
+/// Orders allowed per day, counted locally only
+pub const ORDER_DAILY_LIMIT: u32 = 160_000;
+
+const WEIGHT_WINDOW: Duration = Duration::from_secs(60);
+const ORDER_WINDOW: Duration = Duration::from_secs(10);
+const ORDER_DAILY_WINDOW: Duration = Duration::from_secs(86_400);
+
+/// Back off after a 429 without a retry hint
+const BACKOFF_TOO_MANY_REQUESTS: Duration = Duration::from_secs(60);
+/// Back off after a 418 without a retry hint
+const BACKOFF_BANNED: Duration = Duration::from_secs(120);
+
+/// Binance error code of too much request weight, a 429 or a 418 ban
+const CODE_TOO_MANY_REQUESTS: i64 = -1003;
+/// Binance error code of too many new orders, a 429
+const CODE_TOO_MANY_ORDERS: i64 = -1015;
+
+#[derive(Debug)]
+pub enum RateLimitError {
+    Exhausted(String),
+    Banned(String),
+}
+
+impl Error for RateLimitError {}
+impl std::fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::Exhausted(e) => format!("EXHAUSTED {}", e),
+            Self::Banned(e) => format!("BANNED {}", e),
+        };
+
+        write!(f, "RATE_LIMIT {}", message)
+    }
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitUsage {
+    pub weight:            u32,
+    pub weight_limit:      u32,
+    pub orders:            u32,
+    pub order_limit:       u32,
+    pub orders_daily:      u32,
+    pub order_daily_limit: u32,
+    // Remaining milliseconds of a 429/418 back off
+    pub backoff:           Option<u128>,
+}
+
+struct Window {
+    weights: VecDeque<(Instant, u32)>,
+    orders: VecDeque<Instant>,
+    orders_daily: VecDeque<Instant>,
+    // Last `X-MBX-USED-WEIGHT-1M` reported by the exchange
+    reported: Option<(Instant, u32)>,
+    backoff: Option<Instant>,
+}
+
+impl Window {
+    fn prune(&mut self, now: Instant) {
+        while let Some((t, _)) = self.weights.front() {
+            if now.duration_since(*t) < WEIGHT_WINDOW {
+                break;
+            }
+            self.weights.pop_front();
+        }
+
+        while let Some(t) = self.orders.front() {
+            if now.duration_since(*t) < ORDER_WINDOW {
+                break;
+            }
+            self.orders.pop_front();
+        }
+
+        while let Some(t) = self.orders_daily.front() {
+            if now.duration_since(*t) < ORDER_DAILY_WINDOW {
+                break;
+            }
+            self.orders_daily.pop_front();
+        }
+
+        if let Some((t, _)) = self.reported {
+            if now.duration_since(t) >= WEIGHT_WINDOW {
+                self.reported = None;
+            }
+        }
+
+        if let Some(t) = self.backoff {
+            if now >= t {
+                self.backoff = None;
+            }
+        }
+    }
+
+    fn weight(&self) -> u32 {
+        let local: u32 = self.weights.iter().map(|(_, w)| w).sum();
+
+        match self.reported {
+            Some((_, v)) => u32::max(local, v),
+            None => local,
+        }
+    }
+}
+
+/// Client side limiter of the Binance REST request weight and order count.
+///
+/// Every request is counted locally by its documented weight. The binance client
+/// does not expose its responses, so `X-MBX-USED-WEIGHT-1M` is only reconciled
+/// for the requests sent without it (market data and test orders), and a 429 or
+/// 418 of the client is only recognised from the error body in its message.
+///
+/// The order limits are local only. Orders are sent through the binance client,
+/// `X-MBX-ORDER-COUNT-10S` and `X-MBX-ORDER-COUNT-1D` are never read, so orders
+/// placed by other processes on the same account are not counted.
+pub struct RateLimiter {
+    window: Mutex<Window>,
+    weight_limit: u32,
+    max_wait: Duration,
+}
+
+impl RateLimiter {
+    pub fn new(weight_limit: u32, max_wait: Duration) -> Self {
+        let window = Window {
+            weights: VecDeque::new(),
+            orders: VecDeque::new(),
+            orders_daily: VecDeque::new(),
+            reported: None,
+            backoff: None,
+        };
+
+        Self {
+            window: Mutex::new(window),
+            weight_limit,
+            max_wait,
+        }
+    }
+
+    /// Reserve the weight, waits up to `max_wait` for the window to free up
+    pub async fn acquire(&self, weight: u32, order: bool) -> Result<(), RateLimitError> {
+        let mut waited = Duration::ZERO;
+
+        loop {
+            let wait = self.try_acquire(weight, order)?;
+            let wait = match wait {
+                Some(v) => v,
+                None => return Ok(()),
+            };
+
+            if waited + wait > self.max_wait {
+                return Err(RateLimitError::Exhausted(format!(
+                    "request weight {} would exceed the limit of {} per minute, used {}",
+                    weight,
+                    self.weight_limit,
+                    self.usage().weight
+                )));
+            }
+
+            tokio::time::sleep(wait).await;
+            waited += wait;
+        }
+    }
+
+    /// Reserve the weight now, or return how long to wait before retrying
+    fn try_acquire(&self, weight: u32, order: bool) -> Result<Option<Duration>, RateLimitError> {
+        let now = Instant::now();
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        window.prune(now);
+
+        if let Some(t) = window.backoff {
+            return Err(RateLimitError::Banned(format!(
+                "requests are backed off for {} ms",
+                t.duration_since(now).as_millis()
+            )));
+        }
+
+        if order && window.orders_daily.len() as u32 >= ORDER_DAILY_LIMIT {
+            return Err(RateLimitError::Exhausted(format!(
+                "daily order limit of {} reached",
+                ORDER_DAILY_LIMIT
+            )));
+        }
+
+        if order && window.orders.len() as u32 >= ORDER_LIMIT {
+            let wait = window
+                .orders
+                .front()
+                .map(|t| ORDER_WINDOW.saturating_sub(now.duration_since(*t)))
+                .unwrap_or_default();
+
+            return Ok(Some(wait));
+        }
+
+        if window.weight() + weight > self.weight_limit {
+            // Wait until enough of the local window expires, or the reported one resets
+            let mut free = window.weight() + weight - self.weight_limit;
+            let mut wait = WEIGHT_WINDOW;
+            for (t, w) in window.weights.iter() {
+                wait = WEIGHT_WINDOW.saturating_sub(now.duration_since(*t));
+                if *w >= free {
+                    break;
+                }
+                free -= w;
+            }
+
+            if let Some((t, _)) = window.reported {
+                wait = wait.max(WEIGHT_WINDOW.saturating_sub(now.duration_since(t)));
+            }
+
+            return Ok(Some(wait.max(Duration::from_millis(10))));
+        }
+
+        window.weights.push_back((now, weight));
+        if order {
+            window.orders.push_back(now);
+            window.orders_daily.push_back(now);
+        }
+
+        Ok(None)
+    }
+
+    /// Reconcile with the `X-MBX-USED-WEIGHT-1M` response header, where it can be read
+    pub fn observe_used_weight(&self, weight: u32) {
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        window.reported = Some((Instant::now(), weight));
+    }
+
+    /// Back off on a 429 or 418 response, `retry_after` is the `Retry-After` header
+    pub fn observe_status(&self, status: u16, retry_after: Option<Duration>) {
+        let backoff = match status {
+            429 => retry_after.unwrap_or(BACKOFF_TOO_MANY_REQUESTS),
+            418 => retry_after.unwrap_or(BACKOFF_BANNED),
+            _ => return,
+        };
+
+        tracing::warn!("binance responded {}, backing off {:?}", status, backoff);
+
+        let until = Instant::now() + backoff;
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        window.backoff = Some(window.backoff.map_or(until, |v| v.max(until)));
+    }
+
+    /// The client error does not expose the response, back off on the rate
+    /// limit codes of the Binance error body its message carries
+    pub fn observe_error(&self, error: &ClientError) {
+        if let Some((status, retry_after)) = error_status(&error.to_string()) {
+            self.observe_status(status, retry_after)
+        }
+    }
+
+    pub fn usage(&self) -> RateLimitUsage {
+        let now = Instant::now();
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        window.prune(now);
+
+        RateLimitUsage {
+            weight: window.weight(),
+            weight_limit: self.weight_limit,
+            orders: window.orders.len() as u32,
+            order_limit: ORDER_LIMIT,
+            orders_daily: window.orders_daily.len() as u32,
+            order_daily_limit: ORDER_DAILY_LIMIT,
+            backoff: window.backoff.map(|t| t.duration_since(now).as_millis()),
+        }
+    }
+}
+
+/// Status and retry hint of a rate limit error body within the message
+fn error_status(message: &str) -> Option<(u16, Option<Duration>)> {
+    let (start, end) = (message.find('{')?, message.rfind('}')?);
+    if end < start {
+        return None;
+    }
+
+    let body: ErrorBody = serde_json::from_str(&message[start..=end]).ok()?;
+    match body.code {
+        CODE_TOO_MANY_REQUESTS => match body.msg.split_once("banned until ") {
+            // Ban end in milliseconds since the epoch
+            Some((_, until)) => {
+                let until: String = until.chars().take_while(|c| c.is_ascii_digit()).collect();
+                let retry_after = until.parse::<u128>().ok().map(|v| {
+                    let millis = v.saturating_sub(timestamp().as_millis());
+                    Duration::from_millis(millis.min(u64::MAX as u128) as u64)
+                });
+
+                Some((418, retry_after))
+            }
+            None => Some((429, None)),
+        },
+        CODE_TOO_MANY_ORDERS => Some((429, None)),
+        _ => None,
+    }
+}
+
+lazy_static::lazy_static! {
+    // Binance limits are per IP, the whole process shares one limiter
+    pub static ref LIMITER: RateLimiter = RateLimiter::new(WEIGHT_LIMIT, Duration::from_secs(5));
+}
+
+/// Run a Binance REST request through the limiter
+pub async fn request<T, F>(weight: u32, future: F) -> Result<T, Box<dyn Error>>
+where
+    F: Future<Output = Result<T, ClientError>>,
+{
+    LIMITER.acquire(weight, false).await?;
+
+    match future.await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            LIMITER.observe_error(&e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+/// Run a Binance order request through the limiter, it also counts as an order
+pub async fn order<T, F>(future: F) -> Result<T, Box<dyn Error>>
+where
+    F: Future<Output = Result<T, ClientError>>,
+{
+    LIMITER.acquire(weight::ORDER, true).await?;
+
+    match future.await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            LIMITER.observe_error(&e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+pub fn usage() -> RateLimitUsage {
+    LIMITER.usage()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RateLimiter;
+
+    #[test]
+    fn test_weight_window() {
+        let limiter = RateLimiter::new(100, Duration::ZERO);
+
+        assert_eq!(limiter.try_acquire(60, false).unwrap(), None);
+        assert_eq!(limiter.try_acquire(40, false).unwrap(), None);
+        assert!(limiter.try_acquire(1, false).unwrap().is_some());
+        assert_eq!(limiter.usage().weight, 100);
+    }
+
+    #[test]
+    fn test_reported_weight() {
+        let limiter = RateLimiter::new(100, Duration::ZERO);
+        limiter.observe_used_weight(95);
+
+        assert!(limiter.try_acquire(10, false).unwrap().is_some());
+        assert_eq!(limiter.try_acquire(5, false).unwrap(), None);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let limiter = RateLimiter::new(100, Duration::ZERO);
+        limiter.observe_status(418, Some(Duration::from_secs(30)));
+
+        assert!(limiter.try_acquire(1, false).is_err());
+        assert!(limiter.usage().backoff.is_some());
+    }
+
+    #[test]
+    fn test_error_status() {
+        use super::error_status;
+
+        // Numbers of the request never look like a rate limit
+        assert_eq!(error_status("order 4291 of 418 USDT rejected"), None);
+        assert_eq!(
+            error_status(r#"400 {"code":-2010,"msg":"Account has insufficient balance 429"}"#),
+            None
+        );
+
+        assert_eq!(
+            error_status(r#"429 {"code":-1003,"msg":"Too much request weight used"}"#),
+            Some((429, None))
+        );
+        assert_eq!(
+            error_status(r#"{"code":-1015,"msg":"Too many new orders"}"#),
+            Some((429, None))
+        );
+
+        let (status, retry_after) = error_status(
+            r#"{"code":-1003,"msg":"Way too much request weight used; IP banned until 1000. Please use WebSocket Streams"}"#,
+        )
+        .unwrap();
+        assert_eq!(status, 418);
+        assert_eq!(retry_after, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_order_count() {
+        let limiter = RateLimiter::new(1000, Duration::ZERO);
+        for _ in 0..super::ORDER_LIMIT {
+            assert_eq!(limiter.try_acquire(1, true).unwrap(), None);
+        }
+
+        assert!(limiter.try_acquire(1, true).unwrap().is_some());
+        assert_eq!(limiter.try_acquire(1, false).unwrap(), None);
+    }
+}
@@ -0,0 +1,561 @@
+use std::error::Error;
+use std::time::Duration;
+
+use binance::types::{ExchangeInfo, Symbol};
+use plot::types::{Decimal, Price};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::limit::LIMITER;
+
+const API_ENDPOINT: &str = "https://api.binance.com";
+
+/// Klines returned by one request
+pub const KLINES_LIMIT: u16 = 1000;
+
+/// Pages of native klines fetched for one aggregated request, requests needing
+/// more are rejected
+const KLINES_AGGREGATE_PAGES: usize = 10;
+
+/// Binance weeks open on Monday, four days after the Thursday of the epoch
+const WEEK_OFFSET: u64 = 4 * 86_400_000;
+
+/// Native kline intervals supported by the exchange
+const INTERVALS: [&str; 16] = [
+    "1s", "1m", "3m", "5m", "15m", "30m", "1h", "2h", "4h", "6h", "8h", "12h", "1d", "3d", "1w",
+    "1M",
+];
+
+#[derive(Debug)]
+pub enum MarketError {
+    Interval(String),
+    Limit(String),
+    Response(String),
+}
+
+impl Error for MarketError {}
+impl std::fmt::Display for MarketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::Interval(e) => format!("INTERVAL {}", e),
+            Self::Limit(e) => format!("LIMIT {}", e),
+            Self::Response(e) => format!("RESPONSE {}", e),
+        };
+
+        write!(f, "MARKET {}", message)
+    }
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Kline {
+    pub open_time:              u64,
+    pub open:                   Price,
+    pub high:                   Price,
+    pub low:                    Price,
+    pub close:                  Price,
+    pub volume:                 Decimal,
+    pub close_time:             u64,
+    pub quote_volume:           Decimal,
+    pub trades:                 u64,
+    pub taker_buy_base_volume:  Decimal,
+    pub taker_buy_quote_volume: Decimal,
+}
+
+#[rustfmt::skip]
+type KlineRow = (u64, Decimal, Decimal, Decimal, Decimal, Decimal, u64, Decimal, u64, Decimal, Decimal, serde_json::Value);
+
+impl From<KlineRow> for Kline {
+    fn from(v: KlineRow) -> Self {
+        Self {
+            open_time: v.0,
+            open: v.1,
+            high: v.2,
+            low: v.3,
+            close: v.4,
+            volume: v.5,
+            close_time: v.6,
+            quote_volume: v.7,
+            trades: v.8,
+            taker_buy_base_volume: v.9,
+            taker_buy_quote_volume: v.10,
+        }
+    }
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Ticker24hr {
+    pub symbol:               Symbol,
+    pub price_change:         Decimal,
+    pub price_change_percent: Decimal,
+    pub weighted_avg_price:   Price,
+    pub prev_close_price:     Price,
+    pub last_price:           Price,
+    pub last_qty:             Decimal,
+    pub bid_price:            Price,
+    pub bid_qty:              Decimal,
+    pub ask_price:            Price,
+    pub ask_qty:              Decimal,
+    pub open_price:           Price,
+    pub high_price:           Price,
+    pub low_price:            Price,
+    pub volume:               Decimal,
+    pub quote_volume:         Decimal,
+    pub open_time:            u64,
+    pub close_time:           u64,
+    pub first_id:             i64,
+    pub last_id:              i64,
+    pub count:                u64,
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Depth {
+    pub last_update_id: u64,
+    pub bids:           Vec<(Price, Decimal)>,
+    pub asks:           Vec<(Price, Decimal)>,
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct MarketTrade {
+    pub id:             i64,
+    pub price:          Price,
+    pub qty:            Decimal,
+    pub quote_qty:      Decimal,
+    pub time:           u64,
+    pub is_buyer_maker: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerPrice {
+    pub symbol: Symbol,
+    pub price: Price,
+}
+
+#[derive(Deserialize)]
+pub(super) struct ErrorBody {
+    pub(super) code: i64,
+    pub(super) msg: String,
+}
+
+/// Public market data REST client
+pub struct Market {
+    http: reqwest::Client,
+    endpoint: String,
+}
+
+impl Market {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()?;
+
+        Ok(Self {
+            http,
+            endpoint: API_ENDPOINT.into(),
+        })
+    }
+
+    /// Send a GET request through the rate limiter and record the reported weight
+    pub(super) async fn get<T>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        weight: u32,
+    ) -> Result<T, Box<dyn Error>>
+    where
+        T: DeserializeOwned,
+    {
+        LIMITER.acquire(weight, false).await?;
+
+        let response = self
+            .http
+            .get(format!("{}{}", self.endpoint, path))
+            .query(query)
+            .send()
+            .await?;
+
+        let used_weight = header_u64(&response, "x-mbx-used-weight-1m");
+        let retry_after = header_u64(&response, "retry-after");
+
+        if let Some(v) = used_weight {
+            LIMITER.observe_used_weight(v as u32);
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            LIMITER.observe_status(status.as_u16(), retry_after.map(Duration::from_secs));
+
+            let body = response.text().await.unwrap_or_default();
+            let message = match serde_json::from_str::<ErrorBody>(&body) {
+                Ok(v) => format!("{} {}", v.code, v.msg),
+                Err(_) => format!("{} {}", status, body),
+            };
+
+            return Err(Box::new(MarketError::Response(message)));
+        }
+
+        Ok(response.json::<T>().await?)
+    }
+
+    pub async fn klines(
+        &self,
+        symbol: &Symbol,
+        interval: &str,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        limit: Option<u16>,
+    ) -> Result<Vec<Kline>, Box<dyn Error>> {
+        let mut query = vec![
+            ("symbol", symbol.clone()),
+            ("interval", interval.to_string()),
+        ];
+        if let Some(v) = start_time {
+            query.push(("startTime", v.to_string()));
+        }
+        if let Some(v) = end_time {
+            query.push(("endTime", v.to_string()));
+        }
+        if let Some(v) = limit {
+            query.push(("limit", v.to_string()));
+        }
+
+        let rows: Vec<KlineRow> = self.get("/api/v3/klines", &query, 2).await?;
+
+        Ok(rows.into_iter().map(Kline::from).collect())
+    }
+
+    /// Klines of any interval, custom intervals are aggregated from a native one
+    pub async fn klines_aggregated(
+        &self,
+        symbol: &Symbol,
+        interval: &str,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        limit: Option<u16>,
+    ) -> Result<Vec<Kline>, Box<dyn Error>> {
+        if INTERVALS.contains(&interval) {
+            return self
+                .klines(symbol, interval, start_time, end_time, limit)
+                .await;
+        }
+
+        if interval.ends_with('M') {
+            return Err(Box::new(MarketError::Interval(format!(
+                "months vary in length, custom interval {} is not supported",
+                interval
+            ))));
+        }
+
+        let custom = interval_millis(interval)?;
+        let (native, native_millis) = native_interval_of(custom)?;
+        let ratio = (custom / native_millis) as usize;
+        let limit = limit.unwrap_or(500).clamp(1, KLINES_LIMIT) as usize;
+
+        let pages = (ratio * limit).div_ceil(KLINES_LIMIT as usize);
+        if pages > KLINES_AGGREGATE_PAGES {
+            return Err(Box::new(MarketError::Limit(format!(
+                "{} klines of {} need {} pages of {} klines, at most {} are fetched",
+                limit, interval, pages, native, KLINES_AGGREGATE_PAGES
+            ))));
+        }
+
+        // Fetch enough native klines for the requested count, a page at a time
+        let end_time = match (start_time, end_time) {
+            (None, None) => Some(crate::time::timestamp().as_millis() as u64),
+            (_, v) => v,
+        };
+        let start_time = match start_time {
+            Some(v) => bucket_of(v, custom),
+            None => {
+                let span = custom.checked_mul(limit as u64 - 1).ok_or_else(|| {
+                    MarketError::Interval(format!("interval {} is too long", interval))
+                })?;
+
+                bucket_of(end_time.unwrap_or_default(), custom).saturating_sub(span)
+            }
+        };
+
+        let mut rows = Vec::new();
+        let mut from = start_time;
+        for _ in 0..pages {
+            if rows.len() >= ratio * limit {
+                break;
+            }
+
+            let page = self
+                .klines(symbol, native, Some(from), end_time, Some(KLINES_LIMIT))
+                .await?;
+
+            let last = match page.last() {
+                Some(v) => v.close_time,
+                None => break,
+            };
+
+            let full = page.len() == KLINES_LIMIT as usize;
+            rows.extend(page);
+
+            if !full {
+                break;
+            }
+            from = last + 1;
+        }
+
+        let mut klines = aggregate(&rows, custom);
+        klines.truncate(limit);
+
+        Ok(klines)
+    }
+
+    pub async fn ticker_24hr(&self, symbol: &Symbol) -> Result<Ticker24hr, Box<dyn Error>> {
+        let query = [("symbol", symbol.clone())];
+
+        self.get("/api/v3/ticker/24hr", &query, 2).await
+    }
+
+    pub async fn depth(
+        &self,
+        symbol: &Symbol,
+        limit: Option<u16>,
+    ) -> Result<Depth, Box<dyn Error>> {
+        let limit = limit.unwrap_or(100);
+        let weight = match limit {
+            0..=100 => 5,
+            101..=500 => 25,
+            501..=1000 => 50,
+            _ => 250,
+        };
+        let query = [("symbol", symbol.clone()), ("limit", limit.to_string())];
+
+        self.get("/api/v3/depth", &query, weight).await
+    }
+
+    /// Latest public trades of the symbol, oldest first
+    pub async fn trades(
+        &self,
+        symbol: &Symbol,
+        limit: Option<u16>,
+    ) -> Result<Vec<MarketTrade>, Box<dyn Error>> {
+        let limit = limit.unwrap_or(500).clamp(1, 1000);
+        let query = [("symbol", symbol.clone()), ("limit", limit.to_string())];
+
+        self.get("/api/v3/trades", &query, 25).await
+    }
+
+    /// Exchange info of several symbols in one request
+    pub async fn exchange_info(&self, symbols: &[Symbol]) -> Result<ExchangeInfo, Box<dyn Error>> {
+        let query = [("symbols", serde_json::to_string(symbols)?)];
+
+        self.get("/api/v3/exchangeInfo", &query, 20).await
+    }
+
+    /// Latest prices of several symbols in one request
+    pub async fn prices(&self, symbols: &[Symbol]) -> Result<Vec<TickerPrice>, Box<dyn Error>> {
+        let query = [("symbols", serde_json::to_string(symbols)?)];
+
+        self.get("/api/v3/ticker/price", &query, 4).await
+    }
+
+    pub async fn price(&self, symbol: &Symbol) -> Result<Price, Box<dyn Error>> {
+        let query = [("symbol", symbol.clone())];
+        let ticker: TickerPrice = self.get("/api/v3/ticker/price", &query, 2).await?;
+
+        Ok(ticker.price)
+    }
+}
+
+pub(super) fn header_u64(response: &reqwest::Response, name: &str) -> Option<u64> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+}
+
+/// Symbols of a comma separated list, upper cased and without duplicates
+pub fn split_symbols(value: &str) -> Vec<Symbol> {
+    let mut symbols: Vec<Symbol> = Vec::new();
+    for symbol in value.split(',').map(|v| v.trim().to_uppercase()) {
+        if !symbol.is_empty() && !symbols.contains(&symbol) {
+            symbols.push(symbol);
+        }
+    }
+
+    symbols
+}
+
+/// Interval length in milliseconds, as `<number><s|m|h|d|w|M>`
+///
+/// A month counts as 30 days, it is only exact for the native `1M` klines.
+pub fn interval_millis(interval: &str) -> Result<u64, MarketError> {
+    let invalid = || MarketError::Interval(format!("invalid kline interval {}", interval));
+
+    if interval.is_empty() || !interval.is_ascii() {
+        return Err(invalid());
+    }
+
+    let (value, unit) = interval.split_at(interval.len().saturating_sub(1));
+    let value: u64 = value.parse().map_err(|_| invalid())?;
+    let unit = match unit {
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        "w" => 604_800_000,
+        "M" => 2_592_000_000,
+        _ => return Err(invalid()),
+    };
+
+    if value == 0 {
+        return Err(invalid());
+    }
+
+    value
+        .checked_mul(unit)
+        .ok_or_else(|| MarketError::Interval(format!("kline interval {} is too long", interval)))
+}
+
+/// Largest native interval that evenly divides the custom one
+fn native_interval_of(millis: u64) -> Result<(&'static str, u64), MarketError> {
+    INTERVALS
+        .iter()
+        .rev()
+        .filter(|v| !v.ends_with('M'))
+        .filter_map(|v| interval_millis(v).ok().map(|m| (*v, m)))
+        .find(|(_, m)| millis % m == 0)
+        .ok_or(MarketError::Interval(format!(
+            "interval of {} ms is not a multiple of a native interval",
+            millis
+        )))
+}
+
+/// Open time of the bucket holding `time`, buckets of whole weeks open on Monday as on
+/// Binance and the others are aligned to the epoch
+fn bucket_of(time: u64, millis: u64) -> u64 {
+    let offset = match millis % 604_800_000 {
+        0 => WEEK_OFFSET % millis,
+        _ => 0,
+    };
+
+    match time.checked_sub(offset) {
+        Some(v) => time - v % millis,
+        None => 0,
+    }
+}
+
+/// Merge consecutive klines into buckets of `millis`, see `bucket_of` for their alignment
+pub fn aggregate(klines: &[Kline], millis: u64) -> Vec<Kline> {
+    let mut result: Vec<Kline> = Vec::new();
+
+    for kline in klines.iter() {
+        let open_time = bucket_of(kline.open_time, millis);
+
+        match result.last_mut() {
+            Some(v) if v.open_time == open_time => {
+                v.high = v.high.max(kline.high);
+                v.low = v.low.min(kline.low);
+                v.close = kline.close;
+                v.close_time = kline.close_time;
+                v.volume += kline.volume;
+                v.quote_volume += kline.quote_volume;
+                v.trades += kline.trades;
+                v.taker_buy_base_volume += kline.taker_buy_base_volume;
+                v.taker_buy_quote_volume += kline.taker_buy_quote_volume;
+            }
+            _ => {
+                let mut kline = kline.clone();
+                kline.open_time = open_time;
+                result.push(kline);
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use plot::types::Decimal;
+
+    use super::{aggregate, bucket_of, interval_millis, native_interval_of, split_symbols, Kline};
+
+    fn kline(open_time: u64, open: i64, high: i64, low: i64, close: i64) -> Kline {
+        Kline {
+            open_time,
+            open: Decimal::from(open),
+            high: Decimal::from(high),
+            low: Decimal::from(low),
+            close: Decimal::from(close),
+            volume: Decimal::ONE,
+            close_time: open_time + 59_999,
+            quote_volume: Decimal::from(close),
+            trades: 1,
+            taker_buy_base_volume: Decimal::ONE,
+            taker_buy_quote_volume: Decimal::from(close),
+        }
+    }
+
+    #[test]
+    fn test_interval() {
+        assert_eq!(interval_millis("90m").unwrap(), 5_400_000);
+        assert_eq!(native_interval_of(5_400_000).unwrap().0, "30m");
+        assert_eq!(native_interval_of(10_800_000).unwrap().0, "1h");
+        assert!(interval_millis("0m").is_err());
+        assert!(interval_millis("5y").is_err());
+        assert!(interval_millis("99999999999999w").is_err());
+        assert_eq!(interval_millis("1M").unwrap(), 2_592_000_000);
+        assert_eq!(native_interval_of(5_184_000_000).unwrap().0, "3d");
+    }
+
+    #[test]
+    fn test_bucket_of() {
+        // Monday 2024-01-01 00:00 UTC and the Thursday after it
+        let monday = 1_704_067_200_000;
+        let thursday = monday + 3 * 86_400_000;
+
+        assert_eq!(bucket_of(thursday, 604_800_000), monday);
+        assert_eq!(
+            bucket_of(thursday + 604_800_000, 1_209_600_000) % 604_800_000,
+            monday % 604_800_000
+        );
+        assert_eq!(
+            bucket_of(thursday + 3_600_000, 3_600_000),
+            thursday + 3_600_000
+        );
+        assert_eq!(bucket_of(1_000, 604_800_000), 0);
+    }
+
+    #[test]
+    fn test_split_symbols() {
+        assert_eq!(
+            split_symbols("btcusdt, ETHUSDT,,BTCUSDT"),
+            vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let klines = vec![
+            kline(0, 10, 12, 9, 11),
+            kline(60_000, 11, 15, 10, 14),
+            kline(120_000, 14, 14, 8, 9),
+            kline(180_000, 9, 10, 7, 8),
+        ];
+
+        let result = aggregate(&klines, 120_000);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].open, Decimal::from(10));
+        assert_eq!(result[0].high, Decimal::from(15));
+        assert_eq!(result[0].low, Decimal::from(9));
+        assert_eq!(result[0].close, Decimal::from(14));
+        assert_eq!(result[0].volume, Decimal::from(2));
+        assert_eq!(result[1].open_time, 120_000);
+        assert_eq!(result[1].low, Decimal::from(7));
+        assert_eq!(result[1].close_time, 239_999);
+    }
+}
@@ -1,15 +1,39 @@
+pub mod accounting;
+pub mod alert;
+pub mod catalogue;
+pub mod execution;
 pub mod filter;
+#[cfg(test)]
+mod fixture;
+pub mod guard;
+pub mod history;
+pub mod job;
+pub mod limit;
+pub mod market;
+pub mod notify;
+pub mod recommend;
+pub mod risk;
+pub mod strategy;
+pub mod validate;
 
 use binance::{
     prelude::{Client, ClientBuilder},
-    types::{OrderSide, SymbolInfo},
+    types::{OrderResponseFull, OrderSide, Symbol, SymbolInfo},
 };
 use filter::error::SymbolFilterError;
 use plot::{
     trade::{Trade, Trader},
     types::{BaseQuantity, Decimal, Price, QuoteQuantity},
 };
-use std::{error::Error, time::Duration};
+use risk::RiskEngine;
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use validate::{OrderQuantity, OrderTest};
+
+use crate::services::webhook::Outbox;
 
 pub fn client() -> Result<Client, Box<dyn Error>> {
     let result = ClientBuilder::new().build()?;
@@ -27,14 +51,79 @@ pub fn client_with_sign(api_key: String, secret_key: String) -> Result<Client, B
     Ok(result)
 }
 
+pub async fn symbol_info(client: &Client, symbol: &Symbol) -> Result<SymbolInfo, Box<dyn Error>> {
+    let info = client.exchange_info(symbol);
+    let mut info = limit::request(limit::weight::EXCHANGE_INFO, info).await?;
+    match info.symbols.pop() {
+        Some(v) => Ok(v),
+        None => Err(format!("symbol {} exchange info not found", symbol).into()),
+    }
+}
+
+/// Taker commission of the account on the symbol, standard plus tax,
+/// market orders always pay the taker rate
+pub async fn taker_commission(client: &Client, symbol: &Symbol) -> Result<Decimal, Box<dyn Error>> {
+    use std::str::FromStr;
+
+    let commission = client.spot_commission(symbol);
+    let commission = limit::request(limit::weight::COMMISSION, commission).await?;
+
+    let standard = Decimal::from_str(&commission.standard_commission.taker)?;
+    let tax = Decimal::from_str(&commission.tax_commission.taker)?;
+
+    Ok(standard + tax)
+}
+
 pub struct BinanceSpotTest {
     normal: SymbolInfo,
     commission: Decimal,
+    validation: Option<OrderTest>,
 }
 
 impl BinanceSpotTest {
     pub fn new(normal: SymbolInfo, commission: Decimal) -> Self {
-        Self { normal, commission }
+        Self {
+            normal,
+            commission,
+            validation: None,
+        }
+    }
+
+    /// Also send every order to the exchange test endpoint, the commission
+    /// rates it computes replace the configured one
+    pub fn with_validation(mut self, validation: OrderTest) -> Self {
+        self.validation = Some(validation);
+
+        self
+    }
+
+    async fn validate(
+        &self,
+        side: &OrderSide,
+        quantity: &OrderQuantity,
+    ) -> Result<Decimal, Box<dyn Error>> {
+        let validation = match &self.validation {
+            Some(v) => v,
+            None => return Ok(self.commission),
+        };
+
+        let result = validation
+            .market_order(&self.normal.symbol, side, quantity)
+            .await?;
+
+        Ok(result.taker().unwrap_or(self.commission))
+    }
+
+    /// Simulate the orders of the account with the real symbol filters and its commission
+    pub async fn with_account(client: &Client, symbol: &Symbol) -> Result<Self, Box<dyn Error>> {
+        let normal = symbol_info(client, symbol).await?;
+        let commission = taker_commission(client, symbol).await?;
+
+        Ok(Self::new(normal, commission))
+    }
+
+    pub fn commission(&self) -> Decimal {
+        self.commission
     }
 }
 
@@ -46,7 +135,10 @@ impl Trader for BinanceSpotTest {
     ) -> Result<Vec<Trade>, Box<dyn Error>> {
         let quote_quantity = self.normal.correct_quote_quantity(price, quantity)?;
         self.normal.filter_quote_quantity(price, &quote_quantity)?;
-        let base_quantity = (quote_quantity / price) * (Decimal::ONE - self.commission);
+        let commission = self
+            .validate(&OrderSide::Buy, &OrderQuantity::Quote(quote_quantity))
+            .await?;
+        let base_quantity = (quote_quantity / price) * (Decimal::ONE - commission);
         let base_quantity = base_quantity.trunc_with_scale(self.normal.base_asset_precision.into());
         let trade = Trade::with_buy(price.clone(), base_quantity, quote_quantity);
 
@@ -60,7 +152,10 @@ impl Trader for BinanceSpotTest {
     ) -> Result<Vec<Trade>, Box<dyn Error>> {
         let base_quantity = self.normal.correct_base_quantity(price, quantity)?;
         self.normal.filter_base_quantity(price, &base_quantity)?;
-        let quote_quantity = (base_quantity * price) * (Decimal::ONE - self.commission);
+        let commission = self
+            .validate(&OrderSide::Sell, &OrderQuantity::Base(base_quantity))
+            .await?;
+        let quote_quantity = (base_quantity * price) * (Decimal::ONE - commission);
         let quote_quantity =
             quote_quantity.trunc_with_scale(self.normal.quote_asset_precision.into());
         let trade = Trade::with_sell(price.clone(), base_quantity, quote_quantity);
@@ -70,13 +165,71 @@ impl Trader for BinanceSpotTest {
 }
 
 pub struct BinanceSpot {
-    client: Client,
+    // Shared with the background reads of the trade events
+    client: Arc<Client>,
     normal: SymbolInfo,
+    risk: Option<(Arc<RiskEngine>, String)>,
+    outbox: Option<(Arc<Outbox>, String)>,
+    position: Mutex<Option<usize>>,
 }
 
 impl BinanceSpot {
     pub fn new(normal: SymbolInfo, client: Client) -> Self {
-        Self { client, normal }
+        Self {
+            client: Arc::new(client),
+            normal,
+            risk: None,
+            outbox: None,
+            position: Mutex::new(None),
+        }
+    }
+
+    /// Check every order against the limits of the account before sending it
+    pub fn with_risk(mut self, risk: Arc<RiskEngine>, account: String) -> Self {
+        self.risk = Some((risk, account));
+
+        self
+    }
+
+    /// Queue a trade event of every executed order for the webhooks
+    pub fn with_outbox(mut self, outbox: Arc<Outbox>, account: String) -> Self {
+        self.outbox = Some((outbox, account));
+
+        self
+    }
+
+    /// Bot position the next orders belong to, reported in the trade events
+    pub fn set_position(&self, position: Option<usize>) {
+        *self.position.lock().unwrap_or_else(|e| e.into_inner()) = position;
+    }
+
+    async fn notify(&self, order: &OrderResponseFull) {
+        if let Some((outbox, account)) = &self.outbox {
+            let position = *self.position.lock().unwrap_or_else(|e| e.into_inner());
+            notify::notify(
+                outbox,
+                &self.client,
+                account,
+                &self.normal.symbol,
+                order,
+                position,
+            )
+            .await;
+        }
+    }
+
+    fn allow(&self, side: &OrderSide, notional: &QuoteQuantity) -> Result<(), Box<dyn Error>> {
+        if let Some((risk, account)) = &self.risk {
+            risk.allow(account, &self.normal.symbol, side, notional)?;
+        }
+
+        Ok(())
+    }
+
+    fn release(&self, side: &OrderSide, notional: &QuoteQuantity) {
+        if let Some((risk, account)) = &self.risk {
+            risk.release(account, side, notional);
+        }
     }
 }
 
@@ -88,17 +241,22 @@ impl Trader for BinanceSpot {
     ) -> Result<Vec<Trade>, Box<dyn Error>> {
         let quantity = self.normal.correct_quote_quantity(price, quantity)?;
         self.normal.filter_quote_quantity(price, &quantity)?;
+        self.allow(&OrderSide::Buy, &quantity)?;
 
         // Buy the base quantity by the quoted quantity
-        let order = self
-            .client
-            .spot_market_order_with_quote(
-                &self.normal.symbol,
-                OrderSide::Buy,
-                &quantity.to_string(),
-                None,
-            )
-            .await?;
+        let notional = quantity;
+        let quantity = quantity.to_string();
+        let order = self.client.spot_market_order_with_quote(
+            &self.normal.symbol,
+            OrderSide::Buy,
+            &quantity,
+            None,
+        );
+        let order = limit::order(order).await.map_err(|e| {
+            self.release(&OrderSide::Buy, &notional);
+            e
+        })?;
+        self.notify(&order).await;
 
         Ok(order.to_trades())
     }
@@ -110,16 +268,21 @@ impl Trader for BinanceSpot {
     ) -> Result<Vec<Trade>, Box<dyn Error>> {
         let quantity = self.normal.correct_base_quantity(price, quantity)?;
         self.normal.filter_base_quantity(price, &quantity)?;
+        let notional = quantity * price;
+        self.allow(&OrderSide::Sell, &notional)?;
 
-        let order = self
-            .client
-            .spot_market_order_with_base(
-                &self.normal.symbol,
-                OrderSide::Sell,
-                &quantity.to_string(),
-                None,
-            )
-            .await?;
+        let quantity = quantity.to_string();
+        let order = self.client.spot_market_order_with_base(
+            &self.normal.symbol,
+            OrderSide::Sell,
+            &quantity,
+            None,
+        );
+        let order = limit::order(order).await.map_err(|e| {
+            self.release(&OrderSide::Sell, &notional);
+            e
+        })?;
+        self.notify(&order).await;
 
         Ok(order.to_trades())
     }
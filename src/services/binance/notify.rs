@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use binance::prelude::Client;
+use binance::types::{OrderResponseFull, OrderSide, Symbol, UserAsset};
+use plot::trade::Trade;
+use serde::{Deserialize, Serialize};
+
+use super::limit::{self, weight};
+use super::ConvertTrades;
+use crate::services::webhook::Outbox;
+use crate::time::timestamp;
+
+pub const EVENT: &str = "trade";
+
+/// Characters of the api key identifying the account in the events
+const ACCOUNT_PREFIX: usize = 8;
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeEvent {
+    pub account:   String,
+    pub symbol:    Symbol,
+    pub side:      OrderSide,
+    // Index of the bot position that placed the order
+    pub position:  Option<usize>,
+    pub fills:     Vec<Trade>,
+    pub order:     OrderResponseFull,
+    // Account assets after the order, missing when they could not be read
+    pub balances:  Option<Vec<UserAsset>>,
+    pub timestamp: u128,
+}
+
+/// Queue the executed order for the configured webhooks, never fails the order.
+/// The balances are read in the background, the event is held until then
+pub async fn notify(
+    outbox: &Arc<Outbox>,
+    client: &Arc<Client>,
+    account: &str,
+    symbol: &Symbol,
+    order: &OrderResponseFull,
+    position: Option<usize>,
+) {
+    if !outbox.is_enabled() {
+        return;
+    }
+
+    let mut event = TradeEvent {
+        account: account.chars().take(ACCOUNT_PREFIX).collect(),
+        symbol: symbol.clone(),
+        side: order.side.clone(),
+        position,
+        fills: order.to_trades(),
+        order: order.clone(),
+        balances: None,
+        timestamp: timestamp().as_millis(),
+    };
+
+    let ids = match outbox.hold(EVENT, &event).await {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::error!("trade event of {} not queued: {}", symbol, e);
+            return;
+        }
+    };
+
+    let (outbox, client) = (outbox.clone(), client.clone());
+    tokio::spawn(async move {
+        let balances = limit::request(
+            weight::USER_ASSET,
+            client.user_asset(None, Some(false), None),
+        );
+        match balances.await {
+            Ok(v) => event.balances = Some(v),
+            Err(e) => tracing::warn!("trade event balances of {}: {}", event.symbol, e),
+        };
+
+        // Released also without the balances
+        if let Err(e) = outbox.amend(&ids, &event).await {
+            tracing::error!("trade event of {} not released: {}", event.symbol, e);
+        }
+    });
+}
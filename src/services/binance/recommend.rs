@@ -0,0 +1,406 @@
+use binance::types::SymbolInfo;
+use plot::types::{Decimal, Price, QuoteQuantity};
+use serde::{Deserialize, Serialize};
+
+use super::filter::spot;
+use super::market::Kline;
+use super::ConvertFilter;
+
+/// Klines used for the average true range
+const ATR_PERIOD: usize = 14;
+
+/// Percentile of lows and highs taken as support and resistance, ignores the wicks
+const RANGE_PERCENTILE: f64 = 0.05;
+
+/// Upper bound of the proposed level count
+const MAX_LEVELS: u32 = 200;
+
+const YEAR_MILLIS: f64 = 365.0 * 86_400_000.0;
+
+#[derive(Debug)]
+pub enum RecommendError {
+    Klines(String),
+    Investment(String),
+    Step(String),
+}
+
+impl std::error::Error for RecommendError {}
+impl std::fmt::Display for RecommendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::Klines(e) => format!("KLINES {}", e),
+            Self::Investment(e) => format!("INVESTMENT {}", e),
+            Self::Step(e) => format!("STEP {}", e),
+        };
+
+        write!(f, "RECOMMEND {}", message)
+    }
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub price:                 Price,
+    pub atr:                   Price,
+    // Standard deviation of the log returns per kline, and per year
+    pub volatility:            f64,
+    pub volatility_annualised: f64,
+    pub support:               Price,
+    pub resistance:            Price,
+    pub lower:                 Price,
+    pub upper:                 Price,
+    pub levels:                u32,
+    // Geometric step between two levels, 0.01 is 1%
+    pub step:                  Decimal,
+    pub level_quote_quantity:  QuoteQuantity,
+    // Profit of one filled level after the buy and sell commission
+    pub level_profit:          Decimal,
+    pub prices:                Vec<Price>,
+}
+
+/// Propose grid bounds, level count and level size from recent klines
+pub fn recommend(
+    normal: &SymbolInfo,
+    klines: &[Kline],
+    interval_millis: u64,
+    investment: &QuoteQuantity,
+    commission: &Decimal,
+    min_profit: &Decimal,
+) -> Result<Recommendation, Box<dyn std::error::Error>> {
+    if klines.len() <= ATR_PERIOD {
+        return Err(Box::new(RecommendError::Klines(format!(
+            "at least {} klines required, got {}",
+            ATR_PERIOD + 1,
+            klines.len()
+        ))));
+    }
+
+    let price = klines.last().map(|v| v.close).unwrap_or_default();
+    if price.is_zero() {
+        return Err(Box::new(RecommendError::Klines(
+            "last close price is zero".into(),
+        )));
+    }
+
+    let atr = atr(klines, ATR_PERIOD);
+    let volatility = volatility(klines);
+    let volatility_annualised = volatility * (YEAR_MILLIS / interval_millis as f64).sqrt();
+
+    let support = percentile(klines.iter().map(|v| v.low).collect(), RANGE_PERCENTILE);
+    let resistance = percentile(
+        klines.iter().map(|v| v.high).collect(),
+        1.0 - RANGE_PERCENTILE,
+    );
+
+    // Keep the current price inside the grid
+    let lower = support.min(price - atr);
+    let upper = resistance.max(price + atr);
+    if lower <= Decimal::ZERO {
+        return Err(Box::new(RecommendError::Klines(format!(
+            "lower bound {} is not positive",
+            lower
+        ))));
+    }
+
+    // A level must cover the buy and sell commission and the required profit,
+    // and is not narrower than one average true range
+    let min_step = {
+        let kept = (Decimal::ONE - commission) * (Decimal::ONE - commission);
+        let fee_step = (Decimal::ONE + min_profit) / kept - Decimal::ONE;
+        let atr_step = atr / price;
+
+        fee_step.max(atr_step)
+    };
+    if min_step <= Decimal::ZERO {
+        return Err(Box::new(RecommendError::Step(format!(
+            "step {} is not positive, check the commission and the minimum profit",
+            min_step
+        ))));
+    }
+
+    let span = to_f64(&(upper / lower)).ln();
+    let max_levels = (span / to_f64(&(Decimal::ONE + min_step)).ln()).floor();
+    let max_levels = max_levels.clamp(1.0, MAX_LEVELS as f64) as u32;
+
+    let precision = normal.quote_asset_precision.into();
+    let mut levels = max_levels;
+    while levels > 0 {
+        // Fewer levels spread over the same range with a wider step
+        let step = Decimal::try_from((span / levels as f64).exp() - 1.0)
+            .unwrap_or_default()
+            .round_dp(8)
+            .max(min_step);
+
+        let quantity = (investment / Decimal::from(levels)).trunc_with_scale(precision);
+        let prices = level_prices(normal, &lower, &step, levels)?;
+
+        if fits_filters(normal, &prices, &quantity, commission) {
+            let kept = (Decimal::ONE - commission) * (Decimal::ONE - commission);
+            let level_profit = (Decimal::ONE + step) * kept - Decimal::ONE;
+
+            return Ok(Recommendation {
+                price,
+                atr,
+                volatility,
+                volatility_annualised,
+                support,
+                resistance,
+                lower: prices.first().cloned().unwrap_or(lower),
+                upper: prices.last().cloned().unwrap_or(upper),
+                levels,
+                step,
+                level_quote_quantity: quantity,
+                level_profit,
+                prices,
+            });
+        }
+
+        levels -= 1;
+    }
+
+    Err(Box::new(RecommendError::Investment(format!(
+        "investment {} is too small for a single level between {} and {}",
+        investment, lower, upper
+    ))))
+}
+
+/// Prices of the grid on the tick size, `levels` buys each selling one step above
+fn level_prices(
+    normal: &SymbolInfo,
+    lower: &Price,
+    step: &Decimal,
+    levels: u32,
+) -> Result<Vec<Price>, Box<dyn std::error::Error>> {
+    let mut prices = Vec::with_capacity(levels as usize + 1);
+    let mut price = lower.clone();
+
+    for _ in 0..=levels {
+        prices.push(spot::price::correct(normal, &price)?);
+        price = price.checked_mul(Decimal::ONE + step).ok_or_else(|| {
+            RecommendError::Step(format!("level price above {} overflows", price))
+        })?;
+    }
+
+    Ok(prices)
+}
+
+/// Every level buys with the quote quantity and sells the bought base one level above
+fn fits_filters(
+    normal: &SymbolInfo,
+    prices: &[Price],
+    quantity: &QuoteQuantity,
+    commission: &Decimal,
+) -> bool {
+    for level in prices.windows(2) {
+        let (buy, sell) = (&level[0], &level[1]);
+
+        // The tick size swallowed the step
+        if sell <= buy || buy.is_zero() {
+            return false;
+        }
+
+        let quote = match normal.correct_quote_quantity(buy, quantity) {
+            Ok(v) => v,
+            Err(_) => return false,
+        };
+        if normal.filter_quote_quantity(buy, &quote).is_err() {
+            return false;
+        }
+
+        let base = (quote / buy) * (Decimal::ONE - commission);
+        let base = match normal.correct_base_quantity(sell, &base) {
+            Ok(v) => v,
+            Err(_) => return false,
+        };
+        if normal.filter_base_quantity(sell, &base).is_err() {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Simple average of the true range over the last `period` klines
+pub fn atr(klines: &[Kline], period: usize) -> Price {
+    let ranges: Vec<Price> = klines
+        .windows(2)
+        .map(|v| {
+            let (prev, curr) = (&v[0], &v[1]);
+            let high_low = curr.high - curr.low;
+            let high_close = (curr.high - prev.close).abs();
+            let low_close = (curr.low - prev.close).abs();
+
+            high_low.max(high_close).max(low_close)
+        })
+        .collect();
+
+    let period = period.min(ranges.len());
+    if period == 0 {
+        return Decimal::ZERO;
+    }
+
+    let sum: Price = ranges[ranges.len() - period..].iter().sum();
+
+    sum / Decimal::from(period as u64)
+}
+
+/// Sample standard deviation of the close to close log returns
+pub fn volatility(klines: &[Kline]) -> f64 {
+    let returns: Vec<f64> = klines
+        .windows(2)
+        .filter(|v| !v[0].close.is_zero())
+        .map(|v| to_f64(&(v[1].close / v[0].close)).ln())
+        .collect();
+
+    if returns.len() < 2 {
+        return 0.0;
+    }
+
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let variance = returns.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+
+    variance.sqrt()
+}
+
+pub fn percentile(mut values: Vec<Price>, rank: f64) -> Price {
+    if values.is_empty() {
+        return Decimal::ZERO;
+    }
+
+    values.sort();
+    let index = ((values.len() - 1) as f64 * rank.clamp(0.0, 1.0)).round() as usize;
+
+    values[index]
+}
+
+fn to_f64(value: &Decimal) -> f64 {
+    f64::try_from(*value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use binance::types::SymbolInfo;
+    use plot::types::Decimal;
+
+    use super::super::fixture::{self, dec};
+    use super::super::market::Kline;
+    use super::{atr, fits_filters, percentile, recommend, volatility};
+
+    fn normal() -> SymbolInfo {
+        fixture::symbol_info(&[fixture::PRICE_FILTER, fixture::LOT_SIZE, fixture::NOTIONAL])
+    }
+
+    /// Closes oscillating between 98 and 102
+    fn klines() -> Vec<Kline> {
+        (0..30)
+            .map(|i| {
+                let close = 98 + i % 5;
+                kline(close + 1, close - 1, close)
+            })
+            .collect()
+    }
+
+    fn kline(high: i64, low: i64, close: i64) -> Kline {
+        Kline {
+            open_time: 0,
+            open: Decimal::from(close),
+            high: Decimal::from(high),
+            low: Decimal::from(low),
+            close: Decimal::from(close),
+            volume: Decimal::ZERO,
+            close_time: 0,
+            quote_volume: Decimal::ZERO,
+            trades: 0,
+            taker_buy_base_volume: Decimal::ZERO,
+            taker_buy_quote_volume: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_atr() {
+        let klines = vec![kline(12, 8, 10), kline(11, 9, 10), kline(16, 12, 14)];
+
+        // true ranges 2 and 6 (gap up from the close of 10)
+        assert_eq!(atr(&klines, 14), Decimal::from(4));
+        assert_eq!(atr(&klines, 1), Decimal::from(6));
+    }
+
+    #[test]
+    fn test_volatility() {
+        let flat = vec![kline(10, 10, 10); 5];
+        assert_eq!(volatility(&flat), 0.0);
+
+        let moving = vec![kline(10, 10, 10), kline(11, 11, 11), kline(10, 10, 10)];
+        assert!(volatility(&moving) > 0.09);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values = (1..=101).map(Decimal::from).collect();
+        assert_eq!(percentile(values, 0.05), Decimal::from(6));
+    }
+
+    #[test]
+    fn test_fits_filters() {
+        let commission = dec("0.001");
+        let prices = vec![dec("100"), dec("101"), dec("102.01")];
+
+        assert!(fits_filters(&normal(), &prices, &dec("10"), &commission));
+        // Below the minimum notional of 5
+        assert!(!fits_filters(&normal(), &prices, &dec("4"), &commission));
+        // Two levels on the same tick
+        let prices = vec![dec("100"), dec("100")];
+        assert!(!fits_filters(&normal(), &prices, &dec("10"), &commission));
+    }
+
+    #[test]
+    fn test_recommend() {
+        let (commission, min_profit) = (dec("0.001"), dec("0.001"));
+        let result = recommend(
+            &normal(),
+            &klines(),
+            3_600_000,
+            &dec("1000"),
+            &commission,
+            &min_profit,
+        )
+        .unwrap();
+
+        assert_eq!(result.prices.len(), result.levels as usize + 1);
+        assert!(result.prices.windows(2).all(|v| v[0] < v[1]));
+        assert!(result.prices.iter().all(|v| v.scale() <= 2));
+        assert!(result.lower < result.price && result.price < result.upper);
+
+        // Fewer levels still span the range instead of stopping below the price
+        let small = recommend(
+            &normal(),
+            &klines(),
+            3_600_000,
+            &dec("8"),
+            &commission,
+            &min_profit,
+        )
+        .unwrap();
+
+        assert!(small.levels < result.levels);
+        assert!(small.step > result.step);
+        assert!(small.upper > small.price);
+        assert!(small.upper >= result.upper - result.upper * result.step);
+    }
+
+    #[test]
+    fn test_recommend_step() {
+        let flat = vec![kline(100, 100, 100); 20];
+        let result = recommend(
+            &normal(),
+            &flat,
+            3_600_000,
+            &dec("1000"),
+            &Decimal::ZERO,
+            &dec("-0.5"),
+        );
+
+        assert!(result.is_err());
+    }
+}
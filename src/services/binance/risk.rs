@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use binance::types::{OrderSide, Symbol};
+use plot::types::{Decimal, QuoteQuantity};
+use serde::{Deserialize, Serialize};
+
+use crate::time::timestamp;
+
+const DAY_MILLIS: u128 = 86_400_000;
+
+#[derive(Debug)]
+pub enum RiskError {
+    KillSwitch(String),
+    Symbol(String),
+    OrderNotional(String),
+    DailyVolume(String),
+    Exposure(String),
+}
+
+impl std::error::Error for RiskError {}
+impl std::fmt::Display for RiskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::KillSwitch(e) => format!("KILL_SWITCH {}", e),
+            Self::Symbol(e) => format!("SYMBOL {}", e),
+            Self::OrderNotional(e) => format!("ORDER_NOTIONAL {}", e),
+            Self::DailyVolume(e) => format!("DAILY_VOLUME {}", e),
+            Self::Exposure(e) => format!("EXPOSURE {}", e),
+        };
+
+        write!(f, "RISK {}", message)
+    }
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskLimits {
+    // Quote notional of a single order
+    pub max_order_notional: Option<QuoteQuantity>,
+    // Quote notional traded per UTC day
+    pub max_daily_volume:   Option<QuoteQuantity>,
+    // Symbols the account may trade, any when absent
+    pub symbols:            Option<Vec<Symbol>>,
+    // Quote notional bought and not yet sold
+    pub max_exposure:       Option<QuoteQuantity>,
+}
+
+/// Risk configuration file, accounts are keyed by API key
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskConfig {
+    #[serde(default)]
+    pub default: RiskLimits,
+    #[serde(default)]
+    pub accounts: HashMap<String, RiskLimits>,
+}
+
+/// Saved next to the configuration, a restart keeps trading halted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RiskState {
+    killed: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Usage {
+    day: u128,
+    volume: QuoteQuantity,
+    exposure: QuoteQuantity,
+}
+
+/// Pre-trade checks in front of every live order
+pub struct RiskEngine {
+    killed: AtomicBool,
+    config: RiskConfig,
+    usage: Mutex<HashMap<String, Usage>>,
+    // File keeping the kill switch across restarts
+    state: Option<PathBuf>,
+}
+
+impl RiskEngine {
+    pub fn new(config: RiskConfig) -> Self {
+        Self {
+            killed: AtomicBool::new(false),
+            config,
+            usage: Mutex::new(HashMap::new()),
+            state: None,
+        }
+    }
+
+    /// The kill switch is kept in `{config}.state.json` next to the file
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let config = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        Self::new(config).with_state(path.with_extension("state.json"))
+    }
+
+    /// Keep the kill switch in the file, switched on again when it was on
+    pub fn with_state(mut self, path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+
+        let state: RiskState = match fs::read(&path) {
+            Ok(v) => serde_json::from_slice(&v)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => RiskState::default(),
+            Err(e) => return Err(e),
+        };
+        if state.killed {
+            tracing::warn!("trading kill switch on since the last run");
+        }
+
+        self.killed = AtomicBool::new(state.killed);
+        self.state = Some(path);
+
+        Ok(self)
+    }
+
+    /// The switch takes effect before it is saved, a failed save only loses
+    /// it across a restart
+    pub async fn kill(&self, enabled: bool) -> io::Result<()> {
+        tracing::warn!("trading kill switch {}", if enabled { "on" } else { "off" });
+        self.killed.store(enabled, Ordering::SeqCst);
+
+        let path = match &self.state {
+            Some(v) => v.clone(),
+            None => return Ok(()),
+        };
+        let body = serde_json::to_vec(&RiskState { killed: enabled })?;
+
+        tokio::task::spawn_blocking(move || {
+            let temp = path.with_extension("tmp");
+            let mut file = File::create(&temp)?;
+            file.write_all(&body)?;
+            file.sync_all()?;
+
+            fs::rename(temp, path)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::SeqCst)
+    }
+
+    fn limits(&self, account: &str) -> &RiskLimits {
+        self.config
+            .accounts
+            .get(account)
+            .unwrap_or(&self.config.default)
+    }
+
+    /// Check the order against the account limits and book it when allowed
+    pub fn allow(
+        &self,
+        account: &str,
+        symbol: &Symbol,
+        side: &OrderSide,
+        notional: &QuoteQuantity,
+    ) -> Result<(), RiskError> {
+        if self.is_killed() {
+            return Err(RiskError::KillSwitch("trading is halted".into()));
+        }
+
+        let limits = self.limits(account);
+
+        if let Some(v) = &limits.symbols {
+            if !v.contains(symbol) {
+                return Err(RiskError::Symbol(format!(
+                    "symbol {} is not allowed for the account",
+                    symbol
+                )));
+            }
+        }
+
+        if let Some(v) = &limits.max_order_notional {
+            if notional > v {
+                return Err(RiskError::OrderNotional(format!(
+                    "order notional {} exceeds the maximum {}",
+                    notional, v
+                )));
+            }
+        }
+
+        let day = timestamp().as_millis() / DAY_MILLIS;
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        let usage = usage.entry(account.to_string()).or_default();
+        if usage.day != day {
+            usage.day = day;
+            usage.volume = Decimal::ZERO;
+        }
+
+        if let Some(v) = &limits.max_daily_volume {
+            if usage.volume + notional > *v {
+                return Err(RiskError::DailyVolume(format!(
+                    "daily volume {} with the order {} exceeds the maximum {}",
+                    usage.volume, notional, v
+                )));
+            }
+        }
+
+        let exposure = match side {
+            OrderSide::Buy => usage.exposure + notional,
+            OrderSide::Sell => (usage.exposure - notional).max(Decimal::ZERO),
+        };
+
+        if let (OrderSide::Buy, Some(v)) = (side, &limits.max_exposure) {
+            if exposure > *v {
+                return Err(RiskError::Exposure(format!(
+                    "open exposure {} with the order {} exceeds the maximum {}",
+                    usage.exposure, notional, v
+                )));
+            }
+        }
+
+        usage.volume += notional;
+        usage.exposure = exposure;
+
+        Ok(())
+    }
+
+    /// Undo the booking of an order the exchange did not execute
+    pub fn release(&self, account: &str, side: &OrderSide, notional: &QuoteQuantity) {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(usage) = usage.get_mut(account) {
+            usage.volume = (usage.volume - notional).max(Decimal::ZERO);
+            usage.exposure = match side {
+                OrderSide::Buy => (usage.exposure - notional).max(Decimal::ZERO),
+                OrderSide::Sell => usage.exposure + notional,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use binance::types::OrderSide;
+    use plot::types::Decimal;
+
+    use super::{RiskConfig, RiskEngine, RiskError, RiskLimits};
+
+    fn engine() -> RiskEngine {
+        let mut config = RiskConfig::default();
+        config.accounts.insert(
+            "key".into(),
+            RiskLimits {
+                max_order_notional: Some(Decimal::from(100)),
+                max_daily_volume: Some(Decimal::from(250)),
+                symbols: Some(vec!["ETHUSDT".into()]),
+                max_exposure: Some(Decimal::from(150)),
+            },
+        );
+
+        RiskEngine::new(config)
+    }
+
+    #[test]
+    fn test_limits() {
+        let risk = engine();
+        let symbol = "ETHUSDT".to_string();
+        let buy = OrderSide::Buy;
+        let sell = OrderSide::Sell;
+
+        assert!(matches!(
+            risk.allow("key", &"BTCUSDT".into(), &buy, &Decimal::from(10)),
+            Err(RiskError::Symbol(_))
+        ));
+        assert!(matches!(
+            risk.allow("key", &symbol, &buy, &Decimal::from(101)),
+            Err(RiskError::OrderNotional(_))
+        ));
+
+        assert!(risk
+            .allow("key", &symbol, &buy, &Decimal::from(100))
+            .is_ok());
+        assert!(matches!(
+            risk.allow("key", &symbol, &buy, &Decimal::from(60)),
+            Err(RiskError::Exposure(_))
+        ));
+
+        assert!(risk
+            .allow("key", &symbol, &sell, &Decimal::from(100))
+            .is_ok());
+        assert!(matches!(
+            risk.allow("key", &symbol, &buy, &Decimal::from(60)),
+            Err(RiskError::DailyVolume(_))
+        ));
+
+        // Accounts without limits use the default ones
+        assert!(risk
+            .allow("other", &symbol, &buy, &Decimal::from(1000))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_kill_switch() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let path = std::env::temp_dir().join(format!("harmony-risk-{}", std::process::id()));
+            std::fs::create_dir_all(&path).unwrap();
+            let config = path.join("risk.json");
+            std::fs::write(&config, "{}").unwrap();
+
+            let risk = RiskEngine::from_file(&config).unwrap();
+            risk.kill(true).await.unwrap();
+
+            assert!(matches!(
+                risk.allow("other", &"ETHUSDT".into(), &OrderSide::Buy, &Decimal::ONE),
+                Err(RiskError::KillSwitch(_))
+            ));
+
+            // A restart keeps trading halted
+            let risk = RiskEngine::from_file(&config).unwrap();
+            assert!(risk.is_killed());
+
+            risk.kill(false).await.unwrap();
+            assert!(risk
+                .allow("other", &"ETHUSDT".into(), &OrderSide::Buy, &Decimal::ONE)
+                .is_ok());
+            assert!(!RiskEngine::from_file(&config).unwrap().is_killed());
+
+            std::fs::remove_dir_all(path).unwrap();
+        });
+    }
+}
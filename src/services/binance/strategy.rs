@@ -0,0 +1,584 @@
+use std::error::Error;
+use std::sync::Mutex;
+
+use plot::strategy::{grid::Grid, grid_percent::GridPercent, Strategy as PlotStrategy};
+use plot::trade::position::Position;
+use plot::trade::{Executor, Trade, Trader};
+use plot::types::{BaseQuantity, Decimal, Price, QuoteQuantity};
+use serde::{Deserialize, Serialize};
+
+/// Scale of the level prices, the finest price precision on Binance
+const PRICE_SCALE: u32 = 8;
+
+/// Upper bound of the levels of an infinity grid or a ladder
+const MAX_LEVELS: u32 = 1000;
+
+/// Price above the sell price of any spot pair, a position holding base sells at it
+const HOLDING_PRICE: i64 = 1_000_000_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Strategy {
+    Grid(Grid),
+    GridPercent(GridPercent),
+    Geometric(Geometric),
+    Trailing(Trailing),
+    Infinity(Infinity),
+    Ladder(Ladder),
+}
+
+impl Strategy {
+    pub fn positions(&self) -> Vec<Position> {
+        match self {
+            Self::Grid(v) => v.assign_position(),
+            Self::GridPercent(v) => v.assign_position(),
+            Self::Geometric(v) => v.levels().iter().map(Level::position).collect(),
+            Self::Trailing(v) => v.levels().iter().map(Level::position).collect(),
+            Self::Infinity(v) => v.levels().iter().map(Level::position).collect(),
+            Self::Ladder(v) => v.levels().iter().map(Level::position).collect(),
+        }
+    }
+
+    /// Move the strategy with the price once the positions traded at it,
+    /// returns true when the positions changed
+    pub async fn follow(&mut self, price: &Price, positions: &mut Vec<Position>) -> bool {
+        match self {
+            Self::Trailing(v) => {
+                if !v.follow(price) {
+                    return false;
+                }
+
+                // Price is above the old grid, a position still holding base did not
+                // get its sell through and is carried over to the new grid
+                let mut kept = Vec::new();
+                for position in positions.iter() {
+                    if is_holding(position, price).await {
+                        kept.push(position.clone());
+                    }
+                }
+
+                kept.extend(v.levels().iter().map(Level::position));
+                *positions = kept;
+                true
+            }
+            Self::Infinity(v) => {
+                let levels = v.follow(price);
+                if levels.is_empty() {
+                    return false;
+                }
+
+                positions.extend(levels.iter().map(Level::position));
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Trader filling nothing that remembers the base it was asked to sell
+#[derive(Default)]
+struct Probe {
+    sold: Mutex<BaseQuantity>,
+}
+
+impl Trader for Probe {
+    async fn buy(
+        &self,
+        _price: &Price,
+        _quantity: &QuoteQuantity,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+
+    async fn sell(
+        &self,
+        _price: &Price,
+        quantity: &BaseQuantity,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        *self.sold.lock().unwrap_or_else(|e| e.into_inner()) += quantity;
+        Ok(Vec::new())
+    }
+}
+
+/// Base a copy of the position trapped at the price wants to sell
+async fn selling(position: &Position, price: &Price) -> BaseQuantity {
+    let probe = Probe::default();
+    let _ = position.clone().trap(&probe, price).await;
+
+    let sold = *probe.sold.lock().unwrap_or_else(|e| e.into_inner());
+    sold
+}
+
+/// Whether the position wants to sell base at the price
+async fn is_holding(position: &Position, price: &Price) -> bool {
+    selling(position, price).await > Decimal::ZERO
+}
+
+/// Base held by the position, whatever its sell price
+pub async fn held_base(position: &Position) -> BaseQuantity {
+    selling(position, &Decimal::from(HOLDING_PRICE)).await
+}
+
+/// One grid level, buys at `buy` and sells the bought quantity at `sell`
+#[derive(Debug, Clone)]
+pub struct Level {
+    pub buy: Price,
+    pub sell: Price,
+    pub investment: QuoteQuantity,
+}
+
+impl Level {
+    pub fn position(&self) -> Position {
+        Position::new(self.buy, self.sell, self.investment)
+    }
+}
+
+/// Levels at a constant ratio between the bounds
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Geometric {
+    pub lower:      Price,
+    pub upper:      Price,
+    pub levels:     u32,
+    pub investment: QuoteQuantity,
+}
+
+impl Geometric {
+    pub fn levels(&self) -> Vec<Level> {
+        if self.levels == 0 || self.lower <= Decimal::ZERO || self.upper <= self.lower {
+            return Vec::new();
+        }
+
+        let ratio = f64::try_from(self.upper / self.lower).unwrap_or(1.0);
+        let ratio = ratio.powf(1.0 / self.levels as f64);
+        let ratio = Decimal::try_from(ratio).unwrap_or(Decimal::ONE);
+
+        let mut prices = Vec::with_capacity(self.levels as usize + 1);
+        let mut price = self.lower;
+        for _ in 0..self.levels {
+            prices.push(price.round_dp(PRICE_SCALE));
+            price *= ratio;
+        }
+        prices.push(self.upper);
+
+        let investment = self.investment / Decimal::from(self.levels);
+        to_levels(&prices, investment)
+    }
+}
+
+/// Arithmetic grid that moves its range up by whole steps when price rises above it
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trailing {
+    pub lower:      Price,
+    pub upper:      Price,
+    pub levels:     u32,
+    pub investment: QuoteQuantity,
+    // Highest upper bound the grid may trail to
+    pub limit:      Option<Price>,
+}
+
+impl Trailing {
+    fn step(&self) -> Decimal {
+        (self.upper - self.lower) / Decimal::from(self.levels.max(1))
+    }
+
+    pub fn levels(&self) -> Vec<Level> {
+        if self.levels == 0 || self.upper <= self.lower {
+            return Vec::new();
+        }
+
+        let step = self.step();
+        let prices: Vec<Price> = (0..=self.levels)
+            .map(|i| (self.lower + step * Decimal::from(i)).round_dp(PRICE_SCALE))
+            .collect();
+
+        let investment = self.investment / Decimal::from(self.levels);
+        to_levels(&prices, investment)
+    }
+
+    /// Shift the range until it contains the price, returns true when it moved
+    pub fn follow(&mut self, price: &Price) -> bool {
+        let step = self.step();
+        if step <= Decimal::ZERO || price <= &self.upper {
+            return false;
+        }
+
+        let mut steps = ((price - self.upper) / step).ceil();
+        if let Some(limit) = self.limit {
+            steps = steps.min(((limit - self.upper) / step).floor());
+        }
+
+        if steps <= Decimal::ZERO {
+            return false;
+        }
+
+        self.lower += step * steps;
+        self.upper += step * steps;
+
+        true
+    }
+}
+
+/// Geometric levels from the lower bound without an upper bound,
+/// a level is added on top each time price rises one step above the grid
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Infinity {
+    pub lower:      Price,
+    // Ratio between two levels, 0.01 is 1%
+    pub step:       Decimal,
+    pub levels:     u32,
+    // Quote quantity of each level
+    pub investment: QuoteQuantity,
+}
+
+impl Infinity {
+    /// Prices from the lower bound up to `levels` steps above it,
+    /// stops early when the next price overflows
+    fn prices(&self, levels: u32) -> Vec<Price> {
+        let mut prices = Vec::with_capacity(levels as usize + 1);
+        let mut price = self.lower;
+
+        for i in 0..=levels {
+            prices.push(price.round_dp(PRICE_SCALE));
+            if i == levels {
+                break;
+            }
+
+            price = match price.checked_mul(Decimal::ONE + self.step) {
+                Some(v) => v,
+                None => break,
+            };
+        }
+
+        prices
+    }
+
+    pub fn levels(&self) -> Vec<Level> {
+        if self.step <= Decimal::ZERO || self.lower <= Decimal::ZERO {
+            return Vec::new();
+        }
+
+        to_levels(&self.prices(self.levels.min(MAX_LEVELS)), self.investment)
+    }
+
+    /// Add the levels between the grid top and the price, up to `MAX_LEVELS` in all
+    pub fn follow(&mut self, price: &Price) -> Vec<Level> {
+        let mut levels = Vec::new();
+        if self.step <= Decimal::ZERO || self.lower <= Decimal::ZERO || self.levels >= MAX_LEVELS {
+            return levels;
+        }
+
+        let mut top = match self.prices(self.levels).last() {
+            Some(v) => *v,
+            None => return levels,
+        };
+
+        while self.levels < MAX_LEVELS {
+            let next = match top.checked_mul(Decimal::ONE + self.step) {
+                Some(v) => v.round_dp(PRICE_SCALE),
+                None => break,
+            };
+            if price < &next {
+                break;
+            }
+
+            levels.push(Level {
+                buy: top,
+                sell: next,
+                investment: self.investment,
+            });
+            self.levels += 1;
+            top = next;
+        }
+
+        levels
+    }
+}
+
+/// DCA ladder, each level buys `step` lower than the previous one with the
+/// investment scaled by `multiplier`, a multiplier above one is a martingale
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ladder {
+    pub start:       Price,
+    // Price drop between two levels, 0.02 is 2%
+    pub step:        Decimal,
+    pub levels:      u32,
+    // Quote quantity of the first level
+    pub investment:  QuoteQuantity,
+    pub multiplier:  Decimal,
+    // Sell each level this much above its buy price
+    pub take_profit: Decimal,
+}
+
+impl Ladder {
+    pub fn levels(&self) -> Vec<Level> {
+        // A step of one or more buys at zero, the others at a loss or nothing
+        let valid = self.start > Decimal::ZERO
+            && self.step > Decimal::ZERO
+            && self.step < Decimal::ONE
+            && self.multiplier > Decimal::ZERO
+            && self.take_profit > Decimal::ZERO;
+        if !valid {
+            return Vec::new();
+        }
+
+        let count = self.levels.min(MAX_LEVELS);
+        let mut levels = Vec::with_capacity(count as usize);
+        let mut buy = self.start;
+        let mut investment = self.investment;
+
+        // A martingale stops at the level whose investment overflows
+        for _ in 0..count {
+            let sell = match buy.checked_mul(Decimal::ONE + self.take_profit) {
+                Some(v) => v,
+                None => break,
+            };
+
+            levels.push(Level {
+                buy: buy.round_dp(PRICE_SCALE),
+                sell: sell.round_dp(PRICE_SCALE),
+                investment,
+            });
+
+            match (
+                buy.checked_mul(Decimal::ONE - self.step),
+                investment.checked_mul(self.multiplier),
+            ) {
+                (Some(v), Some(w)) => (buy, investment) = (v, w),
+                _ => break,
+            }
+        }
+
+        levels
+    }
+}
+
+fn to_levels(prices: &[Price], investment: QuoteQuantity) -> Vec<Level> {
+    prices
+        .windows(2)
+        .map(|v| Level {
+            buy: v[0],
+            sell: v[1],
+            investment,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use plot::trade::{Executor, Trade, Trader};
+    use plot::types::{BaseQuantity, Decimal, Price, QuoteQuantity};
+
+    use super::super::fixture::dec;
+    use super::{Geometric, Infinity, Ladder, Strategy, Trailing, MAX_LEVELS};
+
+    /// Fills every order at the price, or fills no sell at all
+    struct Fill {
+        sells: bool,
+    }
+
+    impl Trader for Fill {
+        async fn buy(
+            &self,
+            price: &Price,
+            quantity: &QuoteQuantity,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            Ok(vec![Trade::with_buy(*price, quantity / price, *quantity)])
+        }
+
+        async fn sell(
+            &self,
+            price: &Price,
+            quantity: &BaseQuantity,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            match self.sells {
+                true => Ok(vec![Trade::with_sell(*price, *quantity, quantity * price)]),
+                false => Ok(Vec::new()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_geometric() {
+        let grid = Geometric {
+            lower: dec("100"),
+            upper: dec("400"),
+            levels: 2,
+            investment: dec("100"),
+        };
+
+        let levels = grid.levels();
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[0].sell, dec("200"));
+        assert_eq!(levels[1].sell, dec("400"));
+        assert_eq!(levels[1].investment, dec("50"));
+    }
+
+    #[test]
+    fn test_trailing() {
+        let mut grid = Trailing {
+            lower: dec("100"),
+            upper: dec("200"),
+            levels: 10,
+            investment: dec("100"),
+            limit: Some(dec("250")),
+        };
+
+        assert!(!grid.follow(&dec("150")));
+        assert!(grid.follow(&dec("215")));
+        assert_eq!(grid.lower, dec("120"));
+        assert_eq!(grid.upper, dec("220"));
+
+        // Stops at the limit
+        assert!(grid.follow(&dec("400")));
+        assert_eq!(grid.upper, dec("250"));
+        assert!(!grid.follow(&dec("400")));
+    }
+
+    #[test]
+    fn test_trailing_follow() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let mut strategy = Strategy::Trailing(Trailing {
+                lower: dec("100"),
+                upper: dec("200"),
+                levels: 2,
+                investment: dec("100"),
+                limit: None,
+            });
+            let mut positions = strategy.positions();
+
+            // Both levels buy, then price jumps above the grid and no sell goes through
+            let price = dec("100");
+            for position in positions.iter_mut() {
+                position.trap(&Fill { sells: true }, &price).await.unwrap();
+            }
+            let price = dec("260");
+            for position in positions.iter_mut() {
+                position.trap(&Fill { sells: false }, &price).await.unwrap();
+            }
+
+            // The holding positions stay next to the levels of the moved grid
+            assert!(strategy.follow(&price, &mut positions).await);
+            assert_eq!(positions.len(), 4);
+
+            // Once they sold only the levels of the grid are left
+            let price = dec("360");
+            for position in positions.iter_mut() {
+                position.trap(&Fill { sells: true }, &price).await.unwrap();
+            }
+            assert!(strategy.follow(&price, &mut positions).await);
+            assert_eq!(positions.len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_infinity() {
+        let mut grid = Infinity {
+            lower: dec("100"),
+            step: dec("0.1"),
+            levels: 2,
+            investment: dec("10"),
+        };
+
+        assert_eq!(grid.levels().len(), 2);
+        assert!(grid.follow(&dec("125")).is_empty());
+
+        let levels = grid.follow(&dec("146.41"));
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[0].buy, dec("121"));
+        assert_eq!(levels[1].sell, dec("146.41"));
+        assert_eq!(grid.levels, 4);
+    }
+
+    #[test]
+    fn test_ladder() {
+        let ladder = Ladder {
+            start: dec("100"),
+            step: dec("0.1"),
+            levels: 3,
+            investment: dec("10"),
+            multiplier: dec("2"),
+            take_profit: dec("0.05"),
+        };
+
+        let levels = ladder.levels();
+        assert_eq!(levels[2].buy, dec("81"));
+        assert_eq!(levels[2].investment, dec("40"));
+        assert_eq!(levels[0].sell, dec("105"));
+    }
+
+    #[test]
+    fn test_ladder_invalid() {
+        let ladder = Ladder {
+            start: dec("100"),
+            step: dec("0.1"),
+            levels: 3,
+            investment: dec("10"),
+            multiplier: dec("2"),
+            take_profit: dec("0.05"),
+        };
+
+        let invalid = [
+            Ladder {
+                step: dec("1"),
+                ..ladder.clone()
+            },
+            Ladder {
+                step: dec("0"),
+                ..ladder.clone()
+            },
+            Ladder {
+                multiplier: dec("0"),
+                ..ladder.clone()
+            },
+            Ladder {
+                take_profit: dec("0"),
+                ..ladder.clone()
+            },
+            Ladder {
+                take_profit: dec("-1"),
+                ..ladder.clone()
+            },
+            Ladder {
+                start: dec("0"),
+                ..ladder.clone()
+            },
+        ];
+        for v in invalid.iter() {
+            assert!(v.levels().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut grid = Infinity {
+            lower: dec("100"),
+            step: dec("1000000"),
+            levels: u32::MAX,
+            investment: dec("10"),
+        };
+        assert!(grid.levels().len() < 10);
+        assert!(grid.follow(&Decimal::MAX).is_empty());
+
+        grid.step = dec("0.0001");
+        grid.levels = 0;
+        assert_eq!(grid.follow(&Decimal::MAX).len(), MAX_LEVELS as usize);
+
+        let ladder = Ladder {
+            start: dec("100"),
+            step: dec("0.01"),
+            levels: u32::MAX,
+            investment: dec("10"),
+            multiplier: dec("1000"),
+            take_profit: dec("0.05"),
+        };
+        assert!(ladder.levels().len() < 20);
+    }
+}
@@ -0,0 +1,213 @@
+use std::error::Error;
+use std::time::Duration;
+
+use binance::types::{OrderSide, Symbol};
+use hmac::{Hmac, Mac};
+use plot::types::{BaseQuantity, Decimal, QuoteQuantity};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use super::limit::LIMITER;
+use super::market::{header_u64, ErrorBody};
+use crate::time::timestamp;
+
+const API_ENDPOINT: &str = "https://api.binance.com";
+
+const RECV_WINDOW: u64 = 5000;
+
+/// Weight of a test order, and of one that also computes the commission rates
+const WEIGHT: u32 = 1;
+const WEIGHT_COMMISSION: u32 = 20;
+
+#[derive(Debug)]
+pub enum ValidateError {
+    Rejected(String),
+}
+
+impl Error for ValidateError {}
+impl std::fmt::Display for ValidateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::Rejected(e) => format!("REJECTED {}", e),
+        };
+
+        write!(f, "VALIDATE {}", message)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum OrderQuantity {
+    Quote(QuoteQuantity),
+    Base(BaseQuantity),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommissionRate {
+    #[serde(default)]
+    pub maker: Decimal,
+    #[serde(default)]
+    pub taker: Decimal,
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct OrderTestResult {
+    pub standard_commission_for_order: Option<CommissionRate>,
+    pub tax_commission_for_order:      Option<CommissionRate>,
+}
+
+impl OrderTestResult {
+    /// Taker rate the order would pay, when the rates were computed
+    pub fn taker(&self) -> Option<Decimal> {
+        let standard = self.standard_commission_for_order.as_ref()?;
+        let tax = self.tax_commission_for_order.clone().unwrap_or_default();
+
+        Some(standard.taker + tax.taker)
+    }
+}
+
+/// Signed client of the exchange test order endpoint, the order is checked
+/// for signature, permissions and filters but never reaches the matching engine
+pub struct OrderTest {
+    http: reqwest::Client,
+    endpoint: String,
+    api_key: String,
+    secret_key: String,
+    compute_commission: bool,
+}
+
+impl OrderTest {
+    pub fn new(api_key: String, secret_key: String) -> Result<Self, Box<dyn Error>> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()?;
+
+        Ok(Self {
+            http,
+            endpoint: API_ENDPOINT.into(),
+            api_key,
+            secret_key,
+            compute_commission: false,
+        })
+    }
+
+    /// Also ask the exchange for the commission rates of each order
+    pub fn with_commission(mut self, compute_commission: bool) -> Self {
+        self.compute_commission = compute_commission;
+
+        self
+    }
+
+    pub async fn market_order(
+        &self,
+        symbol: &Symbol,
+        side: &OrderSide,
+        quantity: &OrderQuantity,
+    ) -> Result<OrderTestResult, Box<dyn Error>> {
+        let side = match side {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        };
+
+        let mut query = vec![
+            ("symbol", symbol.clone()),
+            ("side", side.to_string()),
+            ("type", "MARKET".to_string()),
+        ];
+        match quantity {
+            OrderQuantity::Quote(v) => query.push(("quoteOrderQty", v.to_string())),
+            OrderQuantity::Base(v) => query.push(("quantity", v.to_string())),
+        }
+
+        let weight = match self.compute_commission {
+            true => {
+                query.push(("computeCommissionRates", "true".to_string()));
+                WEIGHT_COMMISSION
+            }
+            false => WEIGHT,
+        };
+
+        query.push(("recvWindow", RECV_WINDOW.to_string()));
+        query.push(("timestamp", timestamp().as_millis().to_string()));
+
+        let query = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<String>>()
+            .join("&");
+        let signature = sign(&self.secret_key, &query)?;
+
+        LIMITER.acquire(weight, false).await?;
+
+        let response = self
+            .http
+            .post(format!(
+                "{}/api/v3/order/test?{}&signature={}",
+                self.endpoint, query, signature
+            ))
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await?;
+
+        if let Some(v) = header_u64(&response, "x-mbx-used-weight-1m") {
+            LIMITER.observe_used_weight(v as u32);
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = header_u64(&response, "retry-after");
+            LIMITER.observe_status(status.as_u16(), retry_after.map(Duration::from_secs));
+
+            let body = response.text().await.unwrap_or_default();
+            let message = match serde_json::from_str::<ErrorBody>(&body) {
+                Ok(v) => format!("{} {}", v.code, v.msg),
+                Err(_) => format!("{} {}", status, body),
+            };
+
+            return Err(Box::new(ValidateError::Rejected(message)));
+        }
+
+        Ok(response.json::<OrderTestResult>().await?)
+    }
+}
+
+/// Hex encoded HMAC SHA256 of the query string
+fn sign(secret_key: &str, query: &str) -> Result<String, Box<dyn Error>> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret_key.as_bytes()).map_err(|e| e.to_string())?;
+    mac.update(query.as_bytes());
+
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sign, OrderTestResult};
+
+    #[test]
+    fn test_sign() {
+        // Example from the Binance API documentation
+        let query = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        let secret = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+
+        assert_eq!(
+            sign(secret, query).unwrap(),
+            "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71"
+        );
+    }
+
+    #[test]
+    fn test_commission() {
+        let body = r#"{
+            "standardCommissionForOrder": {"maker": "0.00000112", "taker": "0.00000114"},
+            "taxCommissionForOrder": {"maker": "0.00000112", "taker": "0.00000114"},
+            "discount": {"enabledForAccount": true, "enabledForSymbol": true, "discountAsset": "BNB", "discount": "0.25000000"}
+        }"#;
+        let result: OrderTestResult = serde_json::from_str(body).unwrap();
+        assert_eq!(result.taker().unwrap().to_string(), "0.00000228");
+
+        let result: OrderTestResult = serde_json::from_str("{}").unwrap();
+        assert!(result.taker().is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use std::str::FromStr;
+
+use ethers::providers::Middleware;
+use ethers::types::{BlockId, BlockNumber, H256};
+
+use super::chain::BlockChain;
+use super::contract::{ContractError, ContractResult};
+
+/// Block of a read given as a number, a hash or a tag. Numbers are decimal or
+/// 0x prefixed hex, hashes are 0x prefixed 32 bytes
+pub fn parse_block(value: &str) -> ContractResult<BlockId> {
+    let value = value.trim();
+
+    let tag = match value.to_ascii_lowercase().as_str() {
+        "latest" => Some(BlockNumber::Latest),
+        "earliest" => Some(BlockNumber::Earliest),
+        "pending" => Some(BlockNumber::Pending),
+        "safe" => Some(BlockNumber::Safe),
+        "finalized" => Some(BlockNumber::Finalized),
+        _ => None,
+    };
+    if let Some(v) = tag {
+        return Ok(BlockId::Number(v));
+    }
+
+    let invalid = |e: String| ContractError::Block(format!("invalid block {}: {}", value, e));
+
+    match value.strip_prefix("0x") {
+        Some(hex) if hex.len() == 64 => {
+            let hash = H256::from_str(value).map_err(|e| invalid(e.to_string()))?;
+            Ok(BlockId::Hash(hash))
+        }
+        Some(hex) => {
+            let number = u64::from_str_radix(hex, 16).map_err(|e| invalid(e.to_string()))?;
+            Ok(BlockId::Number(BlockNumber::Number(number.into())))
+        }
+        None => {
+            let number = u64::from_str(value).map_err(|e| invalid(e.to_string()))?;
+            Ok(BlockId::Number(BlockNumber::Number(number.into())))
+        }
+    }
+}
+
+/// Block of the request parameters, a unix timestamp in seconds is resolved
+/// to the last block mined at or before it
+pub async fn resolve_block(
+    chain: &BlockChain,
+    block: Option<&str>,
+    timestamp: Option<u64>,
+) -> ContractResult<Option<BlockId>> {
+    match (block, timestamp) {
+        (Some(_), Some(_)) => Err(ContractError::Block(
+            "block and timestamp are exclusive".into(),
+        )),
+        (Some(v), None) => Ok(Some(parse_block(v)?)),
+        (None, Some(v)) => {
+            let number = block_at(chain, v).await?;
+            Ok(Some(BlockId::Number(BlockNumber::Number(number.into()))))
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+/// Block of the request parameters, pinned to the latest number when missing
+/// so every read of a request sees the same block
+pub async fn pin_block(
+    chain: &BlockChain,
+    block: Option<&str>,
+    timestamp: Option<u64>,
+) -> ContractResult<BlockId> {
+    if let Some(v) = resolve_block(chain, block, timestamp).await? {
+        return Ok(v);
+    }
+
+    let number = chain
+        .client()
+        .get_block_number()
+        .await
+        .map_err(|e| ContractError::Execution(e.to_string()))?;
+
+    Ok(BlockId::from(number.as_u64()))
+}
+
+async fn block_timestamp(chain: &BlockChain, block: BlockNumber) -> ContractResult<(u64, u64)> {
+    let block = chain
+        .client()
+        .get_block(block)
+        .await
+        .map_err(|e| ContractError::Execution(e.to_string()))?
+        .ok_or(ContractError::Block(format!("block {:?} not found", block)))?;
+
+    let number = block
+        .number
+        .ok_or(ContractError::Block("pending block has no number".into()))?;
+
+    Ok((number.as_u64(), block.timestamp.as_u64()))
+}
+
+/// Last block mined at or before the unix timestamp in seconds. Binary search
+/// over the block timestamps, the node must keep the headers of the range
+pub async fn block_at(chain: &BlockChain, timestamp: u64) -> ContractResult<u64> {
+    let (latest, latest_timestamp) = block_timestamp(chain, BlockNumber::Latest).await?;
+    if timestamp >= latest_timestamp {
+        return Ok(latest);
+    }
+
+    let (_, genesis_timestamp) = block_timestamp(chain, BlockNumber::Number(0.into())).await?;
+    if timestamp < genesis_timestamp {
+        return Err(ContractError::Block(format!(
+            "timestamp {} is before the genesis block",
+            timestamp
+        )));
+    }
+
+    // Invariant: timestamp(low) <= timestamp < timestamp(high)
+    let (mut low, mut high) = (0, latest);
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        let (_, middle_timestamp) =
+            block_timestamp(chain, BlockNumber::Number(middle.into())).await?;
+
+        if middle_timestamp <= timestamp {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    Ok(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{BlockId, BlockNumber};
+
+    use super::parse_block;
+
+    #[test]
+    fn test_parse_block() {
+        let number = |v: u64| BlockId::Number(BlockNumber::Number(v.into()));
+
+        assert_eq!(parse_block("19000000").unwrap(), number(19_000_000));
+        assert_eq!(parse_block("0x10").unwrap(), number(16));
+        assert_eq!(
+            parse_block("Finalized").unwrap(),
+            BlockId::Number(BlockNumber::Finalized)
+        );
+
+        let hash = format!("0x{}", "ab".repeat(32));
+        assert!(matches!(parse_block(&hash).unwrap(), BlockId::Hash(_)));
+
+        assert!(parse_block("yesterday").is_err());
+        assert!(parse_block("0xzz").is_err());
+    }
+}
@@ -1,38 +1,126 @@
 use serde::{Deserialize, Serialize};
 
 #[rustfmt::skip]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BlockChain {
-    Ethereum = 1,
-    Polygon  = 137,
+    Ethereum  = 1,
+    Polygon   = 137,
+    Arbitrum  = 42161,
+    Optimism  = 10,
+    Base      = 8453,
+    BnbChain  = 56,
+    Avalanche = 43114,
+}
+
+impl BlockChain {
+    pub const ALL: [BlockChain; 7] = [
+        Self::Ethereum,
+        Self::Polygon,
+        Self::Arbitrum,
+        Self::Optimism,
+        Self::Base,
+        Self::BnbChain,
+        Self::Avalanche,
+    ];
+
+    pub fn chain_id(&self) -> u64 {
+        *self as u64
+    }
+}
+
+/// Exchanges with Uniswap V2 style factories and constant product pairs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Exchange {
+    UniswapV2,
+    SushiSwap,
+    QuickSwap,
+    PancakeSwap,
+}
+
+impl Exchange {
+    /// Swap fee of the pairs in basis points
+    pub fn fee(&self) -> u32 {
+        match self {
+            Self::PancakeSwap => 25,
+            _ => 30,
+        }
+    }
 }
 
 mod block_chain_client {
+    use std::collections::HashMap;
     use std::sync::Arc;
 
     use crate::services::crypto::contract::Provider;
+    use crate::services::crypto::rpc::RpcPool;
 
     use super::BlockChain;
 
     const NETWORK_GETWAY_ETHEREUM: &str = "https://eth.public-rpc.com";
     const NETWORK_GETWAY_POLYGON: &str = "https://polygon-rpc.com";
+    const NETWORK_GETWAY_ARBITRUM: &str = "https://arb1.arbitrum.io/rpc";
+    const NETWORK_GETWAY_OPTIMISM: &str = "https://mainnet.optimism.io";
+    const NETWORK_GETWAY_BASE: &str = "https://mainnet.base.org";
+    const NETWORK_GETWAY_BNB_CHAIN: &str = "https://bsc-dataseed.bnbchain.org";
+    const NETWORK_GETWAY_AVALANCHE: &str = "https://api.avax.network/ext/bc/C/rpc";
 
     impl BlockChain {
-        #[rustfmt::skip]
         pub fn client(&self) -> Arc<Provider> {
+            CLIENTS[self].clone()
+        }
+
+        pub fn rpc_pool(&self) -> &'static RpcPool {
+            CLIENTS[self].as_ref().as_ref()
+        }
+
+        /// Variable holding the comma separated RPC URLs of the chain
+        #[rustfmt::skip]
+        pub fn rpc_env(&self) -> &'static str {
             match self {
-                Self::Ethereum => (*CLIENT_ETHEREUM).clone(),
-                Self::Polygon  => (*CLIENT_POLYGON).clone(),
+                Self::Ethereum  => "RPC_ETHEREUM",
+                Self::Polygon   => "RPC_POLYGON",
+                Self::Arbitrum  => "RPC_ARBITRUM",
+                Self::Optimism  => "RPC_OPTIMISM",
+                Self::Base      => "RPC_BASE",
+                Self::BnbChain  => "RPC_BNB_CHAIN",
+                Self::Avalanche => "RPC_AVALANCHE",
             }
         }
-    }
 
-    lazy_static::lazy_static! {
-        pub static ref CLIENT_ETHEREUM: Arc<Provider> = Arc::new(Provider::try_from(NETWORK_GETWAY_ETHEREUM).expect("connect crypto provider error"));
+        /// RPC URLs from the environment, the public gateway when not set
+        pub fn rpc_urls(&self) -> Vec<String> {
+            let urls: Vec<String> = std::env::var(self.rpc_env())
+                .unwrap_or_default()
+                .split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect();
+
+            if !urls.is_empty() {
+                return urls;
+            }
+
+            #[rustfmt::skip]
+            let url = match self {
+                Self::Ethereum  => NETWORK_GETWAY_ETHEREUM,
+                Self::Polygon   => NETWORK_GETWAY_POLYGON,
+                Self::Arbitrum  => NETWORK_GETWAY_ARBITRUM,
+                Self::Optimism  => NETWORK_GETWAY_OPTIMISM,
+                Self::Base      => NETWORK_GETWAY_BASE,
+                Self::BnbChain  => NETWORK_GETWAY_BNB_CHAIN,
+                Self::Avalanche => NETWORK_GETWAY_AVALANCHE,
+            };
+
+            vec![url.to_string()]
+        }
     }
 
     lazy_static::lazy_static! {
-        pub static ref CLIENT_POLYGON: Arc<Provider> = Arc::new( Provider::try_from(NETWORK_GETWAY_POLYGON).expect("connect crypto provider error"));
+        static ref CLIENTS: HashMap<BlockChain, Arc<Provider>> = BlockChain::ALL
+            .iter()
+            .map(|chain| (*chain, Arc::new(Provider::new(RpcPool::new(&chain.rpc_urls())))))
+            .collect();
     }
 }
 
@@ -41,15 +129,20 @@ mod block_chain_contract {
 
     use super::super::contract::types::Address;
     use super::super::contract::{CaseERC20, CaseUniswapV3Factory, CaseUniswapV3Pool};
-    use super::super::contract::{Contract, ContractResult};
+    use super::super::contract::{CaseUniswapV2Factory, CaseUniswapV2Pair, CaseUniswapV3QuoterV2};
+    use super::super::contract::{Contract, ContractResult, Multicall};
 
     impl BlockChain {
+        pub fn multicall(&self) -> ContractResult<Multicall> {
+            Multicall::new(self.client())
+        }
+
         pub fn contract_erc_20(&self, address: Address) -> ContractResult<Contract<CaseERC20>> {
             let client = self.client();
             let contract_address = Contract::<()>::to_address(&address)?;
             let contract_case = CaseERC20::new(contract_address, client);
 
-            Ok(Contract(address, contract_case))
+            Ok(Contract(address, contract_case, None))
         }
 
         pub fn contract_uniswap_v3_pool(
@@ -60,7 +153,7 @@ mod block_chain_contract {
             let contract_address = Contract::<()>::to_address(&address)?;
             let contract_case = CaseUniswapV3Pool::new(contract_address, client);
 
-            Ok(Contract(address, contract_case))
+            Ok(Contract(address, contract_case, None))
         }
 
         pub fn contract_uniswap_v3_factory(
@@ -71,7 +164,40 @@ mod block_chain_contract {
             let contract_address = Contract::<()>::to_address(&address)?;
             let contract_case = CaseUniswapV3Factory::new(contract_address, client);
 
-            Ok(Contract(address, contract_case))
+            Ok(Contract(address, contract_case, None))
+        }
+
+        pub fn contract_uniswap_v2_factory(
+            &self,
+            address: Address,
+        ) -> ContractResult<Contract<CaseUniswapV2Factory>> {
+            let client = self.client();
+            let contract_address = Contract::<()>::to_address(&address)?;
+            let contract_case = CaseUniswapV2Factory::new(contract_address, client);
+
+            Ok(Contract(address, contract_case, None))
+        }
+
+        pub fn contract_uniswap_v2_pair(
+            &self,
+            address: Address,
+        ) -> ContractResult<Contract<CaseUniswapV2Pair>> {
+            let client = self.client();
+            let contract_address = Contract::<()>::to_address(&address)?;
+            let contract_case = CaseUniswapV2Pair::new(contract_address, client);
+
+            Ok(Contract(address, contract_case, None))
+        }
+
+        pub fn contract_uniswap_v3_quoter(
+            &self,
+            address: Address,
+        ) -> ContractResult<Contract<CaseUniswapV3QuoterV2>> {
+            let client = self.client();
+            let contract_address = Contract::<()>::to_address(&address)?;
+            let contract_case = CaseUniswapV3QuoterV2::new(contract_address, client);
+
+            Ok(Contract(address, contract_case, None))
         }
     }
 }
@@ -81,13 +207,18 @@ mod block_chain_erc_20_tokens {
 
     use super::super::contract::types::{Address, Symbol};
     use super::super::contract::{Contract, ContractError, ContractResult};
-    use super::BlockChain;
+    use super::{BlockChain, Exchange};
 
     impl BlockChain {
         fn tokens(&self) -> &HashMap<&'static str, &'static str> {
             match self {
                 Self::Ethereum => &*ETHERRUM_ERC_20_TOKENS,
                 Self::Polygon => &*POLYGON_ERC_20_TOKENS,
+                Self::Arbitrum => &*ARBITRUM_ERC_20_TOKENS,
+                Self::Optimism => &*OPTIMISM_ERC_20_TOKENS,
+                Self::Base => &*BASE_ERC_20_TOKENS,
+                Self::BnbChain => &*BNB_CHAIN_ERC_20_TOKENS,
+                Self::Avalanche => &*AVALANCHE_ERC_20_TOKENS,
             }
         }
 
@@ -116,6 +247,46 @@ mod block_chain_erc_20_tokens {
             match self {
                 Self::Ethereum => String::from(ETHEREUM_UNISWAP_V3_FACTORY_ADDRESS),
                 Self::Polygon => String::from(POLYGON_UNISWAP_V3_FACTORY_ADDRESS),
+                Self::Arbitrum => String::from(ARBITRUM_UNISWAP_V3_FACTORY_ADDRESS),
+                Self::Optimism => String::from(OPTIMISM_UNISWAP_V3_FACTORY_ADDRESS),
+                Self::Base => String::from(BASE_UNISWAP_V3_FACTORY_ADDRESS),
+                Self::BnbChain => String::from(BNB_CHAIN_UNISWAP_V3_FACTORY_ADDRESS),
+                Self::Avalanche => String::from(AVALANCHE_UNISWAP_V3_FACTORY_ADDRESS),
+            }
+        }
+
+        /// Factory of the exchange, an error when it is not deployed on the chain
+        pub fn uniswap_v2_factory_address(&self, exchange: Exchange) -> ContractResult<Address> {
+            #[rustfmt::skip]
+            let address = match (self, exchange) {
+                (Self::Ethereum,  Exchange::UniswapV2)   => ETHEREUM_UNISWAP_V2_FACTORY_ADDRESS,
+                (Self::Ethereum,  Exchange::SushiSwap)   => ETHEREUM_SUSHISWAP_FACTORY_ADDRESS,
+                (Self::Polygon,   Exchange::SushiSwap)   => SUSHISWAP_FACTORY_ADDRESS,
+                (Self::Arbitrum,  Exchange::SushiSwap)   => SUSHISWAP_FACTORY_ADDRESS,
+                (Self::BnbChain,  Exchange::SushiSwap)   => SUSHISWAP_FACTORY_ADDRESS,
+                (Self::Avalanche, Exchange::SushiSwap)   => SUSHISWAP_FACTORY_ADDRESS,
+                (Self::Polygon,   Exchange::QuickSwap)   => POLYGON_QUICKSWAP_FACTORY_ADDRESS,
+                (Self::BnbChain,  Exchange::PancakeSwap) => BNB_CHAIN_PANCAKESWAP_FACTORY_ADDRESS,
+                _ => {
+                    return Err(ContractError::Address(format!(
+                        "{:?} factory not found on {:?}",
+                        exchange, self
+                    )))
+                }
+            };
+
+            Ok(String::from(address))
+        }
+
+        pub fn uniswap_v3_quoter_address(&self) -> Address {
+            match self {
+                Self::Ethereum => String::from(ETHEREUM_UNISWAP_V3_QUOTER_ADDRESS),
+                Self::Polygon => String::from(POLYGON_UNISWAP_V3_QUOTER_ADDRESS),
+                Self::Arbitrum => String::from(ARBITRUM_UNISWAP_V3_QUOTER_ADDRESS),
+                Self::Optimism => String::from(OPTIMISM_UNISWAP_V3_QUOTER_ADDRESS),
+                Self::Base => String::from(BASE_UNISWAP_V3_QUOTER_ADDRESS),
+                Self::BnbChain => String::from(BNB_CHAIN_UNISWAP_V3_QUOTER_ADDRESS),
+                Self::Avalanche => String::from(AVALANCHE_UNISWAP_V3_QUOTER_ADDRESS),
             }
         }
     }
@@ -156,8 +327,131 @@ mod block_chain_erc_20_tokens {
         };
     }
 
+    #[rustfmt::skip]
+    lazy_static::lazy_static! {
+        // ERC-20 token address in Arbitrum One network
+        // https://arbiscan.io/tokens
+        static ref ARBITRUM_ERC_20_TOKENS: HashMap<&'static str, &'static str> = {
+            let mut map = HashMap::new();
+            map.insert("USDC"  , "0xaf88d065e77c8cC2239327C5EDb3A432268e5831"); // USDC    6  decimals   Native
+            map.insert("USDC.E", "0xFF970A61A04b1cA14834A43f5dE4533eBDDB5CC8"); // USDC.e  6  decimals   Bridged
+            map.insert("USDT"  , "0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9"); // USDT    6  decimals
+            map.insert("DAI"   , "0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1"); // DAI     18 decimals
+            map.insert("WBTC"  , "0x2f2a2543B76A4166549F7aaB2e75Bef0aefC5B0f"); // WBTC    8  decimals
+            map.insert("WETH"  , "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1"); // WETH    18 decimals
+            map.insert("ARB"   , "0x912CE59144191C1204E64559FE8253a0e49E6548"); // ARB     18 decimals
+            map.insert("UNI"   , "0xFa7F8980b0f1E64A2062791cc3b0871572f1F7f0"); // UNI     18 decimals
+
+            map
+        };
+    }
+
+    #[rustfmt::skip]
+    lazy_static::lazy_static! {
+        // ERC-20 token address in OP Mainnet network
+        // https://optimistic.etherscan.io/tokens
+        static ref OPTIMISM_ERC_20_TOKENS: HashMap<&'static str, &'static str> = {
+            let mut map = HashMap::new();
+            map.insert("USDC"  , "0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85"); // USDC    6  decimals   Native
+            map.insert("USDC.E", "0x7F5c764cBc14f9669B88837ca1490cCa17c31607"); // USDC.e  6  decimals   Bridged
+            map.insert("USDT"  , "0x94b008aA00579c1307B0EF2c499aD98a8ce58e58"); // USDT    6  decimals
+            map.insert("DAI"   , "0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1"); // DAI     18 decimals
+            map.insert("WBTC"  , "0x68f180fcCe6836688e9084f035309E29Bf0A2095"); // WBTC    8  decimals
+            map.insert("WETH"  , "0x4200000000000000000000000000000000000006"); // WETH    18 decimals
+            map.insert("OP"    , "0x4200000000000000000000000000000000000042"); // OP      18 decimals
+
+            map
+        };
+    }
+
+    #[rustfmt::skip]
+    lazy_static::lazy_static! {
+        // ERC-20 token address in Base network
+        // https://basescan.org/tokens
+        static ref BASE_ERC_20_TOKENS: HashMap<&'static str, &'static str> = {
+            let mut map = HashMap::new();
+            map.insert("USDC" , "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"); // USDC    6  decimals   Native
+            map.insert("USDBC", "0xd9aAEc86B65D86f6A7B5B1b0c42FFA531710b6CA"); // USDbC   6  decimals   Bridged
+            map.insert("DAI"  , "0x50c5725949A6F0c72E6C4a641F24049A917DB0Cb"); // DAI     18 decimals
+            map.insert("WETH" , "0x4200000000000000000000000000000000000006"); // WETH    18 decimals
+            map.insert("CBETH", "0x2Ae3F1Ec7F1F5012CFEab0185bfc7aa3cf0DEc22"); // cbETH   18 decimals
+
+            map
+        };
+    }
+
+    #[rustfmt::skip]
+    lazy_static::lazy_static! {
+        // BEP-20 token address in BNB Smart Chain network
+        // https://bscscan.com/tokens
+        static ref BNB_CHAIN_ERC_20_TOKENS: HashMap<&'static str, &'static str> = {
+            let mut map = HashMap::new();
+            map.insert("BNB" , "0xbb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c"); // WBNB    18 decimals
+            map.insert("USDC", "0x8AC76a51cc950d9822D68b83fE1Ad97B32Cd580d"); // USDC    18 decimals   Binance-Peg
+            map.insert("USDT", "0x55d398326f99059fF775485246999027B3197955"); // USDT    18 decimals   Binance-Peg
+            map.insert("BTCB", "0x7130d2A12B9BCbFAe4f2634d864A1Ee1Ce3Ead9c"); // BTCB    18 decimals   Binance-Peg
+            map.insert("ETH" , "0x2170Ed0880ac9A755fd29B2688956BD959F933F8"); // ETH     18 decimals   Binance-Peg
+            map.insert("CAKE", "0x0E09FaBB73Bd3Ade0a17ECC321fD13a19e81cE82"); // CAKE    18 decimals
+
+            map
+        };
+    }
+
+    #[rustfmt::skip]
+    lazy_static::lazy_static! {
+        // ERC-20 token address in Avalanche C-Chain network
+        // https://snowtrace.io/tokens
+        static ref AVALANCHE_ERC_20_TOKENS: HashMap<&'static str, &'static str> = {
+            let mut map = HashMap::new();
+            map.insert("AVAX" , "0xB31f66AA3C1e785363F0875A1B74E27b85FD66c7"); // WAVAX   18 decimals
+            map.insert("USDC" , "0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E"); // USDC    6  decimals   Native
+            map.insert("USDT" , "0x9702230A8Ea53601f5cD2dc00fDBc13d4dF4A8c7"); // USDT    6  decimals   Native
+            map.insert("WBTC" , "0x50b7545627a5162F82A992c33b87aDc75187B218"); // WBTC.e  8  decimals   Bridged
+            map.insert("BTC.B", "0x152b9d0FdC40C096757F570A51E494bd4b943E50"); // BTC.b   8  decimals
+            map.insert("WETH" , "0x49D5c2BdFfac6CE2BFdB6640F4F80f226bc10bAB"); // WETH.e  18 decimals   Bridged
+
+            map
+        };
+    }
+
     pub const POLYGON_UNISWAP_V3_FACTORY_ADDRESS: &str =
         "0x1F98431c8aD98523631AE4a59f267346ea31F984";
     pub const ETHEREUM_UNISWAP_V3_FACTORY_ADDRESS: &str =
         "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+    pub const ARBITRUM_UNISWAP_V3_FACTORY_ADDRESS: &str =
+        "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+    pub const OPTIMISM_UNISWAP_V3_FACTORY_ADDRESS: &str =
+        "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+    pub const BASE_UNISWAP_V3_FACTORY_ADDRESS: &str = "0x33128a8fC17869897dcE68Ed026d694621f6FDfD";
+    pub const BNB_CHAIN_UNISWAP_V3_FACTORY_ADDRESS: &str =
+        "0xdB1d10011AD0Ff90774D0C6Bb92e5C5c8b4461F7";
+    pub const AVALANCHE_UNISWAP_V3_FACTORY_ADDRESS: &str =
+        "0x740b1c1de25031C31FF4fC9A62f554A55cdC1baD";
+
+    // QuoterV2
+    // https://docs.uniswap.org/contracts/v3/reference/deployments
+    pub const ETHEREUM_UNISWAP_V3_QUOTER_ADDRESS: &str =
+        "0x61fFE014bA17989E743c5F6cB21bF9697530B21e";
+    pub const POLYGON_UNISWAP_V3_QUOTER_ADDRESS: &str =
+        "0x61fFE014bA17989E743c5F6cB21bF9697530B21e";
+    pub const ARBITRUM_UNISWAP_V3_QUOTER_ADDRESS: &str =
+        "0x61fFE014bA17989E743c5F6cB21bF9697530B21e";
+    pub const OPTIMISM_UNISWAP_V3_QUOTER_ADDRESS: &str =
+        "0x61fFE014bA17989E743c5F6cB21bF9697530B21e";
+    pub const BASE_UNISWAP_V3_QUOTER_ADDRESS: &str = "0x3d4e44Eb1374240CE5F1B871ab261CD16335B76a";
+    pub const BNB_CHAIN_UNISWAP_V3_QUOTER_ADDRESS: &str =
+        "0x78D78E420Da98ad378D7799bE8f4AF69033EB077";
+    pub const AVALANCHE_UNISWAP_V3_QUOTER_ADDRESS: &str =
+        "0xbe0F5544EC67e9B3b2D979aaA43f18Fd87E6257F";
+
+    // Uniswap V2 style factories
+    pub const ETHEREUM_UNISWAP_V2_FACTORY_ADDRESS: &str =
+        "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f";
+    pub const ETHEREUM_SUSHISWAP_FACTORY_ADDRESS: &str =
+        "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac";
+    pub const SUSHISWAP_FACTORY_ADDRESS: &str = "0xc35DADB65012eC5796536bD9864eD8773aBc74C4";
+    pub const POLYGON_QUICKSWAP_FACTORY_ADDRESS: &str =
+        "0x5757371414417b8C6CAad45bAeF941aBc7d3Ab32";
+    pub const BNB_CHAIN_PANCAKESWAP_FACTORY_ADDRESS: &str =
+        "0xcA143Ce32Fe78f1f7019d7d551a6402fC5350c73";
 }
@@ -1,22 +1,38 @@
+use std::str::FromStr;
+
+use bigdecimal::num_bigint::{BigInt, Sign};
+use bigdecimal::BigDecimal;
+use serde::Serialize;
+
 use super::abi::CaseERC20;
-use super::{types::*, Contract, ContractResult};
+use super::{types::*, Contract, ContractError, ContractResult, Multicall};
+
+#[rustfmt::skip]
+#[derive(Serialize)]
+pub struct TokenMetadata {
+    pub block:        u64,
+    pub name:         String,
+    pub symbol:       Symbol,
+    pub decimals:     Uint8,
+    pub total_supply: Uint256,
+}
 
 // Read Contract ERC-20
 impl Contract<CaseERC20> {
     pub async fn name(&self) -> ContractResult<String> {
-        let result = Self::contract_call(self.1.name().call().await)?;
+        let result = Self::contract_call(self.pin(self.1.name()).call().await)?;
 
         Ok(result)
     }
 
     pub async fn symbol(&self) -> ContractResult<Symbol> {
-        let result = Self::contract_call(self.1.symbol().call().await)?;
+        let result = Self::contract_call(self.pin(self.1.symbol()).call().await)?;
 
         Ok(result)
     }
 
     pub async fn decimals(&self) -> ContractResult<Uint8> {
-        let result = Self::contract_call(self.1.decimals().call().await)?;
+        let result = Self::contract_call(self.pin(self.1.decimals()).call().await)?;
 
         Ok(result)
     }
@@ -24,8 +40,79 @@ impl Contract<CaseERC20> {
     pub async fn total_supply(&self) -> ContractResult<Uint256> {
         use ethers::types::U256;
 
-        let result: U256 = Self::contract_call(self.1.total_supply().call().await)?;
+        let result: U256 = Self::contract_call(self.pin(self.1.total_supply()).call().await)?;
 
         Ok(result.to_string())
     }
+
+    /// Name, symbol, decimals and supply in one round trip
+    pub async fn metadata(&self) -> ContractResult<TokenMetadata> {
+        let mut multicall = Multicall::new(self.1.client())?.block(self.2);
+        let name = multicall.add(self.1.name())?;
+        let symbol = multicall.add(self.1.symbol())?;
+        let decimals = multicall.add(self.1.decimals())?;
+        let total_supply = multicall.add(self.1.total_supply())?;
+        let result = multicall.call().await?;
+
+        Ok(TokenMetadata {
+            block: result.block,
+            name: result.get(&name)?,
+            symbol: result.get(&symbol)?,
+            decimals: result.get(&decimals)?,
+            total_supply: result.get(&total_supply)?.to_string(),
+        })
+    }
+}
+
+/// Whole tokens to the raw units of a token with the decimals
+pub fn to_raw_amount(amount: &str, decimals: Uint8) -> ContractResult<Uint256> {
+    let value = BigDecimal::from_str(amount.trim())?;
+    if value.sign() != Sign::Plus {
+        return Err(ContractError::Calculation(format!(
+            "amount {} is not positive",
+            amount
+        )));
+    }
+
+    let raw = value * BigDecimal::new(BigInt::from(1), -(decimals as i64));
+    if !raw.is_integer() {
+        return Err(ContractError::Calculation(format!(
+            "amount {} has more than {} decimals",
+            amount, decimals
+        )));
+    }
+
+    Ok(raw.with_scale(0).into_bigint_and_exponent().0.to_string())
+}
+
+/// Raw units of a token with the decimals to whole tokens
+pub fn to_token_amount(raw: &Uint256, decimals: Uint8) -> ContractResult<String> {
+    let value = BigInt::from_str(raw).map_err(|e| ContractError::Calculation(e.to_string()))?;
+
+    Ok(BigDecimal::new(value, decimals as i64)
+        .normalized()
+        .to_plain_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_raw_amount, to_token_amount};
+
+    #[test]
+    fn test_amount() {
+        assert_eq!(to_raw_amount("1.5", 6).unwrap(), "1500000");
+        assert_eq!(to_raw_amount("2", 18).unwrap(), "2000000000000000000");
+        assert_eq!(to_raw_amount("0.00000001", 8).unwrap(), "1");
+        assert!(to_raw_amount("0.0000001", 6).is_err());
+        assert!(to_raw_amount("0", 6).is_err());
+        assert!(to_raw_amount("-1", 6).is_err());
+
+        assert_eq!(to_token_amount(&"1500000".into(), 6).unwrap(), "1.5");
+        assert_eq!(
+            to_token_amount(&"2000000000000000000".into(), 18).unwrap(),
+            "2"
+        );
+        assert_eq!(to_token_amount(&"1".into(), 8).unwrap(), "0.00000001");
+        assert_eq!(to_token_amount(&"0".into(), 8).unwrap(), "0");
+    }
 }
@@ -3,6 +3,7 @@ pub enum ContractError {
     Address(String),
     Execution(String),
     Calculation(String),
+    Block(String),
 }
 
 mod contract_error {
@@ -16,7 +17,14 @@ mod contract_error {
     impl Error for ContractError {}
     impl Display for ContractError {
         fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-            write!(f, "{}", self.to_string())
+            let message = match self {
+                Self::Address(e) => format!("ADDRESS {}", e),
+                Self::Execution(e) => format!("EXECUTION {}", e),
+                Self::Calculation(e) => format!("CALCULATION {}", e),
+                Self::Block(e) => format!("BLOCK {}", e),
+            };
+
+            write!(f, "CONTRACT {}", message)
         }
     }
 
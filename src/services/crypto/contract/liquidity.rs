@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+
+use ethers::types::{BlockId, H160, U256};
+use serde::Serialize;
+
+use super::abi::{CaseERC20, CaseUniswapV3Pool};
+use super::types::*;
+use super::{Contract, ContractError, ContractResult, Multicall};
+
+/// TickMath.MIN_TICK and MAX_TICK
+pub const MIN_TICK: Int24 = -887272;
+pub const MAX_TICK: Int24 = 887272;
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize)]
+pub struct Tick {
+    pub tick:            Int24,
+    pub liquidity_gross: Uint128,
+    // Liquidity added when the price crosses the tick upwards
+    pub liquidity_net:   i128,
+}
+
+/// Ticks between two initialized ticks, their liquidity is constant
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize)]
+pub struct LiquidityRange {
+    pub tick_lower:  Int24,
+    pub tick_upper:  Int24,
+    // Whole token 1 for one whole token 0
+    pub price_lower: f64,
+    pub price_upper: f64,
+    pub liquidity:   Uint128,
+    // Whole tokens held by the range at the current price
+    pub amount_0:    f64,
+    pub amount_1:    f64,
+}
+
+/// Whole tokens swapped until the price moves by the percent. Token 0 leaves
+/// and token 1 enters the pool when the price rises, the reverse when it falls
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize)]
+pub struct Depth {
+    pub percent:  f64,
+    pub price:    f64,
+    pub amount_0: f64,
+    pub amount_1: f64,
+}
+
+#[rustfmt::skip]
+#[derive(Serialize)]
+pub struct LiquidityDistribution {
+    pub block:          u64,
+    pub sqrt_price_x96: Uint160,
+    pub tick:           Int24,
+    pub tick_spacing:   Int24,
+    pub liquidity:      Uint128,
+    pub decimals_0:     Uint8,
+    pub decimals_1:     Uint8,
+    pub price:          f64,
+    pub ticks:          Vec<Tick>,
+    pub ranges:         Vec<LiquidityRange>,
+    pub depth_up:       Depth,
+    pub depth_down:     Depth,
+}
+
+/// Square root of the raw price at the tick
+fn tick_to_sqrt_price(tick: Int24) -> f64 {
+    1.0001f64.powf(tick as f64 / 2.0)
+}
+
+/// Tick of the raw price moved by the fraction
+fn moved_tick(tick: Int24, fraction: f64) -> f64 {
+    tick as f64 + (1.0 + fraction).ln() / 1.0001f64.ln()
+}
+
+/// Raw token amounts between two square root prices with the liquidity
+fn amounts(liquidity: Uint128, sqrt_lower: f64, sqrt_upper: f64) -> (f64, f64) {
+    if sqrt_upper <= sqrt_lower {
+        return (0.0, 0.0);
+    }
+
+    let liquidity = liquidity as f64;
+    (
+        liquidity * (1.0 / sqrt_lower - 1.0 / sqrt_upper),
+        liquidity * (sqrt_upper - sqrt_lower),
+    )
+}
+
+/// Ticks from lower to upper split at the initialized ticks, with the liquidity
+/// of each range walked from the active liquidity of the current tick
+pub fn ranges(
+    tick: Int24,
+    liquidity: Uint128,
+    ticks: &[Tick],
+    lower: Int24,
+    upper: Int24,
+) -> Vec<(Int24, Int24, Uint128)> {
+    let nets: HashMap<Int24, i128> = ticks.iter().map(|v| (v.tick, v.liquidity_net)).collect();
+
+    let mut bounds: Vec<Int24> = ticks
+        .iter()
+        .map(|v| v.tick)
+        .filter(|v| *v > lower && *v < upper)
+        .collect();
+    bounds.push(lower);
+    bounds.push(upper);
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    if bounds.len() < 2 {
+        return Vec::new();
+    }
+
+    let tick = tick.clamp(lower, upper - 1);
+    let current = bounds
+        .windows(2)
+        .position(|v| v[0] <= tick && tick < v[1])
+        .unwrap_or(0);
+
+    let cross =
+        |liquidity: Uint128, net: i128| (liquidity as i128).saturating_add(net).max(0) as Uint128;
+
+    let mut result = vec![0; bounds.len() - 1];
+    result[current] = liquidity;
+    for i in current + 1..result.len() {
+        let net = nets.get(&bounds[i]).copied().unwrap_or(0);
+        result[i] = cross(result[i - 1], net);
+    }
+    for i in (0..current).rev() {
+        let net = nets.get(&bounds[i + 1]).copied().unwrap_or(0);
+        result[i] = cross(result[i + 1], -net);
+    }
+
+    bounds
+        .windows(2)
+        .zip(result)
+        .map(|(v, liquidity)| (v[0], v[1], liquidity))
+        .collect()
+}
+
+/// Raw token amounts swapped moving the square root price from current to target
+pub fn depth(ranges: &[(Int24, Int24, Uint128)], current: f64, target: f64) -> (f64, f64) {
+    let (low, high) = if current <= target {
+        (current, target)
+    } else {
+        (target, current)
+    };
+
+    ranges.iter().fold((0.0, 0.0), |(amount_0, amount_1), v| {
+        let lower = tick_to_sqrt_price(v.0).max(low);
+        let upper = tick_to_sqrt_price(v.1).min(high);
+        let (a, b) = amounts(v.2, lower, upper);
+
+        (amount_0 + a, amount_1 + b)
+    })
+}
+
+fn floor_div(value: Int24, divisor: Int24) -> Int24 {
+    value.div_euclid(divisor)
+}
+
+/// Uniswap V3 Pool Tick Functions
+impl Contract<CaseUniswapV3Pool> {
+    pub async fn tick_spacing(&self) -> ContractResult<Int24> {
+        let result = Self::contract_call(self.pin(self.1.tick_spacing()).call().await)?;
+
+        Ok(result)
+    }
+
+    pub async fn tick_bitmap(&self, word: i16) -> ContractResult<Uint256> {
+        let result: U256 = Self::contract_call(self.pin(self.1.tick_bitmap(word)).call().await)?;
+
+        Ok(result.to_string())
+    }
+
+    pub async fn ticks(&self, tick: Int24) -> ContractResult<Tick> {
+        let result = Self::contract_call(self.pin(self.1.ticks(tick)).call().await)?;
+
+        Ok(Tick {
+            tick,
+            liquidity_gross: result.0,
+            liquidity_net: result.1,
+        })
+    }
+
+    /// Liquidity of the ticks within the percent of the price, and the amounts
+    /// swapped until the price moves by the depth percent either way
+    pub async fn liquidity_distribution(
+        &self,
+        range: f64,
+        depth_percent: f64,
+    ) -> ContractResult<LiquidityDistribution> {
+        let valid = |v: f64| v > 0.0 && v < 100.0;
+        if !valid(range) || !valid(depth_percent) {
+            return Err(ContractError::Calculation(
+                "percent must be between 0 and 100".into(),
+            ));
+        }
+
+        let client = self.1.client();
+
+        let mut multicall = Multicall::new(client.clone())?.block(self.2);
+        let slot_0 = multicall.add(self.1.slot_0())?;
+        let liquidity = multicall.add(self.1.liquidity())?;
+        let tick_spacing = multicall.add(self.1.tick_spacing())?;
+        let token_0 = multicall.add(self.1.token_0())?;
+        let token_1 = multicall.add(self.1.token_1())?;
+        let result = multicall.call().await?;
+
+        let block = result.block;
+        let (sqrt_price_x96, tick, ..) = result.get(&slot_0)?;
+        let liquidity = result.get(&liquidity)?;
+        let spacing = result.get(&tick_spacing)?;
+        let token_0: H160 = result.get(&token_0)?;
+        let token_1: H160 = result.get(&token_1)?;
+
+        // Window of whole tick spacings covering both percents
+        let window = range.max(depth_percent) / 100.0;
+        let lower = moved_tick(tick, -window).floor() as Int24;
+        let upper = moved_tick(tick, window).ceil() as Int24;
+        let lower = (floor_div(lower, spacing) * spacing).max(MIN_TICK);
+        let upper = ((floor_div(upper, spacing) + 1) * spacing).min(MAX_TICK);
+
+        // The rest is read at the block of the slot
+        let pinned = self.2.or(Some(BlockId::from(block)));
+        let mut multicall = Multicall::new(client.clone())?.block(pinned);
+        let decimals_0 = multicall.add(CaseERC20::new(token_0, client.clone()).decimals())?;
+        let decimals_1 = multicall.add(CaseERC20::new(token_1, client.clone()).decimals())?;
+        let mut words = Vec::new();
+        for word in (floor_div(lower, spacing) >> 8)..=(floor_div(upper, spacing) >> 8) {
+            let word = word as i16;
+            words.push((word, multicall.add(self.1.tick_bitmap(word))?));
+        }
+        let result = multicall.call().await?;
+
+        let decimals_0 = result.get(&decimals_0)?;
+        let decimals_1 = result.get(&decimals_1)?;
+        let mut initialized = Vec::new();
+        for (word, call) in words {
+            let bitmap: U256 = result.get(&call)?;
+            for bit in 0..256 {
+                let tick = ((word as Int24) * 256 + bit as Int24) * spacing;
+                if bitmap.bit(bit) && tick >= lower && tick <= upper {
+                    initialized.push(tick);
+                }
+            }
+        }
+
+        let mut multicall = Multicall::new(client.clone())?.block(pinned);
+        let mut calls = Vec::with_capacity(initialized.len());
+        for tick in initialized {
+            calls.push((tick, multicall.add(self.1.ticks(tick))?));
+        }
+        let result = multicall.call().await?;
+
+        let mut ticks = Vec::with_capacity(calls.len());
+        for (tick, call) in calls {
+            let value = result.get(&call)?;
+            ticks.push(Tick {
+                tick,
+                liquidity_gross: value.0,
+                liquidity_net: value.1,
+            });
+        }
+
+        // Raw to whole tokens
+        let scale_0 = 10f64.powi(decimals_0 as i32);
+        let scale_1 = 10f64.powi(decimals_1 as i32);
+        let to_price = |sqrt: f64| sqrt * sqrt * scale_0 / scale_1;
+
+        let current = sqrt_price_x96.to_string().parse::<f64>().unwrap_or(0.0) / 2f64.powi(96);
+        let ranges = ranges(tick, liquidity, &ticks, lower, upper);
+
+        let moved = |fraction: f64| {
+            let target = current * (1.0 + fraction).sqrt();
+            let (amount_0, amount_1) = depth(&ranges, current, target);
+
+            Depth {
+                percent: fraction * 100.0,
+                price: to_price(target),
+                amount_0: amount_0 / scale_0,
+                amount_1: amount_1 / scale_1,
+            }
+        };
+        let depth_up = moved(depth_percent / 100.0);
+        let depth_down = moved(-depth_percent / 100.0);
+
+        let ranges = ranges
+            .iter()
+            .map(|v| {
+                let (sqrt_lower, sqrt_upper) = (tick_to_sqrt_price(v.0), tick_to_sqrt_price(v.1));
+
+                // Token 0 above the price and token 1 below it
+                let (amount_0, _) = amounts(v.2, sqrt_lower.max(current), sqrt_upper);
+                let (_, amount_1) = amounts(v.2, sqrt_lower, sqrt_upper.min(current));
+
+                LiquidityRange {
+                    tick_lower: v.0,
+                    tick_upper: v.1,
+                    price_lower: to_price(sqrt_lower),
+                    price_upper: to_price(sqrt_upper),
+                    liquidity: v.2,
+                    amount_0: amount_0 / scale_0,
+                    amount_1: amount_1 / scale_1,
+                }
+            })
+            .collect();
+
+        Ok(LiquidityDistribution {
+            block,
+            sqrt_price_x96: sqrt_price_x96.to_string(),
+            tick,
+            tick_spacing: spacing,
+            liquidity,
+            decimals_0,
+            decimals_1,
+            price: to_price(current),
+            ticks,
+            ranges,
+            depth_up,
+            depth_down,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{depth, ranges, tick_to_sqrt_price, Tick};
+
+    fn tick(tick: i32, liquidity_net: i128) -> Tick {
+        Tick {
+            tick,
+            liquidity_gross: liquidity_net.unsigned_abs(),
+            liquidity_net,
+        }
+    }
+
+    #[test]
+    fn test_ranges() {
+        // Positions over [-120, 120] and [0, 60]
+        let ticks = [tick(-120, 100), tick(0, 50), tick(60, -50), tick(120, -100)];
+
+        let result = ranges(30, 150, &ticks, -180, 180);
+        assert_eq!(
+            result,
+            vec![
+                (-180, -120, 0),
+                (-120, 0, 100),
+                (0, 60, 150),
+                (60, 120, 100),
+                (120, 180, 0),
+            ]
+        );
+
+        // The window cuts the outer ticks
+        let result = ranges(-10, 100, &ticks, -60, 60);
+        assert_eq!(result, vec![(-60, 0, 100), (0, 60, 150)]);
+    }
+
+    #[test]
+    fn test_depth() {
+        let ranges = [(-600, 0, 1_000_000), (0, 600, 2_000_000)];
+        let current = tick_to_sqrt_price(0);
+
+        // Within one range the amounts follow the constant liquidity
+        let target = tick_to_sqrt_price(300);
+        let (amount_0, amount_1) = depth(&ranges, current, target);
+        assert!((amount_0 - 2e6 * (1.0 / current - 1.0 / target)).abs() < 1e-6);
+        assert!((amount_1 - 2e6 * (target - current)).abs() < 1e-6);
+
+        // Across ranges the amounts add up
+        let target = tick_to_sqrt_price(-900);
+        let (_, amount_1) = depth(&ranges, current, target);
+        let expected = 1e6 * (current - tick_to_sqrt_price(-600));
+        assert!((amount_1 - expected).abs() < 1e-6);
+    }
+}
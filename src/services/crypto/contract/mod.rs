@@ -1,13 +1,34 @@
 mod abi;
 mod erc20;
 mod error;
+mod liquidity;
+mod multicall;
+mod quoter;
 mod uniswap;
+mod uniswap_v2;
 
-/// Contract Struct
-pub struct Contract<T>(pub types::Address, pub T);
+/// Contract Struct, reads at the block when given and at the latest otherwise
+pub struct Contract<T>(
+    pub types::Address,
+    pub T,
+    pub Option<ethers::types::BlockId>,
+);
 
 /// Contract Case
 pub use abi::{CaseERC20, CaseUniswapV3Factory, CaseUniswapV3Pool, Provider};
+pub use abi::{CaseUniswapV2Factory, CaseUniswapV2Pair, CaseUniswapV3QuoterV2};
+
+/// Contract Read Batching
+pub use multicall::{Call, Multicall, MulticallResult, MULTICALL3_ADDRESS};
+
+/// Uniswap V3 Liquidity
+pub use liquidity::{Depth, LiquidityDistribution, LiquidityRange, Tick};
+
+/// Constant Product Amounts
+pub use uniswap_v2::{amount_in, amount_out, amounts_in, amounts_out};
+
+/// Token Amounts
+pub use erc20::{to_raw_amount, to_token_amount};
 
 /// Contract Error
 pub use error::ContractError;
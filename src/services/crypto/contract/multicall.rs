@@ -0,0 +1,138 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use ethers::abi::{Detokenize, Function, ParamType};
+use ethers::contract::ContractCall;
+use ethers::types::{BlockId, U256};
+
+use super::abi::{Call3, CallResult, CaseMulticall3, Provider};
+use super::{Contract, ContractError, ContractResult};
+
+/// Multicall3 has the same address on every supported chain
+/// https://www.multicall3.com/deployments
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// Handle of a read added to a multicall, decodes its own output
+pub struct Call<D> {
+    index: usize,
+    function: Function,
+    output: PhantomData<D>,
+}
+
+/// Contract reads batched into one `aggregate3` eth_call, so they cost one
+/// round trip and all see the state of the same block
+pub struct Multicall {
+    contract: CaseMulticall3,
+    calls: Vec<Call3>,
+    block: Option<BlockId>,
+}
+
+impl Multicall {
+    pub fn new(client: Arc<Provider>) -> ContractResult<Self> {
+        let address = Contract::<()>::to_address(&MULTICALL3_ADDRESS.to_string())?;
+
+        Ok(Self {
+            contract: CaseMulticall3::new(address, client),
+            calls: Vec::new(),
+            block: None,
+        })
+    }
+
+    /// Run the batch at the block, none runs it at the latest block
+    pub fn block(mut self, block: Option<BlockId>) -> Self {
+        self.block = block;
+
+        self
+    }
+
+    pub fn add<D>(&mut self, call: ContractCall<Provider, D>) -> ContractResult<Call<D>>
+    where
+        D: Detokenize,
+    {
+        let target = *call
+            .tx
+            .to_addr()
+            .ok_or(ContractError::Address("multicall target not found".into()))?;
+
+        self.calls.push(Call3 {
+            target,
+            allow_failure: true,
+            call_data: call.calldata().unwrap_or_default(),
+        });
+
+        Ok(Call {
+            index: self.calls.len() - 1,
+            function: call.function,
+            output: PhantomData,
+        })
+    }
+
+    pub async fn call(self) -> ContractResult<MulticallResult> {
+        let Self {
+            contract,
+            mut calls,
+            block,
+        } = self;
+
+        // The block of the batch is read inside the batch
+        calls.push(Call3 {
+            target: contract.address(),
+            allow_failure: false,
+            call_data: contract.get_block_number().calldata().unwrap_or_default(),
+        });
+
+        let mut aggregate = contract.aggregate_3(calls);
+        if let Some(v) = block {
+            aggregate = aggregate.block(v);
+        }
+        let mut results = Contract::<()>::contract_call(aggregate.call().await)?;
+
+        let block = results
+            .pop()
+            .ok_or(ContractError::Execution("multicall block not found".into()))?;
+        let block = ethers::abi::decode(&[ParamType::Uint(256)], &block.return_data)
+            .map_err(|e| ContractError::Execution(e.to_string()))?
+            .pop()
+            .and_then(|v| v.into_uint())
+            .unwrap_or_default();
+
+        Ok(MulticallResult {
+            block: block.min(U256::from(u64::MAX)).as_u64(),
+            results,
+        })
+    }
+}
+
+pub struct MulticallResult {
+    pub block: u64,
+    results: Vec<CallResult>,
+}
+
+impl MulticallResult {
+    pub fn get<D>(&self, call: &Call<D>) -> ContractResult<D>
+    where
+        D: Detokenize,
+    {
+        let result = self
+            .results
+            .get(call.index)
+            .ok_or(ContractError::Execution(format!(
+                "multicall {} result not found",
+                call.function.name
+            )))?;
+
+        if !result.success {
+            return Err(ContractError::Execution(format!(
+                "multicall {} reverted",
+                call.function.name
+            )));
+        }
+
+        let tokens = call
+            .function
+            .decode_output(&result.return_data)
+            .map_err(|e| ContractError::Execution(e.to_string()))?;
+
+        D::from_tokens(tokens).map_err(|e| ContractError::Execution(e.to_string()))
+    }
+}
@@ -0,0 +1,145 @@
+use ethers::types::{Bytes, H160, U256};
+use serde::Serialize;
+
+use super::abi::CaseUniswapV3QuoterV2;
+use super::{types::*, Contract, ContractError, ContractResult};
+
+#[rustfmt::skip]
+#[derive(Serialize)]
+pub struct Quote {
+    pub amount_in:            Uint256,
+    pub amount_out:           Uint256,
+    // Of every pool in the order of the path, from the input token
+    pub sqrt_price_x96_after: Vec<Uint160>,
+    pub ticks_crossed:        Vec<u32>,
+    pub gas_estimate:         Uint256,
+}
+
+/// Path of a swap, every token followed by the fee of the pool to the next one
+pub fn encode_path(tokens: &[H160], fees: &[Uint24]) -> ContractResult<Bytes> {
+    if tokens.len() < 2 || fees.len() != tokens.len() - 1 {
+        return Err(ContractError::Address(format!(
+            "path of {} tokens and {} fees is invalid",
+            tokens.len(),
+            fees.len()
+        )));
+    }
+
+    let mut path = Vec::with_capacity(tokens.len() * 20 + fees.len() * 3);
+    for (i, token) in tokens.iter().enumerate() {
+        path.extend_from_slice(token.as_bytes());
+
+        if let Some(fee) = fees.get(i) {
+            if *fee > 0xFFFFFF {
+                return Err(ContractError::Calculation(format!(
+                    "fee {} is not a uint24",
+                    fee
+                )));
+            }
+            path.extend_from_slice(&fee.to_be_bytes()[1..]);
+        }
+    }
+
+    Ok(path.into())
+}
+
+fn to_u256(value: &Uint256) -> ContractResult<U256> {
+    U256::from_dec_str(value).map_err(|e| ContractError::Calculation(e.to_string()))
+}
+
+fn to_addresses(tokens: &[Address]) -> ContractResult<Vec<H160>> {
+    tokens.iter().map(Contract::<()>::to_address).collect()
+}
+
+/// Uniswap V3 QuoterV2 Read Contract Functions. The quoter simulates the swap
+/// and reverts with the result, so it is only ever read with eth_call
+impl Contract<CaseUniswapV3QuoterV2> {
+    /// Output of swapping exactly the raw input along the tokens
+    pub async fn quote_exact_input(
+        &self,
+        tokens: &[Address],
+        fees: &[Uint24],
+        amount_in: &Uint256,
+    ) -> ContractResult<Quote> {
+        let path = encode_path(&to_addresses(tokens)?, fees)?;
+        let call = self.1.quote_exact_input(path, to_u256(amount_in)?);
+
+        let (amount_out, sqrt_price_x96_after, ticks_crossed, gas_estimate) =
+            Self::contract_call(self.pin(call).call().await)?;
+
+        Ok(Quote {
+            amount_in: amount_in.clone(),
+            amount_out: amount_out.to_string(),
+            sqrt_price_x96_after: sqrt_price_x96_after.iter().map(|v| v.to_string()).collect(),
+            ticks_crossed,
+            gas_estimate: gas_estimate.to_string(),
+        })
+    }
+
+    /// Input needed for exactly the raw output along the tokens
+    pub async fn quote_exact_output(
+        &self,
+        tokens: &[Address],
+        fees: &[Uint24],
+        amount_out: &Uint256,
+    ) -> ContractResult<Quote> {
+        // The quoter walks an exact output path from the output token
+        let mut tokens = to_addresses(tokens)?;
+        let mut fees = fees.to_vec();
+        tokens.reverse();
+        fees.reverse();
+
+        let path = encode_path(&tokens, &fees)?;
+        let call = self.1.quote_exact_output(path, to_u256(amount_out)?);
+
+        let (amount_in, mut sqrt_price_x96_after, mut ticks_crossed, gas_estimate) =
+            Self::contract_call(self.pin(call).call().await)?;
+        sqrt_price_x96_after.reverse();
+        ticks_crossed.reverse();
+
+        Ok(Quote {
+            amount_in: amount_in.to_string(),
+            amount_out: amount_out.clone(),
+            sqrt_price_x96_after: sqrt_price_x96_after.iter().map(|v| v.to_string()).collect(),
+            ticks_crossed,
+            gas_estimate: gas_estimate.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H160;
+
+    use super::encode_path;
+
+    #[test]
+    fn test_encode_path() {
+        let weth: H160 = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+            .parse()
+            .unwrap();
+        let usdc: H160 = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+            .parse()
+            .unwrap();
+        let dai: H160 = "0x6b175474e89094c44da98b954eedeac495271d0f"
+            .parse()
+            .unwrap();
+
+        let path = encode_path(&[weth, usdc], &[500]).unwrap();
+        assert_eq!(
+            path.to_string(),
+            "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2\
+             0001f4\
+             a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        );
+
+        let path = encode_path(&[weth, usdc, dai], &[3000, 100]).unwrap();
+        assert_eq!(path.len(), 20 * 3 + 3 * 2);
+        assert_eq!(&path[20..23], &[0x00, 0x0b, 0xb8]);
+        assert_eq!(&path[43..46], &[0x00, 0x00, 0x64]);
+
+        assert!(encode_path(&[weth], &[]).is_err());
+        assert!(encode_path(&[weth, usdc], &[500, 3000]).is_err());
+        assert!(encode_path(&[weth, usdc], &[0x1000000]).is_err());
+    }
+}
@@ -1,35 +1,125 @@
 mod custom_uniswap_v3_math {
-    const NUM_2_POW_192: &str = "6277101735386680763835789423207666416102355444464034512896";
-
     use std::str::FromStr;
 
-    use bigdecimal::{num_bigint::BigInt, BigDecimal};
+    use bigdecimal::num_bigint::{BigInt, Sign};
+    use bigdecimal::BigDecimal;
+    use ethers::types::U256;
 
+    use super::super::liquidity::MAX_TICK;
     use super::super::types::*;
-    use super::super::ContractResult;
+    use super::super::{ContractError, ContractResult};
+
+    /// 2^128 / sqrt(1.0001)^bit for the bits of the tick above the first, from TickMath
+    #[rustfmt::skip]
+    const RATIOS: [(u32, &str); 19] = [
+        (0x2,     "fff97272373d413259a46990580e213a"),
+        (0x4,     "fff2e50f5f656932ef12357cf3c7fdcc"),
+        (0x8,     "ffe5caca7e10e4e61c3624eaa0941cd0"),
+        (0x10,    "ffcb9843d60f6159c9db58835c926644"),
+        (0x20,    "ff973b41fa98c081472e6896dfb254c0"),
+        (0x40,    "ff2ea16466c96a3843ec78b326b52861"),
+        (0x80,    "fe5dee046a99a2a811c461f1969c3053"),
+        (0x100,   "fcbe86c7900a88aedcffc83b479aa3a4"),
+        (0x200,   "f987a7253ac413176f2b074cf7815e54"),
+        (0x400,   "f3392b0822b70005940c7a398e4b70f3"),
+        (0x800,   "e7159475a2c29b7443b29c7fa6e889d9"),
+        (0x1000,  "d097f3bdfd2022b8845ad8f792aa5825"),
+        (0x2000,  "a9f746462d870fdf8a65dc1f90e061e5"),
+        (0x4000,  "70d869a156d2a1b890bb3df62baf32f7"),
+        (0x8000,  "31be135f97d08fd981231505542fcfa6"),
+        (0x10000, "9aa508b5b7a84e1c677de54f3e99bc9"),
+        (0x20000, "5d6af8dedb81196699c329225ee604"),
+        (0x40000, "2216e584f5fa1ea926041bedfe98"),
+        (0x80000, "48a170391f7dc42444e8fa2"),
+    ];
+
+    /// TickMath.getSqrtRatioAtTick, sqrt(1.0001^tick) * 2^96 rounded up
+    pub fn sqrt_ratio_at_tick(tick: Int24) -> ContractResult<U256> {
+        let hex = |v: &str| U256::from_str_radix(v, 16).unwrap_or_default();
+
+        let abs = tick.unsigned_abs();
+        if abs > MAX_TICK as u32 {
+            return Err(ContractError::Calculation(format!(
+                "tick {} is out of range",
+                tick
+            )));
+        }
+
+        let mut ratio = match abs & 0x1 {
+            0 => U256::one() << 128,
+            _ => hex("fffcb933bd6fad37aa2d162d1a594001"),
+        };
+        for (bit, value) in RATIOS {
+            if abs & bit != 0 {
+                ratio = (ratio * hex(value)) >> 128;
+            }
+        }
+        if tick > 0 {
+            ratio = U256::MAX / ratio;
+        }
 
-    pub fn sqrt_price_x96_to_price(value: &Uint256, decimals: u32) -> ContractResult<Uint256> {
-        let value = BigDecimal::from_str(value)?;
-        let num = BigDecimal::from_str(NUM_2_POW_192)?;
-        let result = (value.square() / num) / num_10_pow_of(decimals);
+        let round = match (ratio % (U256::one() << 32)).is_zero() {
+            true => U256::zero(),
+            false => U256::one(),
+        };
 
-        Ok(result.to_string())
+        Ok((ratio >> 32) + round)
     }
 
-    pub fn sqrt_price_x96_to_price_inverse(
-        value: &Uint256,
-        decimals: u32,
-    ) -> ContractResult<Uint256> {
-        let value = BigDecimal::from_str(value)?;
-        let num = BigDecimal::from_str(NUM_2_POW_192)?;
+    /// Significant digits of a price that has no finite decimal expansion
+    pub const PRECISION: u64 = 100;
+
+    /// Whole token 1 for one whole token 0. The pool price is sqrtPriceX96² / 2^192
+    /// in raw units and 1 / 2^192 = 5^192 / 10^192, so the result is exact
+    pub fn token_0_price(
+        sqrt_price_x96: &str,
+        decimals_0: u8,
+        decimals_1: u8,
+    ) -> ContractResult<BigDecimal> {
+        let value = BigInt::from_str(sqrt_price_x96)
+            .map_err(|e| ContractError::Calculation(e.to_string()))?;
+        if value.sign() != Sign::Plus {
+            return Err(ContractError::Calculation(format!(
+                "sqrt price {} is not positive",
+                sqrt_price_x96
+            )));
+        }
+
+        let digits = &value * &value * BigInt::from(5).pow(192);
+        let scale = 192 - (decimals_0 as i64 - decimals_1 as i64);
 
-        let result = num_10_pow_of(decimals) / (value.square() / num);
+        Ok(BigDecimal::new(digits, scale).normalized())
+    }
+
+    /// Whole token 0 for one whole token 1, rounded to `PRECISION` significant digits
+    pub fn token_1_price(
+        sqrt_price_x96: &str,
+        decimals_0: u8,
+        decimals_1: u8,
+    ) -> ContractResult<BigDecimal> {
+        let price = token_0_price(sqrt_price_x96, decimals_0, decimals_1)?;
 
-        Ok(result.to_string())
+        Ok(price.inverse().with_prec(PRECISION).normalized())
     }
 
-    pub fn num_10_pow_of(exponent: u32) -> BigDecimal {
-        BigDecimal::new(BigInt::from(10).pow(exponent), 0)
+    /// Quote per base and base per quote in whole tokens
+    pub fn base_quote_prices(
+        sqrt_price_x96: &str,
+        base_is_token_0: bool,
+        base_decimals: u8,
+        quote_decimals: u8,
+    ) -> ContractResult<(BigDecimal, BigDecimal)> {
+        if base_is_token_0 {
+            Ok((
+                token_0_price(sqrt_price_x96, base_decimals, quote_decimals)?,
+                token_1_price(sqrt_price_x96, base_decimals, quote_decimals)?,
+            ))
+        } else {
+            Ok((
+                token_1_price(sqrt_price_x96, quote_decimals, base_decimals)?,
+                token_0_price(sqrt_price_x96, quote_decimals, base_decimals)?,
+            ))
+        }
     }
 }
 
@@ -38,33 +128,89 @@ mod contract_uniswap_v3_pool {
 
     use super::super::abi::{CaseERC20, CaseUniswapV3Pool};
     use super::super::types::*;
-    use super::super::{Contract, ContractResult};
+    use super::super::{Contract, ContractError, ContractResult, Multicall};
 
     /// Uniswap V3 Pool Custom Contract Functions
     impl Contract<CaseUniswapV3Pool> {
+        /// Price of the base in the quote, both normalised for the token decimals
         pub async fn price(
             &self,
             base: &Contract<CaseERC20>,
             quote: &Contract<CaseERC20>,
-        ) -> ContractResult<Uint256> {
-            use super::custom_uniswap_v3_math::{
-                sqrt_price_x96_to_price, sqrt_price_x96_to_price_inverse,
-            };
-
-            let slot_0 = self.slot_0().await?;
-
-            // ERC-20 Token decimals abs
-            let decimals =
-                (base.decimals().await? as i16 - quote.decimals().await? as i16).abs() as u32;
+        ) -> ContractResult<PoolPrice> {
+            use super::custom_uniswap_v3_math::base_quote_prices;
+
+            // One round trip for the reads of the pool and both tokens
+            let mut multicall = Multicall::new(self.1.client())?.block(self.2);
+            let slot_0 = multicall.add(self.1.slot_0())?;
+            let token_0 = multicall.add(self.1.token_0())?;
+            let token_1 = multicall.add(self.1.token_1())?;
+            let base_decimals = multicall.add(base.1.decimals())?;
+            let quote_decimals = multicall.add(quote.1.decimals())?;
+            let result = multicall.call().await?;
+
+            let slot_0 = to_slot_0(result.get(&slot_0)?);
+            let token_0 = to_hex(result.get(&token_0)?);
+            let token_1 = to_hex(result.get(&token_1)?);
+            let base_decimals = result.get(&base_decimals)?;
+            let quote_decimals = result.get(&quote_decimals)?;
+
+            let (quote_per_base, base_per_quote) = base_quote_prices(
+                &slot_0.sqrt_price_x96,
+                self.base_is_token_0(base, quote, &token_0, &token_1)?,
+                base_decimals,
+                quote_decimals,
+            )?;
+
+            Ok(PoolPrice {
+                block: result.block,
+                sqrt_price_x96: slot_0.sqrt_price_x96,
+                tick: slot_0.tick,
+                base_decimals,
+                quote_decimals,
+                quote_per_base: quote_per_base.to_plain_string(),
+                base_per_quote: base_per_quote.to_plain_string(),
+            })
+        }
 
-            if base.is_address_match(&self.token_0().await?) {
-                sqrt_price_x96_to_price(&slot_0.sqrt_price_x96, decimals)
-            } else {
-                sqrt_price_x96_to_price_inverse(&slot_0.sqrt_price_x96, decimals)
+        /// Whether the base is token 0 of the pool and the quote token 1, or the reverse
+        pub(super) fn base_is_token_0(
+            &self,
+            base: &Contract<CaseERC20>,
+            quote: &Contract<CaseERC20>,
+            token_0: &Address,
+            token_1: &Address,
+        ) -> ContractResult<bool> {
+            if base.is_address_match(token_0) && quote.is_address_match(token_1) {
+                return Ok(true);
             }
+            if base.is_address_match(token_1) && quote.is_address_match(token_0) {
+                return Ok(false);
+            }
+
+            Err(ContractError::Address(format!(
+                "pool {} is not of {} and {}",
+                self.address(),
+                base.address(),
+                quote.address()
+            )))
         }
     }
 
+    /// Price of a pool in whole tokens. The price of token 0 in token 1 is exact,
+    /// its inverse is rounded to 100 significant digits
+    #[rustfmt::skip]
+    #[derive(Serialize)]
+    pub struct PoolPrice {
+        pub block:          u64,
+        pub sqrt_price_x96: Uint160,
+        pub tick:           Int24,
+        pub base_decimals:  Uint8,
+        pub quote_decimals: Uint8,
+        pub quote_per_base: String,
+        pub base_per_quote: String,
+    }
+
     #[rustfmt::skip]
     #[derive(Serialize)]
     pub struct Slot0 {
@@ -77,54 +223,104 @@ mod contract_uniswap_v3_pool {
         pub unlocked:                     Bool,
     }
 
+    #[rustfmt::skip]
+    #[derive(Serialize)]
+    pub struct PoolState {
+        pub block:                   u64,
+        pub slot_0:                  Slot0,
+        pub factory:                 Address,
+        pub fee:                     Uint24,
+        pub fee_growth_global_0x128: Uint256,
+        pub fee_growth_global_1x128: Uint256,
+        pub liquidity:               Uint128,
+        pub token_0:                 Address,
+        pub token_1:                 Address,
+    }
+
+    pub(super) fn to_slot_0(value: (ethers::types::U256, i32, u16, u16, u16, u8, bool)) -> Slot0 {
+        let (
+            sqrt_price_x96,
+            tick,
+            observation_index,
+            observation_cardinality,
+            observation_cardinality_next,
+            fee_protocol,
+            unlocked,
+        ) = value;
+
+        Slot0 {
+            sqrt_price_x96: sqrt_price_x96.to_string(),
+            tick,
+            observation_index,
+            observation_cardinality,
+            observation_cardinality_next,
+            fee_protocol,
+            unlocked,
+        }
+    }
+
+    pub(super) fn to_hex(address: ethers::types::H160) -> Address {
+        use ethers::utils::hex::ToHexExt;
+
+        address.encode_hex_with_prefix()
+    }
+
     /// Uniswap V3 Pool Read Contract Functions
     impl Contract<CaseUniswapV3Pool> {
         pub async fn slot_0(&self) -> ContractResult<Slot0> {
-            let execute = Self::contract_call(self.1.slot_0().call().await)?;
+            let execute = Self::contract_call(self.pin(self.1.slot_0()).call().await)?;
 
-            let (
-                sqrt_price_x96,
-                tick,
-                observation_index,
-                observation_cardinality,
-                observation_cardinality_next,
-                fee_protocol,
-                unlocked,
-            ) = execute;
-
-            let result = Slot0 {
-                sqrt_price_x96: sqrt_price_x96.to_string(),
-                tick,
-                observation_index,
-                observation_cardinality,
-                observation_cardinality_next,
-                fee_protocol,
-                unlocked,
-            };
+            Ok(to_slot_0(execute))
+        }
 
-            Ok(result)
+        /// Every read of the pool in one round trip, all of the same block
+        pub async fn state(&self) -> ContractResult<PoolState> {
+            let mut multicall = Multicall::new(self.1.client())?.block(self.2);
+            let slot_0 = multicall.add(self.1.slot_0())?;
+            let factory = multicall.add(self.1.factory())?;
+            let fee = multicall.add(self.1.fee())?;
+            let fee_growth_global_0x128 = multicall.add(self.1.fee_growth_global_0x128())?;
+            let fee_growth_global_1x128 = multicall.add(self.1.fee_growth_global_1x128())?;
+            let liquidity = multicall.add(self.1.liquidity())?;
+            let token_0 = multicall.add(self.1.token_0())?;
+            let token_1 = multicall.add(self.1.token_1())?;
+            let result = multicall.call().await?;
+
+            Ok(PoolState {
+                block: result.block,
+                slot_0: to_slot_0(result.get(&slot_0)?),
+                factory: to_hex(result.get(&factory)?),
+                fee: result.get(&fee)?,
+                fee_growth_global_0x128: result.get(&fee_growth_global_0x128)?.to_string(),
+                fee_growth_global_1x128: result.get(&fee_growth_global_1x128)?.to_string(),
+                liquidity: result.get(&liquidity)?,
+                token_0: to_hex(result.get(&token_0)?),
+                token_1: to_hex(result.get(&token_1)?),
+            })
         }
 
         pub async fn fee(&self) -> ContractResult<Uint24> {
-            let result = Self::contract_call(self.case().fee().call().await)?;
+            let result = Self::contract_call(self.pin(self.case().fee()).call().await)?;
 
             Ok(result)
         }
 
         pub async fn fee_growth_global_0x128(&self) -> ContractResult<Uint256> {
-            let result = Self::contract_call(self.case().fee_growth_global_0x128().call().await)?;
+            let result =
+                Self::contract_call(self.pin(self.case().fee_growth_global_0x128()).call().await)?;
 
             Ok(result.to_string())
         }
 
         pub async fn fee_growth_global_1x128(&self) -> ContractResult<Uint256> {
-            let result = Self::contract_call(self.case().fee_growth_global_1x128().call().await)?;
+            let result =
+                Self::contract_call(self.pin(self.case().fee_growth_global_1x128()).call().await)?;
 
             Ok(result.to_string())
         }
 
         pub async fn liquidity(&self) -> ContractResult<Uint128> {
-            let result = Self::contract_call(self.case().liquidity().call().await)?;
+            let result = Self::contract_call(self.pin(self.case().liquidity()).call().await)?;
 
             Ok(result)
         }
@@ -133,7 +329,7 @@ mod contract_uniswap_v3_pool {
             use ethers::types::H160;
             use ethers::utils::hex::ToHexExt;
 
-            let result: H160 = Self::contract_call(self.case().factory().call().await)?;
+            let result: H160 = Self::contract_call(self.pin(self.case().factory()).call().await)?;
 
             Ok(result.encode_hex_with_prefix())
         }
@@ -142,7 +338,7 @@ mod contract_uniswap_v3_pool {
             use ethers::types::H160;
             use ethers::utils::hex::ToHexExt;
 
-            let result: H160 = Self::contract_call(self.case().token_0().call().await)?;
+            let result: H160 = Self::contract_call(self.pin(self.case().token_0()).call().await)?;
 
             Ok(result.encode_hex_with_prefix())
         }
@@ -151,7 +347,7 @@ mod contract_uniswap_v3_pool {
             use ethers::types::H160;
             use ethers::utils::hex::ToHexExt;
 
-            let result: H160 = Self::contract_call(self.case().token_1().call().await)?;
+            let result: H160 = Self::contract_call(self.pin(self.case().token_1()).call().await)?;
 
             Ok(result.encode_hex_with_prefix())
         }
@@ -159,9 +355,33 @@ mod contract_uniswap_v3_pool {
 }
 
 mod contract_uniswap_v3_factory {
-    use super::super::abi::CaseUniswapV3Factory;
+    use ethers::types::{BlockId, H160};
+    use ethers::utils::hex::ToHexExt;
+    use serde::Serialize;
+
+    use super::super::abi::{CaseUniswapV3Factory, CaseUniswapV3Pool};
     use super::super::types::*;
-    use super::super::{Contract, ContractResult};
+    use super::super::{Contract, ContractError, ContractResult, Multicall};
+
+    /// Fee tiers enabled on every factory, in hundredths of a basis point
+    pub const FEE_TIERS: [Uint24; 4] = [100, 500, 3000, 10000];
+
+    #[rustfmt::skip]
+    #[derive(Serialize)]
+    pub struct RankedPool {
+        pub address:   Address,
+        pub fee:       Uint24,
+        // In range liquidity
+        pub liquidity: Uint128,
+    }
+
+    #[rustfmt::skip]
+    #[derive(Serialize)]
+    pub struct RankedPools {
+        pub block: u64,
+        // The most liquid first
+        pub pools: Vec<RankedPool>,
+    }
 
     impl Contract<CaseUniswapV3Factory> {
         pub async fn get_pool(
@@ -170,17 +390,427 @@ mod contract_uniswap_v3_factory {
             address_1: &Address,
             fee: Uint24,
         ) -> ContractResult<Address> {
-            use ethers::types::H160;
-            use ethers::utils::hex::ToHexExt;
+            let token_0 = Self::to_address(address_0)?;
+            let token_1 = Self::to_address(address_1)?;
+
+            let result: H160 = Self::contract_call(
+                self.pin(self.1.get_pool(token_0, token_1, fee))
+                    .call()
+                    .await,
+            )?;
+
+            if result.is_zero() {
+                return Err(ContractError::Address(format!(
+                    "pool of {} and {} with fee {} not found",
+                    address_0, address_1, fee
+                )));
+            }
 
-            // FIX: stack overflow when params not true
-            let address_0 = Self::to_address(address_0)?;
-            let address_1 = Self::to_address(address_1)?;
+            Ok(result.encode_hex_with_prefix())
+        }
 
-            let result: H160 =
-                Self::contract_call(self.1.get_pool(address_0, address_1, fee).call().await)?;
+        /// Pools of the pair in every fee tier ranked by liquidity
+        pub async fn pools(
+            &self,
+            address_0: &Address,
+            address_1: &Address,
+        ) -> ContractResult<RankedPools> {
+            let token_0 = Self::to_address(address_0)?;
+            let token_1 = Self::to_address(address_1)?;
+            let client = self.1.client();
+
+            let mut multicall = Multicall::new(client.clone())?.block(self.2);
+            let mut calls = Vec::with_capacity(FEE_TIERS.len());
+            for fee in FEE_TIERS {
+                calls.push((fee, multicall.add(self.1.get_pool(token_0, token_1, fee))?));
+            }
+            let result = multicall.call().await?;
+
+            let mut pools = Vec::new();
+            for (fee, call) in calls {
+                let address = result.get(&call)?;
+                if !address.is_zero() {
+                    pools.push((fee, address));
+                }
+            }
 
-            Ok(result.encode_hex_with_prefix())
+            // The liquidity is read at the block of the addresses
+            let block = self.2.or(Some(BlockId::from(result.block)));
+            let mut multicall = Multicall::new(client.clone())?.block(block);
+            let mut calls = Vec::with_capacity(pools.len());
+            for (_, address) in pools.iter() {
+                let pool = CaseUniswapV3Pool::new(*address, client.clone());
+                calls.push(multicall.add(pool.liquidity())?);
+            }
+            let result = multicall.call().await?;
+
+            let mut ranked = Vec::with_capacity(pools.len());
+            for ((fee, address), call) in pools.into_iter().zip(calls) {
+                ranked.push(RankedPool {
+                    address: address.encode_hex_with_prefix(),
+                    fee,
+                    liquidity: result.get(&call)?,
+                });
+            }
+            ranked.sort_by(|a, b| b.liquidity.cmp(&a.liquidity).then(a.fee.cmp(&b.fee)));
+
+            Ok(RankedPools {
+                block: result.block,
+                pools: ranked,
+            })
+        }
+
+        /// The most liquid pool of the pair in any fee tier
+        pub async fn best_pool(
+            &self,
+            address_0: &Address,
+            address_1: &Address,
+        ) -> ContractResult<RankedPool> {
+            self.pools(address_0, address_1)
+                .await?
+                .pools
+                .into_iter()
+                .find(|v| v.liquidity > 0)
+                .ok_or(ContractError::Address(format!(
+                    "pool of {} and {} with liquidity not found",
+                    address_0, address_1
+                )))
+        }
+
+        /// Pool of the fee tier when given, the most liquid pool otherwise
+        pub async fn pool(
+            &self,
+            address_0: &Address,
+            address_1: &Address,
+            fee: Option<Uint24>,
+        ) -> ContractResult<Address> {
+            match fee {
+                Some(v) => self.get_pool(address_0, address_1, v).await,
+                None => Ok(self.best_pool(address_0, address_1).await?.address),
+            }
+        }
+    }
+}
+
+mod contract_uniswap_v3_oracle {
+    use ethers::types::U256;
+    use serde::Serialize;
+
+    use super::super::abi::{CaseERC20, CaseUniswapV3Pool};
+    use super::super::types::*;
+    use super::super::{Contract, ContractError, ContractResult, Multicall};
+    use super::contract_uniswap_v3_pool::{to_hex, to_slot_0};
+    use super::custom_uniswap_v3_math::{base_quote_prices, sqrt_ratio_at_tick};
+
+    #[rustfmt::skip]
+    #[derive(Serialize)]
+    pub struct Observation {
+        pub block_timestamp:                       u32,
+        pub tick_cumulative:                       i64,
+        pub seconds_per_liquidity_cumulative_x128: Uint160,
+        pub initialized:                           Bool,
+    }
+
+    /// Price of a pool over a window of seconds ending at the block
+    #[rustfmt::skip]
+    #[derive(Serialize)]
+    pub struct Twap {
+        pub block:                   u64,
+        pub window:                  u32,
+        // Arithmetic mean tick of the window and the tick at the block
+        pub tick:                    Int24,
+        pub spot_tick:               Int24,
+        // At the mean tick
+        pub sqrt_price_x96:          Uint160,
+        pub harmonic_mean_liquidity: Uint128,
+        pub base_decimals:           Uint8,
+        pub quote_decimals:          Uint8,
+        pub quote_per_base:          String,
+        pub base_per_quote:          String,
+    }
+
+    /// Mean tick of two tick cumulatives as OracleLibrary.consult, rounded to negative infinity
+    pub fn mean_tick(start: i64, end: i64, window: u32) -> Int24 {
+        let (delta, window) = (end - start, window as i64);
+
+        let mut tick = delta / window;
+        if delta < 0 && delta % window != 0 {
+            tick -= 1;
+        }
+
+        tick as Int24
+    }
+
+    /// Harmonic mean liquidity of two seconds per liquidity cumulatives as OracleLibrary.consult
+    pub fn harmonic_mean_liquidity(start: U256, end: U256, window: u32) -> Uint128 {
+        let max_160 = (U256::one() << 160) - 1;
+        let max_192 = (U256::one() << 192) - 1;
+
+        // The cumulatives are uint160 and wrap around
+        let delta = end.overflowing_sub(start).0 & max_160;
+        let denominator = (delta << 32) & max_192;
+        if denominator.is_zero() {
+            return 0;
+        }
+
+        (U256::from(window) * max_160 / denominator).low_u128()
+    }
+
+    /// Uniswap V3 Pool Oracle Functions
+    impl Contract<CaseUniswapV3Pool> {
+        /// Tick and seconds per liquidity cumulatives of every seconds ago
+        pub async fn observe(
+            &self,
+            seconds_agos: Vec<u32>,
+        ) -> ContractResult<(Vec<i64>, Vec<Uint160>)> {
+            let (ticks, seconds) =
+                Self::contract_call(self.pin(self.1.observe(seconds_agos)).call().await)?;
+
+            Ok((ticks, seconds.iter().map(|v| v.to_string()).collect()))
+        }
+
+        pub async fn observations(&self, index: Uint16) -> ContractResult<Observation> {
+            let call = self.1.observations(U256::from(index));
+            let (block_timestamp, tick_cumulative, seconds, initialized) =
+                Self::contract_call(self.pin(call).call().await)?;
+
+            Ok(Observation {
+                block_timestamp,
+                tick_cumulative,
+                seconds_per_liquidity_cumulative_x128: seconds.to_string(),
+                initialized,
+            })
+        }
+
+        /// Arithmetic mean tick and its price over the window in seconds
+        pub async fn twap(
+            &self,
+            base: &Contract<CaseERC20>,
+            quote: &Contract<CaseERC20>,
+            window: u32,
+        ) -> ContractResult<Twap> {
+            if window == 0 {
+                return Err(ContractError::Calculation("window must be positive".into()));
+            }
+
+            let mut multicall = Multicall::new(self.1.client())?.block(self.2);
+            let slot_0 = multicall.add(self.1.slot_0())?;
+            let token_0 = multicall.add(self.1.token_0())?;
+            let token_1 = multicall.add(self.1.token_1())?;
+            let base_decimals = multicall.add(base.1.decimals())?;
+            let quote_decimals = multicall.add(quote.1.decimals())?;
+            let observe = multicall.add(self.1.observe(vec![window, 0]))?;
+            let result = multicall.call().await?;
+
+            let slot_0 = to_slot_0(result.get(&slot_0)?);
+            let token_0 = to_hex(result.get(&token_0)?);
+            let token_1 = to_hex(result.get(&token_1)?);
+            let base_decimals = result.get(&base_decimals)?;
+            let quote_decimals = result.get(&quote_decimals)?;
+
+            // Observe reverts when the window goes past the oldest observation
+            let (ticks, seconds) = match result.get(&observe) {
+                Ok(v) => v,
+                Err(_) => {
+                    let oldest = (slot_0.observation_index as u32 + 1)
+                        % (slot_0.observation_cardinality.max(1) as u32);
+                    let mut observation = self.observations(oldest as Uint16).await?;
+                    if !observation.initialized {
+                        observation = self.observations(0).await?;
+                    }
+
+                    return Err(ContractError::Execution(format!(
+                        "window {} goes past the oldest observation at {}, the pool keeps {}",
+                        window, observation.block_timestamp, slot_0.observation_cardinality
+                    )));
+                }
+            };
+            if ticks.len() != 2 || seconds.len() != 2 {
+                return Err(ContractError::Execution(
+                    "observe returned no window".into(),
+                ));
+            }
+
+            let tick = mean_tick(ticks[0], ticks[1], window);
+            let sqrt_price_x96 = sqrt_ratio_at_tick(tick)?.to_string();
+            let (quote_per_base, base_per_quote) = base_quote_prices(
+                &sqrt_price_x96,
+                self.base_is_token_0(base, quote, &token_0, &token_1)?,
+                base_decimals,
+                quote_decimals,
+            )?;
+
+            Ok(Twap {
+                block: result.block,
+                window,
+                tick,
+                spot_tick: slot_0.tick,
+                sqrt_price_x96,
+                harmonic_mean_liquidity: harmonic_mean_liquidity(seconds[0], seconds[1], window),
+                base_decimals,
+                quote_decimals,
+                quote_per_base: quote_per_base.to_plain_string(),
+                base_per_quote: base_per_quote.to_plain_string(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bigdecimal::num_bigint::{BigInt, BigUint};
+    use bigdecimal::{BigDecimal, ToPrimitive};
+    use ethers::types::U256;
+    use proptest::prelude::*;
+
+    use super::contract_uniswap_v3_oracle::{harmonic_mean_liquidity, mean_tick};
+    use super::custom_uniswap_v3_math::{
+        sqrt_ratio_at_tick, token_0_price, token_1_price, PRECISION,
+    };
+
+    // TickMath.MIN_SQRT_RATIO and MAX_SQRT_RATIO
+    const MIN_SQRT_RATIO: u128 = 4295128739;
+    const MAX_SQRT_RATIO: &str = "1461446703485210103287273052203988822378723970342";
+
+    fn sqrt_price() -> impl Strategy<Value = String> {
+        (any::<u128>(), any::<u64>()).prop_map(|(low, high)| {
+            let max = BigUint::from_str(MAX_SQRT_RATIO).unwrap() - MIN_SQRT_RATIO;
+            let value = ((BigUint::from(high) << 128) + low) % max + MIN_SQRT_RATIO;
+
+            value.to_string()
+        })
+    }
+
+    fn decimal(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_known_pools() {
+        // USDC (6) / WETH (18) with ether at 3000 USDC
+        let sqrt = "1446501726624926496477173928747177";
+        let usdc = token_0_price(sqrt, 6, 18).unwrap();
+        let ether = token_1_price(sqrt, 6, 18).unwrap();
+        assert!(usdc
+            .to_plain_string()
+            .starts_with("0.000333333333333333333333333333333333052364"));
+        assert!(ether
+            .to_plain_string()
+            .starts_with("3000.0000000000000000000000000000025287181822"));
+
+        // WBTC (8) / WETH (18) with bitcoin at 20 ether
+        let sqrt = "35431911422859142059220343232145201";
+        let bitcoin = token_0_price(sqrt, 8, 18).unwrap();
+        let ether = token_1_price(sqrt, 8, 18).unwrap();
+        assert!(bitcoin
+            .to_plain_string()
+            .starts_with("19.99999999999999999999999999999999965407"));
+        assert!(ether
+            .to_plain_string()
+            .starts_with("0.05000000000000000000000000000000000086482"));
+
+        // 2^96 is one raw unit for one raw unit
+        let sqrt = "79228162514264337593543950336";
+        assert_eq!(token_0_price(sqrt, 18, 18).unwrap(), decimal("1"));
+        assert_eq!(
+            token_0_price(sqrt, 18, 6).unwrap(),
+            decimal("1000000000000")
+        );
+        assert_eq!(
+            token_1_price(sqrt, 18, 6).unwrap(),
+            decimal("0.000000000001")
+        );
+
+        assert!(token_0_price("0", 18, 18).is_err());
+        assert!(token_0_price("-1", 18, 18).is_err());
+        assert!(token_0_price("price", 18, 18).is_err());
+    }
+
+    #[test]
+    fn test_sqrt_ratio_at_tick() {
+        let ratio = |tick: i32| sqrt_ratio_at_tick(tick).unwrap().to_string();
+
+        assert_eq!(ratio(-887272), MIN_SQRT_RATIO.to_string());
+        assert_eq!(ratio(887272), MAX_SQRT_RATIO);
+        assert_eq!(ratio(0), "79228162514264337593543950336");
+        assert_eq!(ratio(1), "79232123823359799118286999568");
+        assert_eq!(ratio(-1), "79224201403219477170569942574");
+        assert_eq!(ratio(196256), "1446476584571639225752396618629938");
+
+        assert!(sqrt_ratio_at_tick(887273).is_err());
+        assert!(sqrt_ratio_at_tick(-887273).is_err());
+    }
+
+    #[test]
+    fn test_oracle() {
+        // Ten minutes at tick 200 and 201 on average
+        assert_eq!(mean_tick(1_000, 1_000 + 200 * 600, 600), 200);
+        assert_eq!(mean_tick(0, 201 * 600 + 599, 600), 201);
+
+        // Negative means round down
+        assert_eq!(mean_tick(0, -200 * 600, 600), -200);
+        assert_eq!(mean_tick(0, -200 * 600 - 1, 600), -201);
+
+        // Constant liquidity of 1000 over the window
+        let window = 3_600u32;
+        let start = U256::from(12_345);
+        let end = start + (U256::from(window) << 128) / 1_000;
+        let liquidity = harmonic_mean_liquidity(start, end, window);
+        assert!((999..=1_000).contains(&liquidity));
+
+        // The cumulatives wrap around at uint160
+        let start = (U256::one() << 160) - 10;
+        let end = (U256::from(window) << 128) / 1_000 - 10;
+        assert_eq!(harmonic_mean_liquidity(start, end, window), liquidity);
+    }
+
+    proptest! {
+        #[test]
+        fn test_price_matches_float(sqrt in sqrt_price(), d0 in 0u8..=24, d1 in 0u8..=24) {
+            let price = token_0_price(&sqrt, d0, d1).unwrap().to_f64().unwrap();
+            let expected = (f64::from_str(&sqrt).unwrap() / 2f64.powi(96)).powi(2)
+                * 10f64.powi(d0 as i32 - d1 as i32);
+
+            prop_assert!(((price - expected) / expected).abs() < 1e-9);
+        }
+
+        #[test]
+        fn test_price_is_exact(sqrt in sqrt_price(), d0 in 0u8..=24, d1 in 0u8..=24) {
+            // price * 2^192 gives back sqrtPriceX96² * 10^(d0 - d1)
+            let price = token_0_price(&sqrt, d0, d1).unwrap();
+            let sqrt = BigInt::from_str(&sqrt).unwrap();
+            let expected = BigDecimal::new(&sqrt * &sqrt, d1 as i64 - d0 as i64);
+
+            prop_assert_eq!(price * BigDecimal::from(BigInt::from(2).pow(192)), expected);
+        }
+
+        #[test]
+        fn test_inverse(sqrt in sqrt_price(), d0 in 0u8..=24, d1 in 0u8..=24) {
+            let price_0 = token_0_price(&sqrt, d0, d1).unwrap();
+            let price_1 = token_1_price(&sqrt, d0, d1).unwrap();
+
+            // The product is one up to the rounding of the inverse
+            let error = (&price_0 * &price_1 - BigDecimal::from(1)).abs();
+            prop_assert!(error < BigDecimal::new(1.into(), PRECISION as i64 - 2));
+        }
+
+        #[test]
+        fn test_decimals(sqrt in sqrt_price(), d0 in 0u8..=23, d1 in 0u8..=24) {
+            // One more decimal of token 0 makes a whole token 0 ten times the raw units
+            let price = token_0_price(&sqrt, d0, d1).unwrap();
+            let shifted = token_0_price(&sqrt, d0 + 1, d1).unwrap();
+
+            prop_assert_eq!(shifted, price * BigDecimal::from(10));
+        }
+
+        #[test]
+        fn test_monotonic(a in sqrt_price(), b in sqrt_price()) {
+            let (a, b) = if decimal(&a) <= decimal(&b) { (a, b) } else { (b, a) };
+
+            prop_assert!(token_0_price(&a, 18, 18).unwrap() <= token_0_price(&b, 18, 18).unwrap());
+            prop_assert!(token_1_price(&a, 18, 18).unwrap() >= token_1_price(&b, 18, 18).unwrap());
         }
     }
 }
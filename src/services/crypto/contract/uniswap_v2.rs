@@ -0,0 +1,266 @@
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::BigDecimal;
+use ethers::types::{H160, U256};
+use ethers::utils::hex::ToHexExt;
+use serde::Serialize;
+
+use super::abi::{CaseERC20, CaseUniswapV2Factory, CaseUniswapV2Pair};
+use super::{types::*, Contract, ContractError, ContractResult, Multicall};
+
+/// Basis points of a whole amount
+const BASIS: u32 = 10_000;
+
+/// Significant digits of a price that has no finite decimal expansion
+const PRECISION: u64 = 100;
+
+#[rustfmt::skip]
+#[derive(Serialize)]
+pub struct PairPrice {
+    pub block:                u64,
+    pub reserve_base:         Uint128,
+    pub reserve_quote:        Uint128,
+    // Timestamp of the last block the reserves changed in
+    pub block_timestamp_last: u32,
+    pub base_decimals:        Uint8,
+    pub quote_decimals:       Uint8,
+    // Whole tokens
+    pub quote_per_base:       String,
+    pub base_per_quote:       String,
+}
+
+fn checked(value: Option<U256>) -> ContractResult<U256> {
+    value.ok_or(ContractError::Calculation(
+        "amount overflows uint256".into(),
+    ))
+}
+
+/// UniswapV2Library.getAmountOut with the fee in basis points
+pub fn amount_out(
+    amount_in: U256,
+    reserve_in: U256,
+    reserve_out: U256,
+    fee: u32,
+) -> ContractResult<U256> {
+    if amount_in.is_zero() {
+        return Err(ContractError::Calculation(
+            "insufficient input amount".into(),
+        ));
+    }
+    if reserve_in.is_zero() || reserve_out.is_zero() {
+        return Err(ContractError::Calculation("insufficient liquidity".into()));
+    }
+
+    let with_fee = checked(amount_in.checked_mul(U256::from(BASIS - fee)))?;
+    let numerator = checked(with_fee.checked_mul(reserve_out))?;
+    let denominator = checked(reserve_in.checked_mul(U256::from(BASIS)))?;
+    let denominator = checked(denominator.checked_add(with_fee))?;
+
+    Ok(numerator / denominator)
+}
+
+/// UniswapV2Library.getAmountIn with the fee in basis points
+pub fn amount_in(
+    amount_out: U256,
+    reserve_in: U256,
+    reserve_out: U256,
+    fee: u32,
+) -> ContractResult<U256> {
+    if amount_out.is_zero() {
+        return Err(ContractError::Calculation(
+            "insufficient output amount".into(),
+        ));
+    }
+    if reserve_in.is_zero() || amount_out >= reserve_out {
+        return Err(ContractError::Calculation("insufficient liquidity".into()));
+    }
+
+    let numerator = checked(reserve_in.checked_mul(amount_out))?;
+    let numerator = checked(numerator.checked_mul(U256::from(BASIS)))?;
+    let denominator = checked((reserve_out - amount_out).checked_mul(U256::from(BASIS - fee)))?;
+
+    Ok(numerator / denominator + 1)
+}
+
+/// Amounts along the pairs from the exact input, the reserves are (in, out) of every pair
+pub fn amounts_out(amount: U256, reserves: &[(U256, U256)], fee: u32) -> ContractResult<Vec<U256>> {
+    let mut amounts = vec![amount];
+    for (reserve_in, reserve_out) in reserves {
+        let last = amounts[amounts.len() - 1];
+        amounts.push(amount_out(last, *reserve_in, *reserve_out, fee)?);
+    }
+
+    Ok(amounts)
+}
+
+/// Amounts along the pairs to the exact output, the reserves are (in, out) of every pair
+pub fn amounts_in(amount: U256, reserves: &[(U256, U256)], fee: u32) -> ContractResult<Vec<U256>> {
+    let mut amounts = vec![amount];
+    for (reserve_in, reserve_out) in reserves.iter().rev() {
+        let first = amounts[0];
+        amounts.insert(0, amount_in(first, *reserve_in, *reserve_out, fee)?);
+    }
+
+    Ok(amounts)
+}
+
+/// Whole quote tokens for one whole base token at the reserves
+pub fn pair_price(
+    reserve_base: Uint128,
+    reserve_quote: Uint128,
+    base_decimals: Uint8,
+    quote_decimals: Uint8,
+) -> ContractResult<BigDecimal> {
+    if reserve_base == 0 || reserve_quote == 0 {
+        return Err(ContractError::Calculation("pair has no liquidity".into()));
+    }
+
+    let base = BigDecimal::new(BigInt::from(reserve_base), base_decimals as i64);
+    let quote = BigDecimal::new(BigInt::from(reserve_quote), quote_decimals as i64);
+
+    Ok((quote / base).with_prec(PRECISION).normalized())
+}
+
+/// Uniswap V2 Factory Read Contract Functions
+impl Contract<CaseUniswapV2Factory> {
+    pub async fn get_pair(
+        &self,
+        address_0: &Address,
+        address_1: &Address,
+    ) -> ContractResult<Address> {
+        let token_0 = Self::to_address(address_0)?;
+        let token_1 = Self::to_address(address_1)?;
+
+        let result: H160 =
+            Self::contract_call(self.pin(self.1.get_pair(token_0, token_1)).call().await)?;
+
+        if result.is_zero() {
+            return Err(ContractError::Address(format!(
+                "pair of {} and {} not found",
+                address_0, address_1
+            )));
+        }
+
+        Ok(result.encode_hex_with_prefix())
+    }
+}
+
+/// Uniswap V2 Pair Read Contract Functions
+impl Contract<CaseUniswapV2Pair> {
+    /// Reserves of token 0 and token 1 and the timestamp they last changed at
+    pub async fn get_reserves(&self) -> ContractResult<(Uint128, Uint128, u32)> {
+        let result = Self::contract_call(self.pin(self.1.get_reserves()).call().await)?;
+
+        Ok(result)
+    }
+
+    /// Price of the base in the quote from the reserves, in one round trip
+    pub async fn price(
+        &self,
+        base: &Contract<CaseERC20>,
+        quote: &Contract<CaseERC20>,
+    ) -> ContractResult<PairPrice> {
+        let mut multicall = Multicall::new(self.1.client())?.block(self.2);
+        let reserves = multicall.add(self.1.get_reserves())?;
+        let token_0 = multicall.add(self.1.token_0())?;
+        let token_1 = multicall.add(self.1.token_1())?;
+        let base_decimals = multicall.add(base.1.decimals())?;
+        let quote_decimals = multicall.add(quote.1.decimals())?;
+        let result = multicall.call().await?;
+
+        let (reserve_0, reserve_1, block_timestamp_last) = result.get(&reserves)?;
+        let token_0: Address = result.get(&token_0)?.encode_hex_with_prefix();
+        let token_1: Address = result.get(&token_1)?.encode_hex_with_prefix();
+        let base_decimals = result.get(&base_decimals)?;
+        let quote_decimals = result.get(&quote_decimals)?;
+
+        let (reserve_base, reserve_quote) =
+            if base.is_address_match(&token_0) && quote.is_address_match(&token_1) {
+                (reserve_0, reserve_1)
+            } else if base.is_address_match(&token_1) && quote.is_address_match(&token_0) {
+                (reserve_1, reserve_0)
+            } else {
+                return Err(ContractError::Address(format!(
+                    "pair {} is not of {} and {}",
+                    self.address(),
+                    base.address(),
+                    quote.address()
+                )));
+            };
+
+        let quote_per_base =
+            pair_price(reserve_base, reserve_quote, base_decimals, quote_decimals)?;
+        let base_per_quote =
+            pair_price(reserve_quote, reserve_base, quote_decimals, base_decimals)?;
+
+        Ok(PairPrice {
+            block: result.block,
+            reserve_base,
+            reserve_quote,
+            block_timestamp_last,
+            base_decimals,
+            quote_decimals,
+            quote_per_base: quote_per_base.to_plain_string(),
+            base_per_quote: base_per_quote.to_plain_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bigdecimal::BigDecimal;
+    use ethers::types::U256;
+
+    use super::{amount_in, amount_out, amounts_in, amounts_out, pair_price};
+
+    fn ether(value: u64) -> U256 {
+        U256::from(value) * U256::exp10(18)
+    }
+
+    #[test]
+    fn test_amounts() {
+        // 100 WETH and 200000 USDC
+        let (weth, usdc) = (ether(100), U256::from(200_000_000_000u64));
+
+        let out = amount_out(ether(1), weth, usdc, 30).unwrap();
+        assert_eq!(out, U256::from(1_974_316_068u64));
+        let out = amount_out(ether(1), weth, usdc, 25).unwrap();
+        assert_eq!(out, U256::from(1_975_296_418u64));
+
+        let needed = amount_in(U256::from(1_980_000_000u64), weth, usdc, 30).unwrap();
+        assert_eq!(needed, U256::from(1_002_907_723_270_812_337u64));
+
+        assert!(amount_out(U256::zero(), weth, usdc, 30).is_err());
+        assert!(amount_in(usdc, weth, usdc, 30).is_err());
+        assert!(amount_out(U256::MAX, weth, usdc, 30).is_err());
+
+        // WETH to USDC to DAI with 1000000 USDC and DAI
+        let (usdc_pair, dai) = (U256::from(1_000_000_000_000u64), ether(1_000_000));
+        let reserves = [(weth, usdc), (usdc_pair, dai)];
+
+        let amounts = amounts_out(ether(1), &reserves, 30).unwrap();
+        assert_eq!(amounts[1], U256::from(1_974_316_068u64));
+        assert_eq!(
+            amounts[2],
+            U256::from_dec_str("1964526160018959424535").unwrap()
+        );
+
+        let amounts = amounts_in(ether(1_000), &reserves, 30).unwrap();
+        assert_eq!(amounts[1], U256::from(1_004_013_041u64));
+        assert_eq!(amounts[0], U256::from(506_057_513_430_044_423u64));
+        assert_eq!(amounts[2], ether(1_000));
+    }
+
+    #[test]
+    fn test_pair_price() {
+        let weth = 100 * 10u128.pow(18);
+        let usdc = 200_000 * 10u128.pow(6);
+
+        let price = pair_price(weth, usdc, 18, 6).unwrap();
+        assert_eq!(price, BigDecimal::from(2000));
+
+        let price = pair_price(usdc, weth, 6, 18).unwrap();
+        assert_eq!(price.to_plain_string(), "0.0005");
+
+        assert!(pair_price(0, usdc, 18, 6).is_err());
+    }
+}
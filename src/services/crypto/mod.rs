@@ -1,2 +1,5 @@
+pub mod block;
 pub mod chain;
 pub mod contract;
+pub mod quote;
+pub mod rpc;
@@ -0,0 +1,88 @@
+use std::str::FromStr;
+
+use bigdecimal::{BigDecimal, Zero};
+
+use super::chain::BlockChain;
+use super::contract::types::Address;
+use super::contract::{ContractError, ContractResult};
+
+/// Significant digits of the prices of a quote
+const PRECISION: u64 = 36;
+
+/// Whole output tokens for one input token of a swap along a path
+#[rustfmt::skip]
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuotePrices {
+    // As swapped
+    pub price:        String,
+    // At the spot prices of the hops
+    pub spot_price:   String,
+    // Fraction of the spot price lost to the depth and the fees, 0.01 is 1%
+    pub price_impact: String,
+}
+
+/// Tokens of a comma separated path of addresses or symbols, from the input token
+pub fn parse_path(chain: &BlockChain, path: &str) -> ContractResult<Vec<Address>> {
+    let tokens = path
+        .split(',')
+        .map(|v| chain.lookup_or_validate_address(v.trim().to_string()))
+        .collect::<ContractResult<Vec<_>>>()?;
+
+    if tokens.len() < 2 {
+        return Err(ContractError::Address("path needs two tokens".into()));
+    }
+
+    Ok(tokens)
+}
+
+/// Prices of a quote from the spot price of every hop and the whole token
+/// amounts it swaps
+pub fn quote_prices(
+    spot_prices: &[&str],
+    amount_in: &str,
+    amount_out: &str,
+) -> ContractResult<QuotePrices> {
+    let mut spot_price = BigDecimal::from(1);
+    for price in spot_prices.iter() {
+        spot_price = spot_price * BigDecimal::from_str(price)?;
+    }
+    let spot_price = spot_price.with_prec(PRECISION).normalized();
+
+    let spent = BigDecimal::from_str(amount_in)?;
+    if spent.is_zero() {
+        return Err(ContractError::Calculation("quote spends no input".into()));
+    }
+    if spot_price.is_zero() {
+        return Err(ContractError::Calculation("path has no spot price".into()));
+    }
+
+    let price = (BigDecimal::from_str(amount_out)? / spent)
+        .with_prec(PRECISION)
+        .normalized();
+    let price_impact = ((&spot_price - &price) / &spot_price)
+        .with_prec(PRECISION)
+        .normalized();
+
+    Ok(QuotePrices {
+        price: price.to_plain_string(),
+        spot_price: spot_price.to_plain_string(),
+        price_impact: price_impact.to_plain_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::quote_prices;
+
+    #[test]
+    fn test_quote_prices() {
+        // 2000 USDC per ETH then 0.5 DAI per USDC, 1 ETH swaps to 990 DAI
+        let prices = quote_prices(&["2000", "0.5"], "1", "990").unwrap();
+
+        assert_eq!(prices.spot_price, "1000");
+        assert_eq!(prices.price, "990");
+        assert_eq!(prices.price_impact, "0.01");
+
+        assert!(quote_prices(&["2000"], "0", "1").is_err());
+    }
+}
@@ -3,3 +3,6 @@ pub mod crypto;
 
 #[cfg(feature = "service-binance")]
 pub mod binance;
+
+#[cfg(feature = "service-webhook")]
+pub mod webhook;
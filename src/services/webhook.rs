@@ -0,0 +1,813 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::Mutex;
+
+use crate::time::timestamp;
+
+/// Attempts of one delivery before it is given up
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Longest wait for a held outbox entry to be amended, a crash in between
+/// delivers it as it was queued
+const HOLD: Duration = Duration::from_secs(30);
+
+/// Wait before the first retry, doubled after each failed attempt
+const BACKOFF: Duration = Duration::from_secs(1);
+
+pub const HEADER_EVENT: &str = "X-Harmony-Event";
+pub const HEADER_TIMESTAMP: &str = "X-Harmony-Timestamp";
+pub const HEADER_SIGNATURE: &str = "X-Harmony-Signature";
+pub const HEADER_DELIVERY: &str = "X-Harmony-Delivery";
+
+/// Sequence appended to the delivery ids created within the same nanosecond
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug)]
+pub enum WebhookError {
+    Delivery(String),
+    Target(String),
+}
+
+impl Error for WebhookError {}
+impl std::fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::Delivery(e) => format!("DELIVERY {}", e),
+            Self::Target(e) => format!("TARGET {}", e),
+        };
+
+        write!(f, "WEBHOOK {}", message)
+    }
+}
+
+/// Receiver of the events, with a secret the payloads are signed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    #[serde(default, skip_serializing)]
+    pub secret: Option<String>,
+}
+
+pub struct WebhookClient {
+    http: reqwest::Client,
+    backoff: Duration,
+    // Connect only to public addresses, the endpoints are given by the users
+    public: bool,
+}
+
+impl WebhookClient {
+    /// Client of the endpoints configured by the operator
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Self::build(false)
+    }
+
+    /// Client of the endpoints given by the users, the addresses are checked
+    /// when the connection is made so a host resolving elsewhere later is
+    /// refused too. `allow_private` lifts the check, such as for a local receiver
+    pub fn public(allow_private: bool) -> Result<Self, Box<dyn Error>> {
+        Self::build(!allow_private)
+    }
+
+    fn build(public: bool) -> Result<Self, Box<dyn Error>> {
+        // A redirect could lead a checked target to any other host
+        let mut http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none());
+        if public {
+            http = http.dns_resolver(Arc::new(PublicResolver));
+        }
+
+        Ok(Self {
+            http: http.build()?,
+            backoff: BACKOFF,
+            public,
+        })
+    }
+
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+
+        self
+    }
+
+    /// POST the payload as JSON until the receiver answers with a 2xx status.
+    /// The signature is the hex HMAC SHA256 of `{timestamp}.{body}`
+    pub async fn deliver<T>(
+        &self,
+        webhook: &Webhook,
+        event: &str,
+        payload: &T,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        T: Serialize,
+    {
+        let body = serde_json::to_string(payload)?;
+        let delivery = delivery_id();
+        let mut backoff = self.backoff;
+        let mut last_error = String::new();
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            match self.send(webhook, event, &delivery, &body).await {
+                Ok(()) => return Ok(()),
+                Err(e) => last_error = e.to_string(),
+            }
+
+            tracing::warn!(
+                "webhook {} attempt {} failed: {}",
+                webhook.url,
+                attempt,
+                last_error
+            );
+
+            if attempt < MAX_ATTEMPTS {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+
+        Err(Box::new(WebhookError::Delivery(format!(
+            "{} after {} attempts: {}",
+            webhook.url, MAX_ATTEMPTS, last_error
+        ))))
+    }
+
+    /// One attempt of a delivery, receivers deduplicate retries by the delivery id
+    pub async fn send(
+        &self,
+        webhook: &Webhook,
+        event: &str,
+        delivery: &str,
+        body: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        // Address literals never reach the resolver
+        if self.public {
+            check_literal(&webhook.url)?;
+        }
+
+        let time = timestamp().as_millis().to_string();
+
+        let mut request = self
+            .http
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header(HEADER_EVENT, event)
+            .header(HEADER_DELIVERY, delivery)
+            .header(HEADER_TIMESTAMP, &time)
+            .body(body.to_string());
+
+        if let Some(secret) = &webhook.secret {
+            request = request.header(HEADER_SIGNATURE, sign(secret, &time, body)?);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(Box::new(WebhookError::Delivery(format!(
+                "{} status {}",
+                webhook.url,
+                response.status()
+            ))));
+        }
+
+        Ok(())
+    }
+}
+
+/// Resolves the hosts of the user endpoints, refused when any of their
+/// addresses is not public
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if let Some(v) = addresses.iter().find(|v| !is_public(&v.ip())) {
+                let e = WebhookError::Target(format!("{} resolves to {}", host, v.ip()));
+                return Err(e.into());
+            }
+
+            let addresses: reqwest::dns::Addrs = Box::new(addresses.into_iter());
+            Ok(addresses)
+        })
+    }
+}
+
+/// Check a webhook url of the users before it is saved, only public http(s)
+/// endpoints receive their events unless `allow_private`
+pub async fn check_target(url: &str, allow_private: bool) -> Result<(), Box<dyn Error>> {
+    let target = |e: &str| Box::new(WebhookError::Target(format!("{} {}", url, e)));
+
+    let parsed = reqwest::Url::parse(url).map_err(|e| target(&e.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(target("must be http(s)"));
+    }
+    if allow_private {
+        return Ok(());
+    }
+
+    let (host, port) = host_of(&parsed).ok_or_else(|| target("has no host"))?;
+    let addresses: Vec<IpAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| target(&e.to_string()))?
+        .map(|v| v.ip())
+        .collect();
+    if addresses.is_empty() {
+        return Err(target("does not resolve"));
+    }
+    if let Some(v) = addresses.iter().find(|v| !is_public(v)) {
+        return Err(target(&format!("resolves to the non public address {}", v)));
+    }
+
+    Ok(())
+}
+
+/// Refuse a url whose host is a non public address
+fn check_literal(url: &str) -> Result<(), WebhookError> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| WebhookError::Target(e.to_string()))?;
+    let ip = host_of(&parsed).and_then(|(host, _)| host.parse::<IpAddr>().ok());
+
+    match ip {
+        Some(v) if !is_public(&v) => Err(WebhookError::Target(format!(
+            "{} is the non public address {}",
+            url, v
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn host_of(url: &reqwest::Url) -> Option<(&str, u16)> {
+    let host = url
+        .host_str()?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+
+    Some((host, url.port_or_known_default().unwrap_or(80)))
+}
+
+/// Whether the address is reachable from the internet
+pub fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v) => {
+            let [a, b, ..] = v.octets();
+
+            !(v.is_private()
+                || v.is_loopback()
+                || v.is_link_local()
+                || v.is_unspecified()
+                || v.is_broadcast()
+                || v.is_multicast()
+                || v.is_documentation()
+                // This network 0.0.0.0/8 and the shared address space 100.64.0.0/10
+                || a == 0
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(v) => match v.to_ipv4_mapped() {
+            Some(v) => is_public(&IpAddr::V4(v)),
+            None => {
+                let segment = v.segments()[0];
+
+                // Unique local fc00::/7 and link local fe80::/10
+                !(v.is_loopback()
+                    || v.is_unspecified()
+                    || v.is_multicast()
+                    || (segment & 0xfe00) == 0xfc00
+                    || (segment & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Unique and time ordered
+fn delivery_id() -> String {
+    let sequence = SEQUENCE.fetch_add(1, Ordering::SeqCst) % 1_000_000;
+
+    format!("{:020}{:06}", timestamp().as_nanos(), sequence)
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub id:       String,
+    pub url:      String,
+    pub event:    String,
+    pub body:     String,
+    pub created:  u128,
+    pub attempts: u32,
+    // Not delivered before it is amended or held for too long
+    #[serde(default)]
+    pub held:     bool,
+}
+
+/// Events waiting for their delivery, one JSON file per event and endpoint.
+/// A file is removed only after the endpoint accepted it, so every event is
+/// delivered at least once, also across restarts. The files are written off
+/// the runtime threads
+pub struct Outbox {
+    path: PathBuf,
+    webhooks: Vec<Webhook>,
+    lock: Mutex<()>,
+}
+
+impl Outbox {
+    pub fn new(path: impl Into<PathBuf>, webhooks: Vec<Webhook>) -> Self {
+        Self {
+            path: path.into().join("outbox"),
+            webhooks,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Endpoints from a JSON file holding a list of webhooks
+    pub fn from_file(
+        path: impl Into<PathBuf>,
+        config: impl Into<PathBuf>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let webhooks = serde_json::from_reader(File::open(config.into())?)?;
+
+        Ok(Self::new(path, webhooks))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.webhooks.is_empty()
+    }
+
+    /// Persist the event for every endpoint, returns the ids of the entries
+    pub async fn push<T>(&self, event: &str, payload: &T) -> Result<Vec<String>, Box<dyn Error>>
+    where
+        T: Serialize,
+    {
+        self.queue(event, payload, false).await
+    }
+
+    /// Persist the event like `push`, its entries wait for `amend` before
+    /// they are delivered
+    pub async fn hold<T>(&self, event: &str, payload: &T) -> Result<Vec<String>, Box<dyn Error>>
+    where
+        T: Serialize,
+    {
+        self.queue(event, payload, true).await
+    }
+
+    async fn queue<T>(
+        &self,
+        event: &str,
+        payload: &T,
+        held: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>>
+    where
+        T: Serialize,
+    {
+        let body = serde_json::to_string(payload)?;
+        let entries: Vec<OutboxEntry> = self
+            .webhooks
+            .iter()
+            .map(|v| OutboxEntry {
+                id: delivery_id(),
+                url: v.url.clone(),
+                event: event.into(),
+                body: body.clone(),
+                created: timestamp().as_millis(),
+                attempts: 0,
+                held,
+            })
+            .collect();
+        let ids = entries.iter().map(|v| v.id.clone()).collect();
+
+        let _guard = self.lock.lock().await;
+        let path = self.path.clone();
+        blocking(move || {
+            fs::create_dir_all(&path)?;
+            for entry in entries.iter() {
+                write(&path, entry)?;
+            }
+
+            Ok(())
+        })
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Replace the payload of the entries not delivered yet and release the
+    /// held ones, the delivered ones keep what their endpoint received
+    pub async fn amend<T>(&self, ids: &[String], payload: &T) -> Result<(), Box<dyn Error>>
+    where
+        T: Serialize,
+    {
+        let body = serde_json::to_string(payload)?;
+        let ids = ids.to_vec();
+
+        let _guard = self.lock.lock().await;
+        let path = self.path.clone();
+        blocking(move || {
+            for id in ids.iter() {
+                if let Some(mut entry) = read(&path, id)? {
+                    entry.body = body.clone();
+                    entry.held = false;
+                    write(&path, &entry)?;
+                }
+            }
+
+            Ok(())
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Pending entries, oldest first
+    pub async fn pending(&self) -> io::Result<Vec<OutboxEntry>> {
+        let _guard = self.lock.lock().await;
+        let path = self.path.clone();
+
+        blocking(move || {
+            let entries = match fs::read_dir(&path) {
+                Ok(v) => v,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(e) => return Err(e),
+            };
+
+            let mut pending = Vec::new();
+            for entry in entries {
+                let path = entry?.path();
+                if path.extension().is_some_and(|v| v == "json") {
+                    match serde_json::from_slice(&fs::read(&path)?) {
+                        Ok(v) => pending.push(v),
+                        Err(e) => tracing::error!("outbox entry {:?} unreadable: {}", path, e),
+                    }
+                }
+            }
+            pending.sort_by(|a: &OutboxEntry, b| a.id.cmp(&b.id));
+
+            Ok(pending)
+        })
+        .await
+    }
+
+    async fn remove(&self, entry: &OutboxEntry) -> io::Result<()> {
+        let _guard = self.lock.lock().await;
+        let path = self.path.join(format!("{}.json", entry.id));
+
+        blocking(move || match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        })
+        .await
+    }
+
+    /// Count the failed attempt on the stored entry, its payload may have been amended
+    async fn retry(&self, entry: &OutboxEntry) -> io::Result<()> {
+        let _guard = self.lock.lock().await;
+        let (path, id) = (self.path.clone(), entry.id.clone());
+
+        blocking(move || match read(&path, &id)? {
+            Some(mut entry) => {
+                entry.attempts += 1;
+                write(&path, &entry)
+            }
+            None => Ok(()),
+        })
+        .await
+    }
+
+    /// Try every pending entry once, an endpoint that failed or waits for a
+    /// held entry is skipped until the next flush to keep its events in order.
+    /// Returns the delivered count
+    pub async fn flush(&self, client: &WebhookClient) -> io::Result<usize> {
+        let mut delivered = 0;
+        let mut failed: Vec<String> = Vec::new();
+        let now = timestamp().as_millis();
+
+        for entry in self.pending().await? {
+            if failed.contains(&entry.url) {
+                continue;
+            }
+
+            if entry.held && now < entry.created + HOLD.as_millis() {
+                failed.push(entry.url);
+                continue;
+            }
+
+            // Endpoints removed from the configuration keep their entries
+            let webhook = match self.webhooks.iter().find(|v| v.url == entry.url) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            match client
+                .send(webhook, &entry.event, &entry.id, &entry.body)
+                .await
+            {
+                Ok(()) => {
+                    self.remove(&entry).await?;
+                    delivered += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "outbox {} attempt {} failed: {}",
+                        entry.id,
+                        entry.attempts + 1,
+                        e
+                    );
+                    self.retry(&entry).await?;
+                    failed.push(entry.url);
+                }
+            }
+        }
+
+        Ok(delivered)
+    }
+}
+
+async fn blocking<T, F>(work: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(io::Error::other)?
+}
+
+/// Stored entry of the id, none once it was delivered
+fn read(path: &Path, id: &str) -> io::Result<Option<OutboxEntry>> {
+    match fs::read(path.join(format!("{}.json", id))) {
+        Ok(v) => Ok(Some(serde_json::from_slice(&v)?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Written to a temporary file first, a crash never leaves half an entry
+fn write(path: &Path, entry: &OutboxEntry) -> io::Result<()> {
+    let temp = path.join(format!("{}.tmp", entry.id));
+
+    let mut file = File::create(&temp)?;
+    file.write_all(serde_json::to_string(entry)?.as_bytes())?;
+    file.sync_all()?;
+
+    fs::rename(temp, path.join(format!("{}.json", entry.id)))
+}
+
+/// Flush the outbox on every interval, the wait doubles while an endpoint
+/// keeps failing up to the maximum backoff
+pub fn spawn(outbox: Arc<Outbox>, interval: Duration) {
+    tokio::spawn(async move {
+        let client = match WebhookClient::new() {
+            Ok(v) => v,
+            Err(e) => {
+                tracing::error!("outbox failed to start: {}", e);
+                return;
+            }
+        };
+
+        let max = interval * 2u32.pow(MAX_ATTEMPTS);
+        let mut wait = interval;
+        loop {
+            tokio::time::sleep(wait).await;
+
+            wait = match outbox.flush(&client).await {
+                Ok(_) if outbox.pending().await.is_some_and(|v| v.is_empty()) => interval,
+                Ok(_) => (wait * 2).min(max),
+                Err(e) => {
+                    tracing::error!("outbox flush failed: {}", e);
+                    (wait * 2).min(max)
+                }
+            };
+        }
+    });
+}
+
+pub fn sign(secret: &str, timestamp: &str, body: &str) -> Result<String, Box<dyn Error>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|e| e.to_string())?;
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+
+    use super::{
+        check_target, is_public, sign, Outbox, Webhook, WebhookClient, HEADER_DELIVERY,
+        HEADER_SIGNATURE, HEADER_TIMESTAMP,
+    };
+
+    #[test]
+    fn test_is_public() {
+        let public = |v: &str| is_public(&v.parse().unwrap());
+
+        assert!(public("8.8.8.8"));
+        assert!(public("2606:4700::1111"));
+
+        assert!(!public("127.0.0.1"));
+        assert!(!public("10.1.2.3"));
+        assert!(!public("172.16.0.1"));
+        assert!(!public("192.168.1.1"));
+        assert!(!public("169.254.169.254"));
+        assert!(!public("100.64.0.1"));
+        assert!(!public("0.0.0.0"));
+        assert!(!public("::1"));
+        assert!(!public("::"));
+        assert!(!public("fd00::1"));
+        assert!(!public("fe80::1"));
+        assert!(!public("::ffff:127.0.0.1"));
+    }
+
+    #[test]
+    fn test_check_target() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            assert!(check_target("http://8.8.8.8/hook", false).await.is_ok());
+
+            assert!(check_target("ftp://8.8.8.8/hook", false).await.is_err());
+            assert!(check_target("http://127.0.0.1:8080/hook", false)
+                .await
+                .is_err());
+            assert!(check_target("http://[::1]/hook", false).await.is_err());
+            assert!(check_target("http://169.254.169.254/latest", false)
+                .await
+                .is_err());
+            assert!(check_target("http://localhost/hook", false).await.is_err());
+
+            // A local receiver when allowed
+            assert!(check_target("http://localhost/hook", true).await.is_ok());
+            assert!(check_target("ftp://localhost/hook", true).await.is_err());
+        });
+    }
+
+    #[test]
+    fn test_public_client() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            let router = axum::Router::new().route("/hook", post(|| async { StatusCode::OK }));
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            tokio::spawn(async move { axum::serve(listener, router).await });
+
+            let send = |client: WebhookClient, host: &str| {
+                let webhook = Webhook {
+                    url: format!("http://{}:{}/hook", host, port),
+                    secret: None,
+                };
+                async move { client.send(&webhook, "test", "1", "{}").await }
+            };
+
+            // Refused when connecting, by name or by address
+            let public = || WebhookClient::public(false).unwrap();
+            assert!(send(public(), "localhost").await.is_err());
+            assert!(send(public(), "127.0.0.1").await.is_err());
+
+            let private = || WebhookClient::public(true).unwrap();
+            assert!(send(private(), "localhost").await.is_ok());
+            assert!(send(private(), "127.0.0.1").await.is_ok());
+        });
+    }
+
+    #[test]
+    fn test_deliver_with_retry() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            // Local receiver failing the first attempt
+            let attempts = Arc::new(AtomicU32::new(0));
+            let received = Arc::new(Mutex::new(None));
+
+            let router = {
+                let attempts = attempts.clone();
+                let received = received.clone();
+
+                axum::Router::new().route(
+                    "/hook",
+                    post(move |headers: HeaderMap, body: String| async move {
+                        if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                            return StatusCode::INTERNAL_SERVER_ERROR;
+                        }
+
+                        let header = |name| headers[name].to_str().unwrap().to_string();
+                        *received.lock().unwrap() =
+                            Some((header(HEADER_TIMESTAMP), header(HEADER_SIGNATURE), body));
+
+                        StatusCode::OK
+                    }),
+                )
+            };
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, router).await });
+
+            let webhook = Webhook {
+                url: format!("http://{}/hook", address),
+                secret: Some("secret".into()),
+            };
+            let client = WebhookClient::new()
+                .unwrap()
+                .with_backoff(Duration::from_millis(10));
+
+            client
+                .deliver(&webhook, "test", &serde_json::json!({"price": "1.5"}))
+                .await
+                .unwrap();
+
+            assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+            let (time, signature, body) = received.lock().unwrap().clone().unwrap();
+            assert_eq!(body, r#"{"price":"1.5"}"#);
+            assert_eq!(signature, sign("secret", &time, &body).unwrap());
+        });
+    }
+
+    #[test]
+    fn test_outbox() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime.block_on(async {
+            // Local receiver failing the first attempt, records the delivery ids
+            let attempts = Arc::new(AtomicU32::new(0));
+            let received = Arc::new(Mutex::new(Vec::new()));
+
+            let router = {
+                let attempts = attempts.clone();
+                let received = received.clone();
+
+                axum::Router::new().route(
+                    "/hook",
+                    post(move |headers: HeaderMap| async move {
+                        if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                            return StatusCode::SERVICE_UNAVAILABLE;
+                        }
+
+                        let delivery = headers[HEADER_DELIVERY].to_str().unwrap().to_string();
+                        received.lock().unwrap().push(delivery);
+
+                        StatusCode::OK
+                    }),
+                )
+            };
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, router).await });
+
+            let path = std::env::temp_dir().join(format!("harmony-outbox-{}", address.port()));
+            let webhook = Webhook {
+                url: format!("http://{}/hook", address),
+                secret: Some("secret".into()),
+            };
+            let outbox = Outbox::new(&path, vec![webhook]);
+            let client = WebhookClient::new().unwrap();
+
+            let first = outbox.push("test", &1).await.unwrap();
+            outbox.push("test", &2).await.unwrap();
+            let pending = outbox.pending().await.unwrap();
+            assert_eq!(pending.len(), 2);
+
+            // The failed endpoint waits for the next flush, the order is kept
+            assert_eq!(outbox.flush(&client).await.unwrap(), 0);
+            assert_eq!(outbox.pending().await.unwrap()[0].attempts, 1);
+
+            // An amended entry keeps its attempts
+            outbox.amend(&first, &3).await.unwrap();
+            let amended = outbox.pending().await.unwrap().remove(0);
+            assert_eq!((amended.body.as_str(), amended.attempts), ("3", 1));
+
+            assert_eq!(outbox.flush(&client).await.unwrap(), 2);
+            assert!(outbox.pending().await.unwrap().is_empty());
+
+            // A held entry waits for its amend, the later ones behind it
+            let held = outbox.hold("test", &4).await.unwrap();
+            outbox.push("test", &5).await.unwrap();
+            assert_eq!(outbox.flush(&client).await.unwrap(), 0);
+            outbox.amend(&held, &6).await.unwrap();
+            assert_eq!(outbox.flush(&client).await.unwrap(), 2);
+
+            let ids: Vec<String> = pending.into_iter().map(|v| v.id).collect();
+            assert_eq!(received.lock().unwrap()[..2], ids);
+
+            std::fs::remove_dir_all(path).unwrap();
+        });
+    }
+}